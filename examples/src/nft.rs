@@ -20,7 +20,7 @@ async fn main() {
     .unwrap();
     println!("new_default_meta outcome: {:#?}", outcome);
 
-    let deposit = 10000000000000000000000;
+    let deposit = NearBalance::from_millinear(10).unwrap();
     let outcome = call(
         &signer,
         contract_id.clone(),
@@ -51,9 +51,10 @@ async fn main() {
     .await
     .unwrap();
 
+    let metadata: serde_json::Value = call_result.json().unwrap();
     println!(
         "--------------\n{}",
-        serde_json::to_string_pretty(&call_result).unwrap()
+        serde_json::to_string_pretty(&metadata).unwrap()
     );
 
     println!("Dev Account ID: {}", contract_id);
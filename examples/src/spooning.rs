@@ -1,19 +1,26 @@
+use serde_json::json;
 use std::convert::TryInto;
 
 use workspaces::borsh::{self, BorshDeserialize, BorshSerialize};
-use workspaces::{AccountId, InMemorySigner};
+use workspaces::{
+    AccountId, AllowDevAccountCreation, Contract, Network, Sandbox, Testnet,
+    TopLevelAccountCreator, Worker,
+};
 
 const STATUS_MSG_WASM_FILEPATH: &str = "./examples/res/status_message.wasm";
 
-/// This is the cached contract_id from running `deploy_testnet` the first time. Used so we don't
-/// overload testnet and have to go through a couple more cycles than we have to, to showcase spooning.
+/// This is the cached contract_id from running `deploy_status_contract` on testnet the first
+/// time. Used so we don't overload testnet and have to go through a couple more cycles than
+/// we have to, to showcase spooning.
 ///
 /// If you'd like a different account to deploy it to, run the following:
 /// ```norun
-/// #[workspaces::main(testnet)]
-/// async fn deploy_testnet() {
-///     let (contract_id, _) = deploy_status_contract("hello from testnet").await;
-///     println!("{}", contract_id);
+/// #[tokio::main]
+/// async fn deploy_testnet() -> anyhow::Result<()> {
+///     let worker = Worker::new(Testnet::new()?);
+///     let contract = deploy_status_contract(&worker, "hello from testnet").await?;
+///     println!("{}", contract.id());
+///     Ok(())
 /// }
 /// ```
 const TESTNET_PREDEPLOYED_CONTRACT_ID: &str = "dev-20211013002148-59466083160385";
@@ -38,102 +45,71 @@ struct StatusMessage {
 ///
 /// For example, our predeployed testnet contract has already done this:
 ///    set_status(TESTNET_PREDEPLOYED_CONTRACT_ID) = "hello from testnet"
-async fn deploy_status_contract(msg: &str) -> (AccountId, InMemorySigner) {
-    let (contract_id, signer) = workspaces::dev_deploy(STATUS_MSG_WASM_FILEPATH)
-        .await
-        .unwrap();
+async fn deploy_status_contract<T>(worker: &Worker<T>, msg: &str) -> anyhow::Result<Contract>
+where
+    T: Network + TopLevelAccountCreator + AllowDevAccountCreation,
+{
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
 
     // This will `call` into `set_status` with the message we want to set.
-    workspaces::call(
-        // The signer of the transaction:
-        &signer,
-        // The signer id:
-        contract_id.clone(),
-        // The contract we want to call into:
-        contract_id.clone(),
-        // The method we want to call:
-        "set_status".into(),
-        // The set of arguments we want to pass:
-        serde_json::json!({
-            "message": msg,
-        })
-        .to_string()
-        .into_bytes(),
-        // No need to worry about this one. Not transferring any tokens, so None:
-        None,
-    )
-    .await
-    .unwrap();
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": msg }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
 
-    (contract_id, signer)
+    Ok(contract)
 }
 
-#[workspaces::basic]
+#[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Grab STATE from the testnet status_message contract. This contract contains the following data:
     //   get_status(dev-20211013002148-59466083160385) => "hello from testnet"
-    let (testnet_contract_id, status_msg) = workspaces::with_testnet(async {
-        let contract_id: AccountId = TESTNET_PREDEPLOYED_CONTRACT_ID
-            .to_string()
-            .try_into()
-            .unwrap();
-
-        let mut state_items = workspaces::view_state(contract_id.clone(), None)
-            .await
-            .unwrap();
-        let state = state_items.remove("STATE").unwrap();
-        let status_msg: StatusMessage =
-            StatusMessage::try_from_slice(&state).expect("Expected to retrieve state");
-
-        (contract_id, status_msg)
-    })
-    .await?;
-
-    // Patch testnet STATE into our local sandboxed status_message contract
-    workspaces::with_sandbox(async move {
-        // Deploy with the following status_message state: sandbox_contract_id => "hello from sandbox"
-        let (sandbox_contract_id, _) = deploy_status_contract("hello from sandbox").await;
-
-        // Patch our testnet STATE into our local sandbox:
-        let _outcome = workspaces::patch_state(
-            sandbox_contract_id.clone(),
+    let testnet = Worker::new(Testnet::new()?);
+    let testnet_contract_id: AccountId = TESTNET_PREDEPLOYED_CONTRACT_ID
+        .to_string()
+        .try_into()
+        .unwrap();
+    let status_msg: StatusMessage = testnet
+        .view_state(testnet_contract_id.clone(), None)
+        .await?
+        .get_borsh("STATE")?;
+
+    // Deploy with the following status_message state: sandbox_contract_id => "hello from sandbox"
+    let sandbox = Worker::new(Sandbox::new()?);
+    let sandbox_contract = deploy_status_contract(&sandbox, "hello from sandbox").await?;
+
+    // Patch our testnet STATE into our local sandbox:
+    sandbox
+        .patch_state(
+            sandbox_contract.id().clone(),
             "STATE".to_string(),
             &status_msg,
         )
         .await
-        .unwrap();
+        .map_err(anyhow::Error::msg)?;
 
-        // Now grab the state to see that it has indeed been patched:
-        let result = workspaces::view(
-            sandbox_contract_id.clone(),
-            "get_status".into(),
-            serde_json::json!({
-                "account_id": testnet_contract_id.to_string(),
-            })
-            .to_string()
-            .into_bytes()
-            .into(),
-        )
+    // Now grab the state to see that it has indeed been patched:
+    let status: String = sandbox_contract
+        .view("get_status")
+        .args_json(json!({ "account_id": testnet_contract_id }))
+        .fetch()
         .await
-        .unwrap();
-
-        let status: String = serde_json::from_value(result).unwrap();
-        assert_eq!(status, "hello from testnet".to_string());
-
-        // See that sandbox state was overriden. Grabbing get_status(sandbox_contract_id) should yield Null
-        let result = workspaces::view(
-            sandbox_contract_id.clone(),
-            "get_status".into(),
-            serde_json::json!({
-                "account_id": sandbox_contract_id.to_string(),
-            })
-            .to_string()
-            .into_bytes()
-            .into(),
-        )
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "hello from testnet".to_string());
+
+    // See that sandbox state was overriden. Grabbing get_status(sandbox_contract_id) should yield Null
+    let status: Option<String> = sandbox_contract
+        .view("get_status")
+        .args_json(json!({ "account_id": sandbox_contract.id() }))
+        .fetch()
         .await
-        .unwrap();
-        assert_eq!(result, serde_json::Value::Null);
-    })
-    .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, None);
+
+    Ok(())
 }
@@ -36,8 +36,6 @@ async fn main() {
     .await
     .unwrap();
 
-    println!(
-        "status: {:?}",
-        serde_json::to_string_pretty(&result).unwrap()
-    );
+    let status: String = result.json().unwrap();
+    println!("status: {:?}", status);
 }
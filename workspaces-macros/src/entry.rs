@@ -2,12 +2,44 @@ use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::{quote, quote_spanned, ToTokens};
 
+#[derive(PartialEq)]
 enum Flavor {
     Sandbox,
     Testnet,
     Mainnet,
 }
 
+impl Flavor {
+    fn name(&self) -> &'static str {
+        match self {
+            Flavor::Sandbox => "sandbox",
+            Flavor::Testnet => "testnet",
+            Flavor::Mainnet => "mainnet",
+        }
+    }
+}
+
+/// Parse a duration such as `120s`, `2m` or `500ms` into milliseconds.
+fn parse_duration(lit: &syn::LitStr) -> Result<u64, syn::Error> {
+    let value = lit.value();
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value.as_str(), ""), |i| value.split_at(i));
+    let scale = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        _ => {
+            let msg = "expected a duration such as \"500ms\", \"120s\" or \"2m\"";
+            return Err(syn::Error::new_spanned(lit, msg));
+        }
+    };
+    number
+        .parse::<u64>()
+        .map(|number| number * scale)
+        .map_err(|_| syn::Error::new_spanned(lit, "expected a whole number before the unit"))
+}
+
 fn parse_knobs(
     mut input: syn::ItemFn,
     args: syn::AttributeArgs,
@@ -18,7 +50,9 @@ fn parse_knobs(
         return Err(syn::Error::new_spanned(input.sig.fn_token, msg));
     }
 
-    let mut flavor = None;
+    let mut flavors = Vec::new();
+    let mut timeout = None;
+    let mut flaky_retries = 0usize;
     for arg in args {
         match arg {
             syn::NestedMeta::Meta(syn::Meta::NameValue(namevalue)) => {
@@ -30,8 +64,31 @@ fn parse_knobs(
                     })?
                     .to_string();
 
-                let msg = format!("Not expected to received a value for: {}", ident.as_str());
-                return Err(syn::Error::new_spanned(namevalue, msg));
+                match (ident.as_str(), &namevalue.lit) {
+                    ("timeout", syn::Lit::Str(lit)) => timeout = Some(parse_duration(lit)?),
+                    ("flaky_retries", syn::Lit::Int(lit)) if is_test => {
+                        flaky_retries = lit.base10_parse()?;
+                    }
+                    ("flaky_retries", _) if !is_test => {
+                        let msg = "only tests can be retried";
+                        return Err(syn::Error::new_spanned(namevalue, msg));
+                    }
+                    ("timeout", _) => {
+                        let msg = "expected a duration such as `timeout = \"120s\"`";
+                        return Err(syn::Error::new_spanned(namevalue, msg));
+                    }
+                    ("flaky_retries", _) => {
+                        let msg = "expected a number such as `flaky_retries = 2`";
+                        return Err(syn::Error::new_spanned(namevalue, msg));
+                    }
+                    _ => {
+                        let msg = format!(
+                            "Unknown attribute {} is specified; expected one of: `timeout`, `flaky_retries`",
+                            ident
+                        );
+                        return Err(syn::Error::new_spanned(namevalue, msg));
+                    }
+                }
             }
             syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
                 let name = path
@@ -39,21 +96,20 @@ fn parse_knobs(
                     .ok_or_else(|| syn::Error::new_spanned(&path, "Must have specified ident"))?
                     .to_string()
                     .to_lowercase();
-                match name.as_str() {
-                    "sandbox" => {
-                        flavor = Some(Flavor::Sandbox);
-                    }
-                    "testnet" => {
-                        flavor = Some(Flavor::Testnet);
-                    }
-                    "mainnet" => {
-                        flavor = Some(Flavor::Mainnet);
-                    }
+                let flavor = match name.as_str() {
+                    "sandbox" => Flavor::Sandbox,
+                    "testnet" => Flavor::Testnet,
+                    "mainnet" => Flavor::Mainnet,
                     name => {
                         let msg = format!("Unknown attribute {} is specified; expected one of: `sandbox`, `testnet`, `mainnet`", name);
                         return Err(syn::Error::new_spanned(path, msg));
                     }
                 };
+                if flavors.contains(&flavor) {
+                    let msg = format!("{} is specified more than once", name);
+                    return Err(syn::Error::new_spanned(path, msg));
+                }
+                flavors.push(flavor);
             }
             other => {
                 return Err(syn::Error::new_spanned(
@@ -64,11 +120,40 @@ fn parse_knobs(
         }
     }
 
-    // TODO: env flag for configuring which runtime flavor
-    if flavor.is_none() {
-        flavor = Some(Flavor::Sandbox);
+    if flavors.is_empty() {
+        flavors.push(Flavor::Sandbox);
+    }
+    if flavors.len() > 1 && !is_test {
+        let msg = "only tests can run against several networks";
+        return Err(syn::Error::new(Span::call_site(), msg));
     }
 
+    // A single argument is the worker of the network to run against, set up for each run:
+    let worker_arg = match input.sig.inputs.len() {
+        0 => None,
+        1 => match input.sig.inputs.pop().unwrap().into_value() {
+            syn::FnArg::Typed(arg) => Some(arg),
+            receiver => {
+                let msg = "expected a worker argument, such as `worker: Worker<Sandbox>`";
+                return Err(syn::Error::new_spanned(receiver, msg));
+            }
+        },
+        _ => {
+            let msg = "expected at most one argument, the worker";
+            return Err(syn::Error::new_spanned(&input.sig.inputs, msg));
+        }
+    };
+    if let Some(arg) = &worker_arg {
+        if flavors.len() > 1 {
+            let msg = "a worker argument can only be set up for a single network";
+            return Err(syn::Error::new_spanned(arg, msg));
+        }
+    }
+    let timeout = match timeout {
+        Some(millis) => quote! { Some(::std::time::Duration::from_millis(#millis)) },
+        None => quote! { None },
+    };
+
     // If type mismatch occurs, the current rustc points to the last statement.
     let (last_stmt_start_span, last_stmt_end_span) = {
         let mut last_stmt = input
@@ -87,18 +172,51 @@ fn parse_knobs(
         (start, end)
     };
 
-    let rt = match flavor.unwrap() {
-        Flavor::Sandbox => quote_spanned! {last_stmt_start_span=>
-            let mut rt = workspaces::SandboxRuntime::default();
-            let _ = rt.run().unwrap();
-        },
-        Flavor::Testnet => quote_spanned! {last_stmt_start_span=>
-            let mut rt = workspaces::TestnetRuntime::default();
-            let _ = rt.run().unwrap();
-        },
-        // TODO: Add further implementations for mainnet
-        _ => unimplemented!(),
-    };
+    let runs = flavors.iter().map(|flavor| {
+        let rt = match flavor {
+            // With a worker argument, the test runs against that worker alone:
+            _ if worker_arg.is_some() => quote! {},
+            Flavor::Sandbox => quote_spanned! {last_stmt_start_span=>
+                let mut rt = workspaces::SandboxRuntime::default();
+                let _ = rt.run().unwrap();
+            },
+            Flavor::Testnet => quote_spanned! {last_stmt_start_span=>
+                let mut rt = workspaces::TestnetRuntime::default();
+                let _ = rt.run().unwrap();
+            },
+            Flavor::Mainnet => quote_spanned! {last_stmt_start_span=>
+                let mut rt = workspaces::MainnetRuntime::default();
+                let _ = rt.run().unwrap();
+            },
+        };
+        let setup = worker_arg.as_ref().map(|arg| {
+            let (pat, ty) = (&arg.pat, &arg.ty);
+            let name = flavor.name();
+            let worker = syn::Ident::new(name, Span::call_site());
+            let msg = format!("could not set up the {} worker", name);
+            quote_spanned! {last_stmt_start_span=>
+                let #pat: #ty = workspaces::#worker().await.expect(#msg);
+            }
+        });
+        let body = &input.block;
+        quote_spanned! {last_stmt_end_span=>
+            workspaces::__private::run_with_retries(
+                #flaky_retries,
+                || {
+                    #rt
+                    let body = async {
+                        #setup
+                        #body
+                    };
+                    let body = workspaces::__private::with_timeout(#timeout, body);
+                    let rt = workspaces::__private::tokio::runtime::Runtime::new().unwrap();
+                    let local = workspaces::__private::tokio::task::LocalSet::new();
+                    local.block_on(&rt, body)
+                },
+            )
+        }
+    });
+    let runs: Vec<_> = runs.collect();
 
     let header = if is_test {
         quote! {
@@ -108,17 +226,24 @@ fn parse_knobs(
         quote! {}
     };
 
-    let body = &input.block;
     let brace_token = input.block.brace_token;
-    input.block = syn::parse2(quote_spanned! {last_stmt_end_span=>
-        {
-            #rt
-            let body = async #body;
-            let rt = workspaces::__private::tokio::runtime::Runtime::new().unwrap();
-            let local = workspaces::__private::tokio::task::LocalSet::new();
-            local.block_on(&rt, body);
-        }
-    })
+    input.block = if let [run] = &runs[..] {
+        syn::parse2(quote_spanned! {last_stmt_end_span=>
+            {
+                #run
+            }
+        })
+    } else {
+        // Each network gets its own runtime, torn down before the next one starts:
+        let names = flavors.iter().map(Flavor::name);
+        syn::parse2(quote_spanned! {last_stmt_end_span=>
+            {
+                workspaces::__private::run_on_networks(vec![
+                    #((#names, Box::new(move || { #runs }) as Box<dyn FnOnce() -> _>),)*
+                ])
+            }
+        })
+    }
     .expect("Parsing failure");
     input.block.brace_token = brace_token;
 
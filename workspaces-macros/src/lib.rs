@@ -1,4 +1,5 @@
 mod entry;
+mod methods;
 
 use proc_macro::TokenStream;
 
@@ -12,3 +13,34 @@ pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
 pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
     entry::main(args, item)
 }
+
+/// Declare the methods of a contract once, by hand or from the ABI generated by
+/// `cargo near abi`, for calls checked at compile time instead of method names and JSON
+/// arguments spelled out at each call site:
+///
+/// ```ignore
+/// workspaces::contract_methods! {
+///     pub struct StatusMessage {
+///         call fn set_status(message: String);
+///         view fn get_status(account_id: AccountId) -> Option<String>;
+///     }
+/// }
+///
+/// workspaces::contract_methods! {
+///     #[abi = "res/status_message_abi.json"]
+///     pub struct StatusMessageAbi;
+/// }
+///
+/// let status = StatusMessage::new(&contract);
+/// status.set_status("hello").deposit(deposit).transact().await?;
+/// let message = status.get_status(contract.id().clone()).await?;
+/// ```
+///
+/// Calls return the [`CallBuilder`] of the method, with its arguments set, and views the
+/// decoded return value. Arguments are passed as JSON, by their declared names.
+///
+/// [`CallBuilder`]: https://docs.rs/workspaces/latest/workspaces/struct.CallBuilder.html
+#[proc_macro]
+pub fn contract_methods(input: TokenStream) -> TokenStream {
+    methods::contract_methods(input)
+}
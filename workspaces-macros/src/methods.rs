@@ -0,0 +1,384 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use serde_json::Value;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, parenthesized, parse_quote, Token};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Call,
+    View,
+}
+
+struct Method {
+    attrs: Vec<syn::Attribute>,
+    kind: Kind,
+    name: syn::Ident,
+    args: Vec<(syn::Ident, syn::Type)>,
+    returns: Option<syn::Type>,
+}
+
+struct Arg {
+    name: syn::Ident,
+    ty: syn::Type,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(Arg { name, ty })
+    }
+}
+
+impl Parse for Method {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let kind: syn::Ident = input.parse()?;
+        let kind = match kind.to_string().as_str() {
+            "call" => Kind::Call,
+            "view" => Kind::View,
+            _ => {
+                let msg = "expected `call fn` or `view fn`";
+                return Err(syn::Error::new_spanned(kind, msg));
+            }
+        };
+        input.parse::<Token![fn]>()?;
+        let name = input.parse()?;
+
+        let content;
+        parenthesized!(content in input);
+        let args = Punctuated::<Arg, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .map(|arg| (arg.name, arg.ty))
+            .collect();
+
+        let returns = if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?;
+            Some(input.parse::<syn::Type>()?)
+        } else {
+            None
+        };
+        input.parse::<Token![;]>()?;
+
+        if let (Kind::Call, Some(returns)) = (kind, &returns) {
+            let msg = "calls return an `ExecutionResult`, decode what the method returned \
+                       with `ExecutionResult::json` instead";
+            return Err(syn::Error::new_spanned(returns, msg));
+        }
+        Ok(Method {
+            attrs,
+            kind,
+            name,
+            args,
+            returns,
+        })
+    }
+}
+
+struct Declaration {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    name: syn::Ident,
+    abi: Option<syn::LitStr>,
+    methods: Vec<Method>,
+}
+
+impl Parse for Declaration {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = Vec::new();
+        let mut abi = None;
+        for attr in input.call(syn::Attribute::parse_outer)? {
+            if !attr.path.is_ident("abi") {
+                attrs.push(attr);
+                continue;
+            }
+            match attr.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(path),
+                    ..
+                }) => abi = Some(path),
+                meta => {
+                    let msg = "expected `#[abi = \"path/to/abi.json\"]`";
+                    return Err(syn::Error::new_spanned(meta, msg));
+                }
+            }
+        }
+
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+
+        let mut methods = Vec::new();
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        } else {
+            let content;
+            braced!(content in input);
+            while !content.is_empty() {
+                methods.push(content.parse()?);
+            }
+        }
+        Ok(Declaration {
+            attrs,
+            vis,
+            name,
+            abi,
+            methods,
+        })
+    }
+}
+
+/// Where the ABI at `path` is, relative to the crate using the macro.
+fn abi_file(path: &syn::LitStr) -> syn::Result<PathBuf> {
+    let dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new_spanned(path, "CARGO_MANIFEST_DIR is not set"))?;
+    Ok(PathBuf::from(dir).join(path.value()))
+}
+
+/// Methods of the contract described by the ABI `file`, as generated by `cargo near abi`.
+fn abi_methods(path: &syn::LitStr, file: &Path) -> syn::Result<Vec<Method>> {
+    let error = |msg: String| syn::Error::new_spanned(path, msg);
+
+    let abi: Value = std::fs::read_to_string(file)
+        .map_err(|err| err.to_string())
+        .and_then(|abi| serde_json::from_str(&abi).map_err(|err| err.to_string()))
+        .map_err(|err| error(format!("could not read {}: {}", file.display(), err)))?;
+
+    let definitions = &abi["body"]["root_schema"]["definitions"];
+    let functions = abi["body"]["functions"]
+        .as_array()
+        .ok_or_else(|| error(format!("{} lists no `body.functions`", file.display())))?;
+
+    let mut methods = Vec::new();
+    for function in functions {
+        let name = function["name"]
+            .as_str()
+            .ok_or_else(|| error("the ABI has a function without a name".to_string()))?;
+        let kind = match function["kind"].as_str() {
+            Some("call") => Kind::Call,
+            Some("view") => Kind::View,
+            kind => return Err(error(format!("`{}` is of unknown kind {:?}", name, kind))),
+        };
+
+        let params = &function["params"];
+        if params["serialization_type"].as_str().unwrap_or("json") != "json" {
+            let msg = format!("`{}` takes borsh arguments, only JSON is supported", name);
+            return Err(error(msg));
+        }
+        let mut args = Vec::new();
+        for arg in params["args"].as_array().map_or(&[][..], Vec::as_slice) {
+            let arg_name = arg["name"]
+                .as_str()
+                .ok_or_else(|| error(format!("`{}` has an argument without a name", name)))?;
+            args.push((
+                ident(arg_name, path)?,
+                rust_type(&arg["type_schema"], definitions),
+            ));
+        }
+
+        // Calls return an `ExecutionResult`, whatever the method itself returns:
+        let returns = match kind {
+            Kind::Call => None,
+            Kind::View => Some(match function.get("result") {
+                Some(result) => rust_type(&result["type_schema"], definitions),
+                None => parse_quote!(()),
+            }),
+        };
+        let doc = format!(" `{}` of the contract ABI.", name);
+        methods.push(Method {
+            attrs: vec![parse_quote!(#[doc = #doc])],
+            kind,
+            name: ident(name, path)?,
+            args,
+            returns,
+        });
+    }
+    Ok(methods)
+}
+
+/// Identifier for a name taken from an ABI, which may be a Rust keyword such as `type`.
+fn ident(name: &str, span: &syn::LitStr) -> syn::Result<syn::Ident> {
+    syn::parse_str::<syn::Ident>(name)
+        .or_else(|_| syn::parse_str::<syn::Ident>(&format!("r#{}", name)))
+        .map_err(|_| syn::Error::new_spanned(span, format!("`{}` is not a valid name", name)))
+}
+
+/// Rust type for the JSON schema of an argument or return value. Anything without an obvious
+/// counterpart, such as objects, is left as a `serde_json::Value`.
+fn rust_type(schema: &Value, definitions: &Value) -> syn::Type {
+    let any: syn::Type = parse_quote!(workspaces::__private::serde_json::Value);
+
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/definitions/");
+        return rust_type(&definitions[name], definitions);
+    }
+    if let Some(variants) = schema["anyOf"].as_array() {
+        let values: Vec<&Value> = variants.iter().filter(|v| v["type"] != "null").collect();
+        return match values.as_slice() {
+            [value] if values.len() < variants.len() => {
+                let inner = rust_type(value, definitions);
+                parse_quote!(::std::option::Option<#inner>)
+            }
+            _ => any,
+        };
+    }
+
+    let ty = match &schema["type"] {
+        Value::String(ty) => ty.as_str(),
+        // Nullable types, such as `["string", "null"]`:
+        Value::Array(types) => {
+            let values: Vec<&Value> = types.iter().filter(|ty| **ty != "null").collect();
+            return match values.as_slice() {
+                [value] if values.len() < types.len() => {
+                    let mut schema = schema.clone();
+                    schema["type"] = (*value).clone();
+                    let inner = rust_type(&schema, definitions);
+                    parse_quote!(::std::option::Option<#inner>)
+                }
+                _ => any,
+            };
+        }
+        _ => return any,
+    };
+    match ty {
+        "string" => parse_quote!(::std::string::String),
+        "boolean" => parse_quote!(bool),
+        "number" => parse_quote!(f64),
+        "null" => parse_quote!(()),
+        "integer" => match schema["format"].as_str() {
+            Some("uint8") => parse_quote!(u8),
+            Some("uint16") => parse_quote!(u16),
+            Some("uint32") => parse_quote!(u32),
+            Some("uint64") => parse_quote!(u64),
+            Some("int8") => parse_quote!(i8),
+            Some("int16") => parse_quote!(i16),
+            Some("int32") => parse_quote!(i32),
+            _ => parse_quote!(i64),
+        },
+        "array" if schema["items"].is_object() => {
+            let item = rust_type(&schema["items"], definitions);
+            parse_quote!(::std::vec::Vec<#item>)
+        }
+        _ => any,
+    }
+}
+
+fn expand_method(method: &Method) -> TokenStream2 {
+    let Method {
+        attrs,
+        kind,
+        name,
+        args,
+        returns,
+    } = method;
+    let method_name = name.unraw().to_string();
+    let arg_names: Vec<&syn::Ident> = args.iter().map(|(name, _)| name).collect();
+    let arg_types: Vec<&syn::Type> = args.iter().map(|(_, ty)| ty).collect();
+    let arg_keys = arg_names.iter().map(|name| name.unraw().to_string());
+    let params = quote! { #(#arg_names: impl ::std::convert::Into<#arg_types>),* };
+    let json_args = quote! {
+        #(let #arg_names: #arg_types = #arg_names.into();)*
+        let args = workspaces::__private::serde_json::json!({ #(#arg_keys: #arg_names),* });
+    };
+
+    match kind {
+        Kind::Call => quote! {
+            #(#attrs)*
+            pub fn #name(&self, #params) -> workspaces::CallBuilder<'a> {
+                #json_args
+                self.account.call(self.contract_id, #method_name).args_json(args)
+            }
+        },
+        Kind::View => {
+            let returns = returns.clone().unwrap_or_else(|| parse_quote!(()));
+            quote! {
+                #(#attrs)*
+                pub async fn #name(
+                    &self,
+                    #params
+                ) -> workspaces::__private::anyhow::Result<#returns> {
+                    #json_args
+                    self.account
+                        .view(self.contract_id, #method_name)
+                        .args_json(args)
+                        .fetch()
+                        .await
+                        .map_err(workspaces::__private::anyhow::Error::msg)?
+                        .json()
+                }
+            }
+        }
+    }
+}
+
+fn expand(declaration: Declaration) -> syn::Result<TokenStream2> {
+    let Declaration {
+        attrs,
+        vis,
+        name,
+        abi,
+        methods: declared,
+    } = declaration;
+    let (mut methods, track_abi) = match &abi {
+        Some(path) => {
+            let file = abi_file(path)?;
+            let file_name = file.to_string_lossy().into_owned();
+            // Nothing else tells cargo to build the bindings again once the ABI changes:
+            let track_abi = quote! { const _: &[u8] = include_bytes!(#file_name); };
+            (abi_methods(path, &file)?, track_abi)
+        }
+        None => (Vec::new(), TokenStream2::new()),
+    };
+    methods.extend(declared);
+    let methods = methods.iter().map(expand_method);
+
+    Ok(quote! {
+        #track_abi
+
+        #(#attrs)*
+        #[derive(Clone, Copy)]
+        #vis struct #name<'a> {
+            account: &'a workspaces::Account,
+            contract_id: &'a workspaces::AccountId,
+        }
+
+        // Tests rarely use every method of a contract:
+        #[allow(dead_code)]
+        impl<'a> #name<'a> {
+            /// The methods of `contract`, called by the contract's own account.
+            pub fn new(contract: &'a workspaces::Contract) -> Self {
+                Self {
+                    account: contract.as_account(),
+                    contract_id: contract.id(),
+                }
+            }
+
+            /// The same methods, called by `account` instead.
+            pub fn called_by(self, account: &'a workspaces::Account) -> Self {
+                Self { account, ..self }
+            }
+
+            #(#methods)*
+        }
+
+        impl<'a> ::std::convert::From<&'a workspaces::Contract> for #name<'a> {
+            fn from(contract: &'a workspaces::Contract) -> Self {
+                Self::new(contract)
+            }
+        }
+    })
+}
+
+pub(crate) fn contract_methods(input: TokenStream) -> TokenStream {
+    let declaration = syn::parse_macro_input!(input as Declaration);
+    expand(declaration)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
@@ -0,0 +1,626 @@
+use std::convert::TryInto;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+use anyhow::anyhow;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+
+use near_crypto::{InMemorySigner, PublicKey, SecretKey, Signer};
+use near_primitives::borsh::BorshSerialize;
+use near_primitives::types::AccountId;
+
+use crate::rpc::client::Client;
+use crate::trace;
+use crate::{
+    AccessKeyInfo, AccessKeyPermission, AccountAlreadyExists, AccountInfo, ExecutionResult, Gas,
+    KeyRejection, NearBalance, StatePrefix, StateRecords, TransactionStatus, ViewResult,
+    DEFAULT_CALL_FN_GAS,
+};
+
+/// An account along with the key it signs transactions with, bound to the network it lives
+/// on. Returned by [`crate::Worker::dev_create`].
+#[derive(Clone)]
+pub struct Account {
+    id: AccountId,
+    signer: InMemorySigner,
+    client: Client,
+}
+
+impl std::fmt::Debug for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Account").field("id", &self.id).finish()
+    }
+}
+
+impl Account {
+    pub(crate) fn new(id: AccountId, signer: InMemorySigner, client: Client) -> Self {
+        Self { id, signer, client }
+    }
+
+    pub fn id(&self) -> &AccountId {
+        &self.id
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub(crate) fn into_parts(self) -> (AccountId, InMemorySigner) {
+        (self.id, self.signer)
+    }
+
+    pub fn signer(&self) -> &InMemorySigner {
+        &self.signer
+    }
+
+    /// Secret key this account signs transactions with, e.g. to hand it to other tools.
+    /// Displays as `ed25519:<base58>`, the format it is parsed from.
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.signer.secret_key
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.signer.public_key()
+    }
+
+    /// Id and secret key of this account, to hand it to another process. See
+    /// [`AccountCredentials`].
+    pub fn credentials(&self) -> AccountCredentials {
+        AccountCredentials {
+            account_id: self.id.clone(),
+            secret_key: self.signer.secret_key.clone(),
+        }
+    }
+
+    /// The same account, signing with `signer` instead, such as a function call access
+    /// key that was added to it.
+    pub fn with_signer(&self, signer: InMemorySigner) -> Self {
+        Self {
+            signer,
+            ..self.clone()
+        }
+    }
+
+    /// Start building a call to `method_name` on `contract_id`, signed by this account.
+    pub fn call<'a>(&'a self, contract_id: &AccountId, method_name: &str) -> CallBuilder<'a> {
+        CallBuilder::new(self, contract_id.clone(), method_name)
+    }
+
+    /// Start building a query of the view method `method_name` on `contract_id`.
+    pub fn view<'a>(&'a self, contract_id: &AccountId, method_name: &str) -> ViewBuilder<'a> {
+        ViewBuilder::new(&self.client, contract_id.clone(), method_name)
+    }
+
+    /// Start building the creation of the subaccount `<name>.<this account>`, such as
+    /// `factory.alice.test.near`, funded by this account.
+    pub fn create_subaccount<'a>(&'a self, name: &str) -> CreateAccountBuilder<'a> {
+        CreateAccountBuilder::new(self, name)
+    }
+
+    pub async fn transfer_near(
+        &self,
+        receiver_id: &AccountId,
+        amount: NearBalance,
+    ) -> Result<ExecutionResult, String> {
+        self.client
+            .transfer_near(&self.signer, self.id.clone(), receiver_id.clone(), amount)
+            .await
+    }
+
+    pub async fn view_account(&self) -> Result<AccountInfo, String> {
+        self.client.display_account_info(self.id.clone()).await
+    }
+
+    /// Balance of this account, not counting any staked amount.
+    pub async fn view_balance(&self) -> Result<NearBalance, String> {
+        let account = self.client.view_account(self.id.clone()).await?;
+        Ok(NearBalance::from_yoctonear(account.amount))
+    }
+
+    pub async fn view_access_key_list(&self) -> Result<Vec<AccessKeyInfo>, String> {
+        self.client.view_access_key_list(self.id.clone()).await
+    }
+
+    /// Add `public_key` to the keys of this account. Use [`AccessKeyPermission::FullAccess`]
+    /// for a key which can sign anything, or a
+    /// [`FunctionCallPermission`](crate::FunctionCallPermission) to restrict it to calls to a
+    /// single contract:
+    ///
+    /// ```ignore
+    /// let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+    ///     allowance: Some(NearBalance::from_near(1)?.to_yoctonear()),
+    ///     receiver_id: contract.id().to_string(),
+    ///     method_names: vec!["set_status".to_string()],
+    /// });
+    /// account.add_key(secret_key.public_key(), permission).await?;
+    /// ```
+    ///
+    /// An empty `method_names` allows calls to any method of the contract. Function call keys
+    /// can't attach deposits to their calls.
+    pub async fn add_key(
+        &self,
+        public_key: PublicKey,
+        permission: AccessKeyPermission,
+    ) -> Result<ExecutionResult, String> {
+        self.client
+            .add_key(self.id.clone(), &self.signer, public_key, permission)
+            .await
+    }
+
+    /// Remove `public_key` from the keys of this account. Deleting the key this account signs
+    /// with leaves it unable to sign anything further.
+    pub async fn delete_key(&self, public_key: &PublicKey) -> Result<ExecutionResult, String> {
+        self.client
+            .delete_keys(self.id.clone(), &self.signer, vec![public_key.clone()])
+            .await
+    }
+
+    /// Delete all the keys of this account in a single transaction, the usual last step of
+    /// deploying a contract nobody should control anymore, or one governed by a DAO. Once
+    /// locked, the account can't sign anything: transactions from it fail with
+    /// [`AccountLocked`](crate::AccountLocked), and only its own code can change it, e.g. by
+    /// deploying an upgrade it receives through a call.
+    pub async fn lock(&self) -> Result<ExecutionResult, String> {
+        let keys = self.view_access_key_list().await?;
+        self.client
+            .delete_keys(
+                self.id.clone(),
+                &self.signer,
+                keys.into_iter().map(|key| key.public_key).collect(),
+            )
+            .await
+    }
+
+    /// Whether the account has no keys left, see [`Account::lock`].
+    pub async fn is_locked(&self) -> Result<bool, String> {
+        Ok(self.view_access_key_list().await?.is_empty())
+    }
+
+    /// Add a fresh key with `permission` to this account, run `body` with a handle to the
+    /// account signing with that key, then delete the key again, e.g. to test how a contract
+    /// behaves once the allowance of a function call key runs out. If `body` panics, the key
+    /// is still deleted before the panic is propagated. `body` may delete the key itself, to
+    /// test revocation.
+    pub async fn with_temporary_key<B, Fut>(
+        &self,
+        permission: AccessKeyPermission,
+        body: B,
+    ) -> anyhow::Result<Fut::Output>
+    where
+        B: FnOnce(Account) -> Fut,
+        Fut: Future,
+    {
+        let secret_key = self.client.random_secret_key();
+        let public_key = secret_key.public_key();
+        let outcome = self
+            .add_key(public_key.clone(), permission)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        if !outcome.is_success() {
+            return Err(anyhow!(
+                "could not add a temporary key to {}: {:?}",
+                self.id,
+                outcome.status()
+            ));
+        }
+
+        let leased = self.with_signer(InMemorySigner::from_secret_key(self.id.clone(), secret_key));
+        let output = AssertUnwindSafe(body(leased)).catch_unwind().await;
+
+        let cleanup = self.delete_temporary_key(&public_key).await;
+        match output {
+            Ok(output) => cleanup.map(|()| output),
+            // The panic is what the test failed on, so a failed cleanup must not replace it:
+            Err(panic) => {
+                if let Err(err) = cleanup {
+                    trace::warn!(
+                        "could not clean up the temporary key {} of {} after a panic: {:?}",
+                        public_key,
+                        self.id,
+                        err
+                    );
+                }
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+
+    /// Delete the key added by [`Account::with_temporary_key`], unless its body already did.
+    async fn delete_temporary_key(&self, public_key: &PublicKey) -> anyhow::Result<()> {
+        let keys = self
+            .view_access_key_list()
+            .await
+            .map_err(anyhow::Error::msg)?;
+        if keys.iter().any(|key| &key.public_key == public_key) {
+            let outcome = self
+                .delete_key(public_key)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            if !outcome.is_success() {
+                return Err(anyhow!(
+                    "could not delete the temporary key {} of {}: {:?}",
+                    public_key,
+                    self.id,
+                    outcome.status()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes this account, sending its remaining balance to `beneficiary_id`, e.g. to check
+    /// the cleanup logic of a contract, or to give back the funds of testnet dev accounts once
+    /// a test suite is done with them. The balance is burnt if `beneficiary_id` doesn't exist.
+    pub async fn delete_account(
+        self,
+        beneficiary_id: &AccountId,
+    ) -> Result<ExecutionResult, String> {
+        self.client
+            .delete_account(self.id, &self.signer, beneficiary_id.clone())
+            .await
+    }
+}
+
+/// Id and secret key of an account, serializable so that fixtures created in one process,
+/// such as a setup binary, can be handed to test processes or load generators running
+/// against the same network. Bind them to a network again through
+/// [`Worker::account_from_credentials`](crate::Worker::account_from_credentials). The secret
+/// key is serialized in the clear, so only ever do this for test accounts.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountCredentials {
+    pub account_id: AccountId,
+    pub secret_key: SecretKey,
+}
+
+impl std::fmt::Debug for AccountCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountCredentials")
+            .field("account_id", &self.account_id)
+            .finish()
+    }
+}
+
+/// An account with a contract deployed to it. Returned by [`crate::Worker::dev_deploy`].
+#[derive(Clone, Debug)]
+pub struct Contract {
+    account: Account,
+}
+
+impl Contract {
+    pub(crate) fn new(id: AccountId, signer: InMemorySigner, client: Client) -> Self {
+        Self {
+            account: Account::new(id, signer, client),
+        }
+    }
+
+    pub fn id(&self) -> &AccountId {
+        self.account.id()
+    }
+
+    pub fn signer(&self) -> &InMemorySigner {
+        self.account.signer()
+    }
+
+    pub fn secret_key(&self) -> &SecretKey {
+        self.account.secret_key()
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.account.public_key()
+    }
+
+    pub fn credentials(&self) -> AccountCredentials {
+        self.account.credentials()
+    }
+
+    /// The account the contract is deployed to, for sending transactions on its behalf
+    /// to other contracts.
+    pub fn as_account(&self) -> &Account {
+        &self.account
+    }
+
+    pub fn into_account(self) -> Account {
+        self.account
+    }
+
+    /// The same contract, signing with `signer` instead, such as a function call access key
+    /// that was added to it. See [`Account::with_signer`].
+    pub fn with_signer(&self, signer: InMemorySigner) -> Self {
+        Self {
+            account: self.account.with_signer(signer),
+        }
+    }
+
+    /// Start building a call to `method_name` on this contract, signed by the contract's
+    /// own account. Use [`Account::call`] to call it from another account.
+    pub fn call(&self, method_name: &str) -> CallBuilder<'_> {
+        self.account.call(self.id(), method_name)
+    }
+
+    /// Start building a query of the view method `method_name` on this contract.
+    pub fn view(&self, method_name: &str) -> ViewBuilder<'_> {
+        self.account.view(self.id(), method_name)
+    }
+
+    /// The methods of this contract as declared with [`contract_methods!`], such as
+    /// `contract.methods::<StatusMessage>().set_status("hello")`.
+    ///
+    /// [`contract_methods!`]: crate::contract_methods
+    pub fn methods<'a, M: From<&'a Contract>>(&'a self) -> M {
+        M::from(self)
+    }
+
+    pub async fn view_account(&self) -> Result<AccountInfo, String> {
+        self.account.view_account().await
+    }
+
+    pub async fn view_balance(&self) -> Result<NearBalance, String> {
+        self.account.view_balance().await
+    }
+
+    pub async fn view_access_key_list(&self) -> Result<Vec<AccessKeyInfo>, String> {
+        self.account.view_access_key_list().await
+    }
+
+    /// Delete all the keys of the contract's account, so that only the contract itself can
+    /// change its code and state. See [`Account::lock`].
+    pub async fn lock(&self) -> Result<ExecutionResult, String> {
+        self.account.lock().await
+    }
+
+    pub async fn is_locked(&self) -> Result<bool, String> {
+        self.account.is_locked().await
+    }
+
+    /// Wasm code deployed to this contract.
+    pub async fn view_code(&self) -> Result<Vec<u8>, String> {
+        self.account.client.view_code(self.id().clone()).await
+    }
+
+    pub async fn view_state(&self, prefix: Option<StatePrefix>) -> anyhow::Result<StateRecords> {
+        self.account
+            .client
+            .view_state(self.id().clone(), prefix)
+            .await
+    }
+
+    /// Deletes the contract's account along with its code and state. See
+    /// [`Account::delete_account`].
+    pub async fn delete_account(
+        self,
+        beneficiary_id: &AccountId,
+    ) -> Result<ExecutionResult, String> {
+        self.account.delete_account(beneficiary_id).await
+    }
+}
+
+/// Builder for a function call transaction, sent once [`CallBuilder::transact`] is awaited.
+pub struct CallBuilder<'a> {
+    account: &'a Account,
+    contract_id: AccountId,
+    method_name: String,
+    args: Result<Vec<u8>, String>,
+    deposit: Option<NearBalance>,
+    gas: Gas,
+    fallback_signer: Option<&'a InMemorySigner>,
+}
+
+impl<'a> CallBuilder<'a> {
+    fn new(account: &'a Account, contract_id: AccountId, method_name: &str) -> Self {
+        Self {
+            account,
+            contract_id,
+            method_name: method_name.to_string(),
+            args: Ok(Vec::new()),
+            deposit: None,
+            gas: Gas::from_gas(DEFAULT_CALL_FN_GAS),
+            fallback_signer: None,
+        }
+    }
+
+    /// Raw arguments, passed to the method as-is.
+    pub fn args(mut self, args: Vec<u8>) -> Self {
+        self.args = Ok(args);
+        self
+    }
+
+    pub fn args_json<T: Serialize>(mut self, args: T) -> Self {
+        self.args = serde_json::to_vec(&args).map_err(|err| format!("serde_json error: {:?}", err));
+        self
+    }
+
+    pub fn args_borsh<T: BorshSerialize>(mut self, args: T) -> Self {
+        self.args = args
+            .try_to_vec()
+            .map_err(|err| format!("borsh error: {:?}", err));
+        self
+    }
+
+    pub fn deposit(mut self, deposit: NearBalance) -> Self {
+        self.deposit = Some(deposit);
+        self
+    }
+
+    /// Gas to attach to the call, 10 Tgas by default. Plain `u64` amounts work as well.
+    pub fn gas(mut self, gas: impl Into<Gas>) -> Self {
+        self.gas = gas.into();
+        self
+    }
+
+    /// Attach [`Gas::MAX`], e.g. for calls doing cross-contract calls.
+    pub fn max_gas(self) -> Self {
+        self.gas(Gas::MAX)
+    }
+
+    /// Sign with `signer`, another key of the same account such as its full access key, if
+    /// the account's function call key refuses to sign the call, as wallets do. The call
+    /// fails with [`AccessKeyRejected`](crate::AccessKeyRejected) without a fallback.
+    pub fn fallback_signer(mut self, signer: &'a InMemorySigner) -> Self {
+        self.fallback_signer = Some(signer);
+        self
+    }
+
+    pub async fn transact(self) -> Result<ExecutionResult, String> {
+        let args = self.args?;
+        let result = self
+            .account
+            .client
+            .call(
+                &self.account.signer,
+                self.account.id.clone(),
+                self.contract_id.clone(),
+                self.method_name.clone(),
+                args.clone(),
+                self.deposit,
+                self.gas.as_gas(),
+            )
+            .await;
+
+        match (result, self.fallback_signer) {
+            (Err(err), Some(fallback)) if KeyRejection::from_error(&err).is_some() => {
+                self.account
+                    .client
+                    .call(
+                        fallback,
+                        self.account.id.clone(),
+                        self.contract_id,
+                        self.method_name,
+                        args,
+                        self.deposit,
+                        self.gas.as_gas(),
+                    )
+                    .await
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Send the transaction without waiting for it to execute, so that several transactions
+    /// can be in flight at once. See [`TransactionStatus`].
+    pub async fn transact_async(self) -> Result<TransactionStatus, String> {
+        self.account
+            .client
+            .call_async(
+                &self.account.signer,
+                self.account.id.clone(),
+                self.contract_id,
+                self.method_name,
+                self.args?,
+                self.deposit,
+                self.gas.as_gas(),
+            )
+            .await
+    }
+}
+
+/// Builder for the creation of a subaccount, sent once [`CreateAccountBuilder::transact`]
+/// is awaited.
+pub struct CreateAccountBuilder<'a> {
+    parent: &'a Account,
+    name: String,
+    initial_balance: Option<NearBalance>,
+    secret_key: Option<SecretKey>,
+}
+
+impl<'a> CreateAccountBuilder<'a> {
+    fn new(parent: &'a Account, name: &str) -> Self {
+        Self {
+            parent,
+            name: name.to_string(),
+            initial_balance: None,
+            secret_key: None,
+        }
+    }
+
+    /// Balance transferred from the parent to the new account. Defaults to 1 NEAR.
+    pub fn initial_balance(mut self, initial_balance: NearBalance) -> Self {
+        self.initial_balance = Some(initial_balance);
+        self
+    }
+
+    /// Full access key of the new account. Defaults to a randomly generated one.
+    pub fn keys(mut self, secret_key: SecretKey) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+
+    pub async fn transact(self) -> anyhow::Result<Account> {
+        let id: AccountId = format!("{}.{}", self.name, self.parent.id)
+            .try_into()
+            .map_err(|err| anyhow!("invalid subaccount name {:?}: {:?}", self.name, err))?;
+        let secret_key = self
+            .secret_key
+            .unwrap_or_else(|| self.parent.client.random_secret_key());
+        let signer = InMemorySigner::from_secret_key(id.clone(), secret_key);
+
+        let result = self
+            .parent
+            .client
+            .create_account(
+                &self.parent.signer,
+                self.parent.id.clone(),
+                id.clone(),
+                signer.public_key(),
+                self.initial_balance,
+            )
+            .await?;
+        if let Some(err) = AccountAlreadyExists::from_status(result.status()) {
+            return Err(err.into());
+        }
+        if !result.is_success() {
+            return Err(anyhow!(
+                "could not create subaccount {}: {:?}",
+                id,
+                result.status()
+            ));
+        }
+
+        Ok(Account::new(id, signer, self.parent.client.clone()))
+    }
+}
+
+/// Builder for a view method query, sent once [`ViewBuilder::fetch`] is awaited.
+pub struct ViewBuilder<'a> {
+    client: &'a Client,
+    contract_id: AccountId,
+    method_name: String,
+    args: Result<Vec<u8>, String>,
+}
+
+impl<'a> ViewBuilder<'a> {
+    pub(crate) fn new(client: &'a Client, contract_id: AccountId, method_name: &str) -> Self {
+        Self {
+            client,
+            contract_id,
+            method_name: method_name.to_string(),
+            args: Ok(Vec::new()),
+        }
+    }
+
+    /// Raw arguments, passed to the method as-is.
+    pub fn args(mut self, args: Vec<u8>) -> Self {
+        self.args = Ok(args);
+        self
+    }
+
+    pub fn args_json<T: Serialize>(mut self, args: T) -> Self {
+        self.args = serde_json::to_vec(&args).map_err(|err| format!("serde_json error: {:?}", err));
+        self
+    }
+
+    pub fn args_borsh<T: BorshSerialize>(mut self, args: T) -> Self {
+        self.args = args
+            .try_to_vec()
+            .map_err(|err| format!("borsh error: {:?}", err));
+        self
+    }
+
+    /// Query the view method. Decode what it returned with [`ViewResult::json`] or
+    /// [`ViewResult::borsh`].
+    pub async fn fetch(self) -> Result<ViewResult, String> {
+        self.client
+            .view_raw(self.contract_id, self.method_name, self.args?.into())
+            .await
+    }
+}
@@ -0,0 +1,126 @@
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight, ShardId};
+use near_primitives::views::{BlockView, ChunkView};
+
+use crate::{Gas, NearBalance};
+
+/// A block, as returned by [`Worker::view_block`](crate::Worker::view_block). Outcomes of
+/// transactions record the block they were included in, see
+/// [`ExecutionOutcome::block_hash`](crate::ExecutionOutcome::block_hash).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    view: BlockView,
+}
+
+impl Block {
+    pub(crate) fn new(view: BlockView) -> Self {
+        Self { view }
+    }
+
+    pub fn height(&self) -> BlockHeight {
+        self.view.header.height
+    }
+
+    pub fn hash(&self) -> &CryptoHash {
+        &self.view.header.hash
+    }
+
+    pub fn prev_hash(&self) -> &CryptoHash {
+        &self.view.header.prev_hash
+    }
+
+    /// Unix time the block was produced at, in nanoseconds. This is what contracts executed
+    /// in it get from `env::block_timestamp`.
+    pub fn timestamp(&self) -> u64 {
+        self.view.header.timestamp_nanosec
+    }
+
+    /// The validator which produced the block.
+    pub fn author(&self) -> &AccountId {
+        &self.view.author
+    }
+
+    pub fn epoch_id(&self) -> &CryptoHash {
+        &self.view.header.epoch_id
+    }
+
+    /// Price of a unit of gas for transactions in the block.
+    pub fn gas_price(&self) -> NearBalance {
+        NearBalance::from_yoctonear(self.view.header.gas_price)
+    }
+
+    /// Exact amount charged for `gas` at the gas price of the block. Receipts executed in
+    /// later blocks are charged at the price the transaction was converted at.
+    pub fn gas_cost(&self, gas: Gas) -> NearBalance {
+        NearBalance::from_yoctonear(self.view.header.gas_price * gas.as_gas() as u128)
+    }
+
+    /// Hashes of the chunks of the block, one per shard.
+    pub fn chunk_hashes(&self) -> impl Iterator<Item = &CryptoHash> {
+        self.view.chunks.iter().map(|chunk| &chunk.chunk_hash)
+    }
+
+    /// The block as returned by the RPC, for anything not exposed above.
+    pub fn raw(&self) -> &BlockView {
+        &self.view
+    }
+
+    pub fn into_inner(self) -> BlockView {
+        self.view
+    }
+}
+
+/// The chunk of a single shard in a block, as returned by
+/// [`Worker::view_chunk`](crate::Worker::view_chunk).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    view: ChunkView,
+}
+
+impl Chunk {
+    pub(crate) fn new(view: ChunkView) -> Self {
+        Self { view }
+    }
+
+    pub fn hash(&self) -> &CryptoHash {
+        &self.view.header.chunk_hash
+    }
+
+    pub fn shard_id(&self) -> ShardId {
+        self.view.header.shard_id
+    }
+
+    /// Height of the block the chunk was produced for. Shards without a new chunk in a block
+    /// repeat their previous chunk, which was created at an earlier height.
+    pub fn height_created(&self) -> BlockHeight {
+        self.view.header.height_created
+    }
+
+    /// The validator which produced the chunk.
+    pub fn author(&self) -> &AccountId {
+        &self.view.author
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.view.header.gas_used
+    }
+
+    /// Hashes of the transactions included in the chunk.
+    pub fn transaction_hashes(&self) -> impl Iterator<Item = &CryptoHash> {
+        self.view.transactions.iter().map(|tx| &tx.hash)
+    }
+
+    /// Ids of the receipts included in the chunk.
+    pub fn receipt_ids(&self) -> impl Iterator<Item = &CryptoHash> {
+        self.view.receipts.iter().map(|receipt| &receipt.receipt_id)
+    }
+
+    /// The chunk as returned by the RPC, for anything not exposed above.
+    pub fn raw(&self) -> &ChunkView {
+        &self.view
+    }
+
+    pub fn into_inner(self) -> ChunkView {
+        self.view
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::anyhow;
+use tokio::process::Command;
+
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// Build the contract crate at `project_dir` for wasm in release mode, and return the wasm
+/// bytes, so tests exercise the current source of a contract rather than a stale prebuilt
+/// file. See [`Compile`] to build with test-only features or environment variables.
+pub async fn compile_project(project_dir: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
+    Compile::new(project_dir).build().await
+}
+
+/// Build of a contract crate, sent once [`Compile::build`] is awaited. Cargo's incremental
+/// compilation makes builds after the first one of a test run cheap.
+#[derive(Debug, Clone)]
+pub struct Compile {
+    project_dir: PathBuf,
+    features: Vec<String>,
+    envs: Vec<(String, String)>,
+}
+
+impl Compile {
+    pub fn new(project_dir: impl AsRef<Path>) -> Self {
+        Self {
+            project_dir: project_dir.as_ref().to_path_buf(),
+            features: Vec::new(),
+            envs: Vec::new(),
+        }
+    }
+
+    /// Cargo features to enable, e.g. `integration-test`, so that hooks only needed by tests
+    /// never end up in the production artifact.
+    pub fn features(mut self, features: &[&str]) -> Self {
+        self.features
+            .extend(features.iter().map(|feature| feature.to_string()));
+        self
+    }
+
+    /// Environment variable to build with, such as `RUSTFLAGS`.
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.envs.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub async fn build(self) -> anyhow::Result<Vec<u8>> {
+        let manifest = self.project_dir.join("Cargo.toml");
+        if !manifest.is_file() {
+            return Err(anyhow!("no Cargo.toml found in {:?}", self.project_dir));
+        }
+
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let mut cmd = Command::new(cargo);
+        cmd.arg("build")
+            .arg("--release")
+            .args(&["--target", WASM_TARGET])
+            .arg("--manifest-path")
+            .arg(&manifest)
+            // Artifacts are listed on stdout, diagnostics still go to stderr:
+            .arg("--message-format=json-render-diagnostics")
+            .envs(self.envs)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if !self.features.is_empty() {
+            cmd.args(&["--features", &self.features.join(",")]);
+        }
+
+        let output = cmd.output().await?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "failed to build {:?} ({}), is the {} target installed?",
+                self.project_dir,
+                output.status,
+                WASM_TARGET
+            ));
+        }
+
+        let wasm = wasm_artifact(&output.stdout)
+            .ok_or_else(|| anyhow!("building {:?} produced no wasm file", self.project_dir))?;
+        Ok(tokio::fs::read(wasm).await?)
+    }
+}
+
+/// The last wasm file cargo reported building, which is the one of the crate itself, its
+/// dependencies being built before it.
+fn wasm_artifact(stdout: &[u8]) -> Option<PathBuf> {
+    stdout
+        .split(|&byte| byte == b'\n')
+        .filter_map(|line| serde_json::from_slice::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .flat_map(|message| match message["filenames"].as_array() {
+            Some(filenames) => filenames.clone(),
+            None => Vec::new(),
+        })
+        .filter_map(|filename| filename.as_str().map(PathBuf::from))
+        .filter(|filename| filename.extension().map_or(false, |ext| ext == "wasm"))
+        .last()
+}
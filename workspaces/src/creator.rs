@@ -0,0 +1,106 @@
+use std::convert::TryInto;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use near_crypto::PublicKey;
+use near_primitives::types::AccountId;
+
+use crate::rpc::tool;
+use crate::{Account, AccountAlreadyExists, NearBalance};
+
+/// How a [`Worker`](crate::Worker) creates the accounts of [`Worker::dev_create`] and
+/// [`Worker::dev_deploy`], instead of the network's own way, such as the testnet helper.
+/// Implementations can integrate existing infrastructure, such as an internal faucet
+/// service, and are set with [`Worker::with_account_creator`].
+///
+/// [`Worker::dev_create`]: crate::Worker::dev_create
+/// [`Worker::dev_deploy`]: crate::Worker::dev_deploy
+/// [`Worker::with_account_creator`]: crate::Worker::with_account_creator
+#[async_trait]
+pub trait AccountCreator: Send + Sync {
+    /// Id of the next account to create. Defaults to a random top-level `dev-` account id.
+    fn new_account_id(&self) -> AccountId {
+        tool::random_account_id()
+    }
+
+    /// Create `account_id` with `public_key` as a full access key, and enough balance to
+    /// deploy a contract. The worker waits for the key to show up to queries afterwards, so
+    /// this can return as soon as the creation is requested. Should fail with
+    /// [`AccountAlreadyExists`] if the account is taken.
+    async fn create_account(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> anyhow::Result<()>;
+}
+
+/// Creates subaccounts of a pre-funded account, funding each of them from it. Useful where
+/// top-level accounts can't be created, or to keep all accounts of a test run together
+/// under one parent:
+///
+/// ```ignore
+/// let creator = worker.account_from_keystore("ci-runner.testnet".parse()?)?;
+/// let worker = worker.with_account_creator(SubaccountCreator::new(creator));
+/// let contract = worker.dev_deploy(wasm).await?; // dev-....ci-runner.testnet
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubaccountCreator {
+    creator: Account,
+    initial_balance: Option<NearBalance>,
+}
+
+impl SubaccountCreator {
+    pub fn new(creator: Account) -> Self {
+        Self {
+            creator,
+            initial_balance: None,
+        }
+    }
+
+    /// Balance transferred from the creator to each new account. Defaults to 1 NEAR, as
+    /// with [`Account::create_subaccount`].
+    pub fn initial_balance(mut self, initial_balance: NearBalance) -> Self {
+        self.initial_balance = Some(initial_balance);
+        self
+    }
+}
+
+#[async_trait]
+impl AccountCreator for SubaccountCreator {
+    fn new_account_id(&self) -> AccountId {
+        format!("{}.{}", tool::random_account_id(), self.creator.id())
+            .try_into()
+            .expect("could not convert dev subaccount into AccountId")
+    }
+
+    async fn create_account(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> anyhow::Result<()> {
+        let result = self
+            .creator
+            .client()
+            .create_account(
+                self.creator.signer(),
+                self.creator.id().clone(),
+                account_id.clone(),
+                public_key.clone(),
+                self.initial_balance,
+            )
+            .await?;
+        if let Some(err) = AccountAlreadyExists::from_status(result.status()) {
+            return Err(err.into());
+        }
+        if !result.is_success() {
+            return Err(anyhow!(
+                "{} could not create {}: {:?}",
+                self.creator.id(),
+                account_id,
+                result.status()
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Costs defined by the protocol, read from the node rather than hardcoded, so that tests
+//! computing expected charges keep working across protocol upgrades.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{Gas, NearBalance};
+
+/// Gas charged for an action or a receipt, split the way the runtime charges it. The send
+/// part is charged when the receipt is created, and the execution part when it is executed
+/// on the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fee {
+    /// Send cost when the receiver is the sender itself.
+    pub send_sir: Gas,
+    /// Send cost when the receiver is another account.
+    pub send_not_sir: Gas,
+    pub execution: Gas,
+}
+
+impl Fee {
+    /// Send and execution costs together. `sir` is whether the receiver is the sender.
+    pub fn total(&self, sir: bool) -> Gas {
+        let send = if sir {
+            self.send_sir
+        } else {
+            self.send_not_sir
+        };
+        Gas::from_gas(send.as_gas() + self.execution.as_gas())
+    }
+}
+
+#[derive(Deserialize)]
+struct RawFee {
+    send_sir: u64,
+    send_not_sir: u64,
+    execution: u64,
+}
+
+impl From<RawFee> for Fee {
+    fn from(fee: RawFee) -> Self {
+        Self {
+            send_sir: Gas::from_gas(fee.send_sir),
+            send_not_sir: Gas::from_gas(fee.send_not_sir),
+            execution: Gas::from_gas(fee.execution),
+        }
+    }
+}
+
+/// Actions whose base cost is part of the protocol config. Actions with a payload, such as
+/// deploying code or calling a function, cost more on top of it per byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    FunctionCall,
+    Transfer,
+    Stake,
+    AddFullAccessKey,
+    AddFunctionCallKey,
+    DeleteKey,
+    DeleteAccount,
+}
+
+impl ActionKind {
+    const ALL: [ActionKind; 9] = [
+        ActionKind::CreateAccount,
+        ActionKind::DeployContract,
+        ActionKind::FunctionCall,
+        ActionKind::Transfer,
+        ActionKind::Stake,
+        ActionKind::AddFullAccessKey,
+        ActionKind::AddFunctionCallKey,
+        ActionKind::DeleteKey,
+        ActionKind::DeleteAccount,
+    ];
+
+    /// JSON pointer of the cost in `action_creation_config`.
+    fn pointer(self) -> &'static str {
+        match self {
+            ActionKind::CreateAccount => "/create_account_cost",
+            ActionKind::DeployContract => "/deploy_contract_cost",
+            ActionKind::FunctionCall => "/function_call_cost",
+            ActionKind::Transfer => "/transfer_cost",
+            ActionKind::Stake => "/stake_cost",
+            ActionKind::AddFullAccessKey => "/add_key_cost/full_access_cost",
+            ActionKind::AddFunctionCallKey => "/add_key_cost/function_call_cost",
+            ActionKind::DeleteKey => "/delete_key_cost",
+            ActionKind::DeleteAccount => "/delete_account_cost",
+        }
+    }
+}
+
+/// The costs of the protocol version a node runs at, as returned by
+/// [`Worker::protocol_costs`](crate::Worker::protocol_costs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolCosts {
+    storage_amount_per_byte: NearBalance,
+    receipt_creation: Fee,
+    actions: HashMap<ActionKind, Fee>,
+}
+
+impl ProtocolCosts {
+    pub(crate) fn from_config(config: &Value) -> anyhow::Result<Self> {
+        let runtime = &config["runtime_config"];
+        let storage_amount_per_byte = runtime["storage_amount_per_byte"]
+            .as_str()
+            .ok_or_else(|| anyhow!("protocol config has no storage_amount_per_byte"))?
+            .parse()?;
+
+        let costs = &runtime["transaction_costs"];
+        let fee = |value: Option<&Value>, name: &str| -> anyhow::Result<Fee> {
+            let value = value.ok_or_else(|| anyhow!("protocol config has no {} cost", name))?;
+            Ok(RawFee::deserialize(value)?.into())
+        };
+        let receipt_creation = fee(
+            costs.get("action_receipt_creation_config"),
+            "action receipt creation",
+        )?;
+        let actions = ActionKind::ALL
+            .iter()
+            .map(|&kind| {
+                let value = costs["action_creation_config"].pointer(kind.pointer());
+                Ok((kind, fee(value, kind.pointer())?))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            storage_amount_per_byte: NearBalance::from_yoctonear(storage_amount_per_byte),
+            receipt_creation,
+            actions,
+        })
+    }
+
+    /// Balance an account has to hold to pay for `bytes` of storage, its code and state
+    /// included.
+    pub fn storage_cost(&self, bytes: u64) -> NearBalance {
+        NearBalance::from_yoctonear(self.storage_amount_per_byte.to_yoctonear() * bytes as u128)
+    }
+
+    /// Base cost of a single action, not counting any per-byte cost of its payload.
+    pub fn action_cost(&self, kind: ActionKind) -> Fee {
+        self.actions[&kind]
+    }
+
+    /// Cost of creating a receipt, charged once per receipt on top of its actions.
+    pub fn receipt_creation_cost(&self) -> Fee {
+        self.receipt_creation
+    }
+}
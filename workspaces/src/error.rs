@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use near_crypto::PublicKey;
+use near_primitives::errors::{ActionError, ActionErrorKind, TxExecutionError};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionStatus;
+
+/// Creating an account failed because its id is already taken, on the sandbox as well as
+/// through the testnet helper. Returned within an `anyhow::Error`, so that "create or reuse"
+/// logic can tell it apart through `err.downcast_ref::<AccountAlreadyExists>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountAlreadyExists {
+    pub account_id: AccountId,
+}
+
+impl std::fmt::Display for AccountAlreadyExists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "account {} already exists", self.account_id)
+    }
+}
+
+impl std::error::Error for AccountAlreadyExists {}
+
+impl AccountAlreadyExists {
+    /// The error, if a transaction creating an account failed with `status` because the
+    /// account exists.
+    pub(crate) fn from_status(status: &FinalExecutionStatus) -> Option<Self> {
+        match status {
+            FinalExecutionStatus::Failure(TxExecutionError::ActionError(ActionError {
+                kind: ActionErrorKind::AccountAlreadyExists { account_id },
+                ..
+            })) => Some(Self {
+                account_id: account_id.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Signing a transaction failed because the account has no access keys left, such as a
+/// contract locked with [`Account::lock`](crate::Account::lock) so that only its own code,
+/// e.g. a DAO's proposals, can change it. Transactions return their errors as strings, so
+/// this is what they fail with, formatted:
+///
+/// ```ignore
+/// let err = contract.call("upgrade").transact().await.unwrap_err();
+/// assert_eq!(err, AccountLocked { account_id: contract.id().clone() }.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountLocked {
+    pub account_id: AccountId,
+}
+
+impl std::fmt::Display for AccountLocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "account {} is locked: it has no access keys to sign transactions with",
+            self.account_id
+        )
+    }
+}
+
+impl std::error::Error for AccountLocked {}
+
+/// A function call access key refused to sign a transaction, such as one whose allowance ran
+/// out. As with [`AccountLocked`], transactions fail with this formatted, so wallet-like flows
+/// can tell it apart and sign with a full access key instead, see
+/// [`CallBuilder::fallback_signer`](crate::CallBuilder::fallback_signer):
+///
+/// ```ignore
+/// let err = limited.call(contract.id(), "set_status").transact().await.unwrap_err();
+/// assert_eq!(KeyRejection::from_error(&err), Some(KeyRejection::NotEnoughAllowance));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessKeyRejected {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    pub reason: KeyRejection,
+}
+
+impl std::fmt::Display for AccessKeyRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "access key {} of {} rejected the transaction: {:?}",
+            self.public_key, self.account_id, self.reason
+        )
+    }
+}
+
+impl std::error::Error for AccessKeyRejected {}
+
+/// Why a function call access key refused to sign a transaction, see [`AccessKeyRejected`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRejection {
+    /// The key's allowance doesn't cover the gas the transaction could burn.
+    NotEnoughAllowance,
+    /// The key only allows calls to other methods.
+    MethodNameMismatch,
+    /// The key only allows calls to another contract.
+    ReceiverMismatch,
+    /// Function call keys can't attach deposits to their calls.
+    DepositWithFunctionCall,
+    /// The transaction isn't a single function call, such as a transfer.
+    RequiresFullAccess,
+}
+
+impl KeyRejection {
+    const ALL: [KeyRejection; 5] = [
+        KeyRejection::NotEnoughAllowance,
+        KeyRejection::MethodNameMismatch,
+        KeyRejection::ReceiverMismatch,
+        KeyRejection::DepositWithFunctionCall,
+        KeyRejection::RequiresFullAccess,
+    ];
+
+    /// Why the access key rejected the transaction failing with `err`, if that is why it
+    /// failed. Works on [`AccessKeyRejected`] errors as well as on raw RPC errors, both of
+    /// which name the reason.
+    pub fn from_error(err: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|reason| err.contains(&format!("{:?}", reason)))
+    }
+}
+
+/// The testnet helper refused or failed to create an account. Returned within an
+/// `anyhow::Error`, like [`AccountAlreadyExists`], which is returned instead when the id is
+/// already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelperError {
+    /// Too many accounts were asked for recently. `retry_after` is how long the helper asked
+    /// to wait before trying again, if it said.
+    RateLimited { retry_after: Option<Duration> },
+    /// The helper doesn't accept the account id, e.g. one which isn't a subaccount of
+    /// `testnet`.
+    InvalidAccountId {
+        account_id: AccountId,
+        message: String,
+    },
+    /// Any other failure, with the HTTP status and body of the helper's response.
+    Failed {
+        account_id: AccountId,
+        status: u16,
+        message: String,
+    },
+    /// The helper at `url` could not be reached at all.
+    Unreachable { url: String, message: String },
+}
+
+impl HelperError {
+    /// Whether the helper is unavailable for now, rather than refusing this account: it kept
+    /// rate limiting, failing on its side or couldn't be reached. Another helper, or another
+    /// way of creating accounts, may still succeed.
+    pub fn is_unavailable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::Unreachable { .. } => true,
+            Self::Failed { status, .. } => *status >= 500,
+            Self::InvalidAccountId { .. } => false,
+        }
+    }
+}
+
+impl std::fmt::Display for HelperError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(
+                f,
+                "helper is rate limiting account creation, retry after {:?}",
+                retry_after
+            ),
+            Self::RateLimited { retry_after: None } => {
+                write!(f, "helper is rate limiting account creation")
+            }
+            Self::InvalidAccountId {
+                account_id,
+                message,
+            } => write!(f, "helper rejected account id {}: {}", account_id, message),
+            Self::Failed {
+                account_id,
+                status,
+                message,
+            } => write!(
+                f,
+                "helper could not create {}: {} {}",
+                account_id, status, message
+            ),
+            Self::Unreachable { url, message } => {
+                write!(f, "helper at {} is unreachable: {}", url, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HelperError {}
@@ -0,0 +1,154 @@
+//! Events emitted by contracts as NEP-297 `EVENT_JSON:` logs, which off-chain indexers
+//! follow rather than the logs themselves.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use near_primitives::types::AccountId;
+
+use crate::ExecutionOutcome;
+
+/// Prefix of the logs holding NEP-297 events.
+const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+
+/// A NEP-297 event emitted by a contract, as parsed from an `EVENT_JSON:` log by
+/// [`ExecutionResult::events`](crate::ExecutionResult::events). Serializes as `{"emitter",
+/// "standard", "version", "event", "data"}`, e.g. to keep the events of a run as a fixture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// The contract which emitted the event.
+    pub emitter: AccountId,
+    /// Standard the event belongs to, such as `nep171`.
+    pub standard: String,
+    /// Version of the standard, such as `1.0.0`.
+    pub version: String,
+    /// Name of the event, such as `nft_mint`.
+    pub event: String,
+    /// Payload of the event, `Null` if it has none. For the token standards, a list of
+    /// objects, one per batched operation.
+    pub data: Value,
+}
+
+impl Event {
+    /// Parse `log`, logged by `emitter`. `None` unless it is a valid event.
+    pub fn from_log(emitter: AccountId, log: &str) -> Option<Self> {
+        let json = log.strip_prefix(EVENT_LOG_PREFIX)?;
+        let event: EventLog = serde_json::from_str(json).ok()?;
+        Some(Event {
+            emitter,
+            standard: event.standard,
+            version: event.version,
+            event: event.event,
+            data: event.data,
+        })
+    }
+
+    /// Deserializes the payload of the event, such as a `Vec` of the standard's event
+    /// structs.
+    pub fn data_as<T: DeserializeOwned>(&self) -> anyhow::Result<T> {
+        Ok(serde_json::from_value(self.data.clone())?)
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} by {}", self.standard, self.event, self.emitter)
+    }
+}
+
+#[derive(Deserialize)]
+struct EventLog {
+    standard: String,
+    // Required by NEP-297, but left out by some contracts:
+    #[serde(default)]
+    version: String,
+    event: String,
+    #[serde(default)]
+    data: Value,
+}
+
+/// Events logged by `outcomes`, ordered by the block each receipt executed in, and within
+/// a block in the order the node reports the receipts, which is the order indexers see them
+/// in. Logs which merely start like events, but aren't valid ones, are skipped.
+pub(crate) fn event_stream<'a>(outcomes: impl Iterator<Item = &'a ExecutionOutcome>) -> Vec<Event> {
+    let mut outcomes: Vec<&ExecutionOutcome> = outcomes.collect();
+    // Stable, so receipts of the same block keep their order:
+    outcomes.sort_by_key(|o| o.block_height);
+
+    outcomes
+        .into_iter()
+        .flat_map(|o| {
+            o.logs
+                .iter()
+                .filter_map(move |log| Event::from_log(o.executor_id.clone(), log))
+        })
+        .collect()
+}
+
+/// An event expected by
+/// [`ExecutionResult::assert_events_in_order`](crate::ExecutionResult::assert_events_in_order),
+/// matching events by name, and optionally by standard and emitting contract. Plain strings
+/// convert to it, either as the event name, e.g. `"nft_transfer"`, or as the standard and
+/// the event name, e.g. `"nep171:nft_transfer"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedEvent {
+    event: String,
+    standard: Option<String>,
+    emitter: Option<AccountId>,
+}
+
+impl ExpectedEvent {
+    pub fn new(event: impl Into<String>) -> Self {
+        Self {
+            event: event.into(),
+            standard: None,
+            emitter: None,
+        }
+    }
+
+    /// Only match events of `standard`, such as `nep141`.
+    pub fn standard(mut self, standard: impl Into<String>) -> Self {
+        self.standard = Some(standard.into());
+        self
+    }
+
+    /// Only match events emitted by the contract `emitter`.
+    pub fn emitted_by(mut self, emitter: &AccountId) -> Self {
+        self.emitter = Some(emitter.clone());
+        self
+    }
+
+    pub(crate) fn matches(&self, event: &Event) -> bool {
+        self.event == event.event
+            && self
+                .standard
+                .as_ref()
+                .map_or(true, |s| s == &event.standard)
+            && self.emitter.as_ref().map_or(true, |e| e == &event.emitter)
+    }
+}
+
+impl From<&str> for ExpectedEvent {
+    fn from(event: &str) -> Self {
+        match event.split_once(':') {
+            Some((standard, event)) => ExpectedEvent::new(event).standard(standard),
+            None => ExpectedEvent::new(event),
+        }
+    }
+}
+
+impl fmt::Display for ExpectedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(standard) = &self.standard {
+            write!(f, "{}:", standard)?;
+        }
+        write!(f, "{}", self.event)?;
+        if let Some(emitter) = &self.emitter {
+            write!(f, " by {}", emitter)?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
-pub use near_crypto::{InMemorySigner, PublicKey, Signer};
+pub use near_crypto::{InMemorySigner, KeyType, PublicKey, SecretKey, Signature, Signer};
+pub use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
 pub use near_primitives::borsh;
-pub use near_primitives::types::AccountId;
+pub use near_primitives::hash::CryptoHash;
+pub use near_primitives::types::{AccountId, BlockId, BlockReference, Finality, ShardId};
+pub use near_primitives::views::{BlockView, ChunkView, FinalExecutionOutcomeView};
 
 /// Allow users to use `#[workspaces::basic]` to not use any kind of NEAR runtimes
 /// and just purely use the underlying "basic" runtime to run tasks instead.
@@ -0,0 +1,118 @@
+//! Reusable environment setup for tests. A [`Fixture`] describes how to set up a piece of
+//! the environment (deploy an FT contract, an AMM, create some test users, ...), and
+//! fixtures compose through tuples and can be shared between tests through [`Cached`].
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use tokio::sync::OnceCell;
+
+use crate::trace;
+use crate::{Network, Worker};
+
+/// Fixtures shared through [`Cached`], set up at most once per worker and its clones.
+pub(crate) type FixtureCache =
+    Arc<Mutex<HashMap<TypeId, Arc<OnceCell<Arc<dyn Any + Send + Sync>>>>>>;
+
+#[async_trait]
+pub trait Fixture<T: Network + 'static>: Sized + Send + Sync + 'static {
+    /// Set up the fixture on the network of `worker`.
+    async fn setup(worker: &Worker<T>) -> anyhow::Result<Self>;
+
+    /// Called by [`with_fixture`] once the test body has finished, even if it panicked.
+    async fn teardown(&self, _worker: &Worker<T>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_fixture_for_tuple {
+    ($($name:ident),+ ; $($rev:tt),+) => {
+        #[async_trait]
+        impl<T: Network + 'static, $($name: Fixture<T>),+> Fixture<T> for ($($name,)+) {
+            async fn setup(worker: &Worker<T>) -> anyhow::Result<Self> {
+                Ok(($($name::setup(worker).await?,)+))
+            }
+
+            /// Tears down in reverse order of setup, since later fixtures may depend on
+            /// earlier ones.
+            async fn teardown(&self, worker: &Worker<T>) -> anyhow::Result<()> {
+                $(self.$rev.teardown(worker).await?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_fixture_for_tuple!(A, B; 1, 0);
+impl_fixture_for_tuple!(A, B, C; 2, 1, 0);
+impl_fixture_for_tuple!(A, B, C, D; 3, 2, 1, 0);
+
+/// A fixture that is only set up once per worker, then shared between every test which
+/// asks for it on that worker or one of its clones. The teardown of the inner fixture is
+/// never called, since other tests may still be using it.
+pub struct Cached<F>(Arc<F>);
+
+impl<F> Deref for Cached<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<T: Network + 'static, F: Fixture<T>> Fixture<T> for Cached<F> {
+    async fn setup(worker: &Worker<T>) -> anyhow::Result<Self> {
+        let cell = worker
+            .fixtures()
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<F>())
+            .or_default()
+            .clone();
+
+        // Tests asking for the fixture concurrently wait for a single setup:
+        let fixture = cell
+            .get_or_try_init(|| async {
+                let fixture: Arc<dyn Any + Send + Sync> = Arc::new(F::setup(worker).await?);
+                Ok::<_, anyhow::Error>(fixture)
+            })
+            .await?;
+        let fixture = fixture
+            .clone()
+            .downcast::<F>()
+            .expect("fixture cache is keyed by TypeId");
+        Ok(Cached(fixture))
+    }
+}
+
+/// Set up the fixture `F` on the network of `worker`, run `body` with it, then tear the
+/// fixture down. If `body` panics, teardown is still run before the panic is propagated,
+/// and a teardown failure is only reported, so as not to hide the panic.
+pub async fn with_fixture<T, F, B, Fut>(worker: &Worker<T>, body: B) -> anyhow::Result<Fut::Output>
+where
+    T: Network + 'static,
+    F: Fixture<T>,
+    B: FnOnce(Arc<F>) -> Fut,
+    Fut: Future,
+{
+    let fixture = Arc::new(F::setup(worker).await?);
+    let output = AssertUnwindSafe(body(fixture.clone())).catch_unwind().await;
+    let teardown = fixture.teardown(worker).await;
+
+    match output {
+        Ok(output) => teardown.map(|()| output),
+        Err(panic) => {
+            if let Err(err) = teardown {
+                trace::warn!("fixture teardown failed after the test panicked: {:?}", err);
+            }
+            std::panic::resume_unwind(panic)
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Property testing of contracts. Inputs are generated from random bytes through
+//! [`arbitrary`], and every case runs against a freshly deployed contract, so cases never
+//! observe each other's state. Failing inputs are shrunk before being reported. Enabled with
+//! the `arbitrary` feature.
+//!
+//! ```ignore
+//! #[derive(Debug, arbitrary::Arbitrary)]
+//! enum Op {
+//!     SetStatus(String),
+//!     GetStatus,
+//! }
+//!
+//! Fuzz::new(&worker, STATUS_MSG_WASM_FILEPATH)?
+//!     .cases(20)
+//!     .run(|contract, ops: Vec<Op>| async move {
+//!         // Apply `ops` to `contract`, erroring out if an invariant is violated.
+//!         Ok(())
+//!     })
+//!     .await?;
+//! ```
+
+use std::fmt::Debug;
+use std::future::Future;
+
+use anyhow::anyhow;
+use arbitrary::{Arbitrary, Unstructured};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::network::{AllowDevAccountCreation, AllowStateChanges, Network, TopLevelAccountCreator};
+use crate::seed;
+use crate::{Contract, ContractCode, InitCall, Worker};
+
+/// Fixes the seed of the generated inputs, to reproduce a reported failure.
+const FUZZ_SEED_ENV: &str = "NEAR_WORKSPACES_FUZZ_SEED";
+
+const DEFAULT_CASES: usize = 32;
+const DEFAULT_INPUT_LEN: usize = 256;
+const DEFAULT_SHRINK_ATTEMPTS: usize = 32;
+
+/// A failing case: the generated input, and what the property failed with.
+struct Failure {
+    input: String,
+    error: anyhow::Error,
+}
+
+pub struct Fuzz<'a, T> {
+    worker: &'a Worker<T>,
+    code: Vec<u8>,
+    init: Option<InitCall>,
+    cases: usize,
+    input_len: usize,
+    shrink_attempts: usize,
+    seed: u64,
+}
+
+impl<'a, T> Fuzz<'a, T>
+where
+    T: Network + AllowStateChanges + TopLevelAccountCreator + AllowDevAccountCreation,
+{
+    /// Fuzz the contract `code`, deployed through `worker` for every case. The seed is taken
+    /// from `NEAR_WORKSPACES_FUZZ_SEED` if set, and derived from the run seed otherwise, see
+    /// [`crate::run_seed`].
+    pub fn new(worker: &'a Worker<T>, code: impl ContractCode) -> anyhow::Result<Self> {
+        let seed = match std::env::var(FUZZ_SEED_ENV) {
+            Ok(seed) => seed
+                .parse()
+                .map_err(|err| anyhow!("invalid {}: {}", FUZZ_SEED_ENV, err))?,
+            Err(_) => seed::with_rng(|rng| rng.gen()),
+        };
+
+        Ok(Self {
+            worker,
+            code: code.into_wasm()?,
+            init: None,
+            cases: DEFAULT_CASES,
+            input_len: DEFAULT_INPUT_LEN,
+            shrink_attempts: DEFAULT_SHRINK_ATTEMPTS,
+            seed,
+        })
+    }
+
+    /// Initialize every deployment with `init`.
+    pub fn init(mut self, init: InitCall) -> Self {
+        self.init = Some(init);
+        self
+    }
+
+    /// Number of cases to run. Each one deploys a contract, so keep it small.
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Number of random bytes inputs are generated from. Bounds the size of inputs.
+    pub fn input_len(mut self, input_len: usize) -> Self {
+        self.input_len = input_len;
+        self
+    }
+
+    /// Maximum number of cases to rerun while shrinking a failing input.
+    pub fn shrink_attempts(mut self, shrink_attempts: usize) -> Self {
+        self.shrink_attempts = shrink_attempts;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run `property` on generated inputs until it fails or all cases pass. The error of a
+    /// failing run holds the shrunk input and the seed to reproduce it with.
+    pub async fn run<A, F, Fut>(&self, property: F) -> anyhow::Result<()>
+    where
+        A: for<'b> Arbitrary<'b> + Debug,
+        F: Fn(Contract, A) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for case in 0..self.cases {
+            let mut bytes = vec![0; self.input_len];
+            rng.fill(&mut bytes[..]);
+
+            if let Some(failure) = self.check::<A, F, Fut>(&property, &bytes).await? {
+                let failure = self.shrink::<A, F, Fut>(&property, bytes, failure).await?;
+                return Err(anyhow!(
+                    "property failed on case {} (reproduce with {}={}) for input {}: {:?}",
+                    case,
+                    FUZZ_SEED_ENV,
+                    self.seed,
+                    failure.input,
+                    failure.error
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the case generated from `bytes`. Errors out only if the contract could not be
+    /// deployed; a failing property is returned as a [`Failure`].
+    async fn check<A, F, Fut>(&self, property: &F, bytes: &[u8]) -> anyhow::Result<Option<Failure>>
+    where
+        A: for<'b> Arbitrary<'b> + Debug,
+        F: Fn(Contract, A) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let input = match A::arbitrary_take_rest(Unstructured::new(bytes)) {
+            Ok(input) => input,
+            // Not enough bytes left to generate an input from, so nothing to check:
+            Err(_) => return Ok(None),
+        };
+        let described = format!("{:?}", input);
+
+        let contract = match &self.init {
+            Some(init) => {
+                self.worker
+                    .dev_deploy_and_init(self.code.clone(), init.clone())
+                    .await?
+            }
+            None => self.worker.dev_deploy(self.code.clone()).await?,
+        };
+
+        Ok(property(contract, input).await.err().map(|error| Failure {
+            input: described,
+            error,
+        }))
+    }
+
+    /// Remove chunks of decreasing size from the failing bytes, keeping every removal after
+    /// which the property still fails.
+    async fn shrink<A, F, Fut>(
+        &self,
+        property: &F,
+        mut bytes: Vec<u8>,
+        mut failure: Failure,
+    ) -> anyhow::Result<Failure>
+    where
+        A: for<'b> Arbitrary<'b> + Debug,
+        F: Fn(Contract, A) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let mut attempts = 0;
+        let mut chunk = bytes.len() / 2;
+        while chunk > 0 && attempts < self.shrink_attempts {
+            let mut start = 0;
+            while start < bytes.len() && attempts < self.shrink_attempts {
+                let mut candidate = bytes.clone();
+                candidate.drain(start..(start + chunk).min(bytes.len()));
+                attempts += 1;
+
+                match self.check::<A, F, Fut>(property, &candidate).await? {
+                    Some(smaller) => {
+                        bytes = candidate;
+                        failure = smaller;
+                    }
+                    None => start += chunk,
+                }
+            }
+            chunk /= 2;
+        }
+        Ok(failure)
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversions between near-sdk types and workspaces' types, so values can flow between
+//! contract crates and their integration tests without string round-trips. Enabled with
+//! the `near-sdk` feature.
+//!
+//! Gas needs no conversion: workspaces reports it as a plain `u64`, which near-sdk's `Gas`
+//! converts from.
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+
+use near_primitives::views::FinalExecutionStatus;
+
+use crate::{AccountId, ExecutionResult, NearBalance};
+
+/// Conversions of nearcore's `AccountId`, as used throughout workspaces, into near-sdk's.
+/// Both types are foreign to this crate, hence a trait rather than `From`.
+pub trait AccountIdExt {
+    fn to_sdk(&self) -> near_sdk::AccountId;
+}
+
+impl AccountIdExt for AccountId {
+    fn to_sdk(&self) -> near_sdk::AccountId {
+        // Both sides validate account ids the same way:
+        self.to_string()
+            .try_into()
+            .expect("nearcore account id is not valid for near-sdk")
+    }
+}
+
+/// Converts near-sdk's `AccountId` into the one used throughout workspaces.
+pub fn from_sdk_account_id(account_id: &near_sdk::AccountId) -> AccountId {
+    account_id
+        .to_string()
+        .try_into()
+        .expect("near-sdk account id is not valid for nearcore")
+}
+
+impl From<NearBalance> for near_sdk::json_types::U128 {
+    fn from(balance: NearBalance) -> Self {
+        balance.to_yoctonear().into()
+    }
+}
+
+impl From<near_sdk::json_types::U128> for NearBalance {
+    fn from(balance: near_sdk::json_types::U128) -> Self {
+        NearBalance::from_yoctonear(balance.0)
+    }
+}
+
+/// Turns the outcome of a transaction into the promise result a contract would observe in
+/// a callback, for feeding integration results into near-sdk unit tests.
+impl TryFrom<&ExecutionResult> for near_sdk::PromiseResult {
+    type Error = anyhow::Error;
+
+    fn try_from(result: &ExecutionResult) -> anyhow::Result<Self> {
+        match result.status() {
+            FinalExecutionStatus::SuccessValue(value) => Ok(near_sdk::PromiseResult::Successful(
+                base64::decode(value).map_err(|e| anyhow!("invalid outcome value: {}", e))?,
+            )),
+            FinalExecutionStatus::Failure(_) => Ok(near_sdk::PromiseResult::Failed),
+            FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {
+                Ok(near_sdk::PromiseResult::NotReady)
+            }
+        }
+    }
+}
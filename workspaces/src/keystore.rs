@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+
+use near_crypto::{InMemorySigner, SecretKey};
+use near_primitives::types::AccountId;
+
+use crate::rpc::tool;
+
+/// Where a [`Worker`](crate::Worker) keeps the keys of the accounts it creates, such as dev
+/// accounts, and finds them again. Workers use a [`FileKeyStore`] in the network's keystore
+/// directory unless configured otherwise with
+/// [`Worker::with_keystore`](crate::Worker::with_keystore).
+pub trait KeyStore: Send + Sync {
+    /// Key of `account_id`, or `None` if this store has none.
+    fn load(&self, account_id: &AccountId) -> anyhow::Result<Option<InMemorySigner>>;
+
+    fn store(&self, signer: &InMemorySigner) -> anyhow::Result<()>;
+
+    /// Directory the keys are persisted in, if any. Other files kept across runs, such as
+    /// the cache of [`Worker::dev_deploy_cached`](crate::Worker::dev_deploy_cached), are only
+    /// written to stores with a directory.
+    fn dir(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Keys as JSON files named after their account, in the format of near-cli, e.g. under
+/// `~/.near-credentials/testnet`.
+#[derive(Debug, Clone)]
+pub struct FileKeyStore {
+    dir: PathBuf,
+}
+
+impl FileKeyStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn load(&self, account_id: &AccountId) -> anyhow::Result<Option<InMemorySigner>> {
+        let path = self.dir.join(format!("{}.json", account_id));
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(InMemorySigner::from_file(&path)))
+    }
+
+    fn store(&self, signer: &InMemorySigner) -> anyhow::Result<()> {
+        signer.write_to_file(&tool::credentials_filepath(
+            &self.dir,
+            signer.account_id.clone(),
+        )?);
+        Ok(())
+    }
+
+    fn dir(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+}
+
+/// Keys kept in memory only, so that nothing is written to disk, e.g. on CI. The keys are
+/// lost once the store is dropped. Clones share the same keys.
+#[derive(Clone, Default)]
+pub struct InMemoryKeyStore {
+    keys: Arc<Mutex<HashMap<AccountId, SecretKey>>>,
+}
+
+impl InMemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for InMemoryKeyStore {
+    fn load(&self, account_id: &AccountId) -> anyhow::Result<Option<InMemorySigner>> {
+        let keys = self.keys.lock().unwrap();
+        Ok(keys.get(account_id).map(|secret_key| {
+            InMemorySigner::from_secret_key(account_id.clone(), secret_key.clone())
+        }))
+    }
+
+    fn store(&self, signer: &InMemorySigner) -> anyhow::Result<()> {
+        self.keys
+            .lock()
+            .unwrap()
+            .insert(signer.account_id.clone(), signer.secret_key.clone());
+        Ok(())
+    }
+}
+
+/// Keys read from environment variables, such as secrets injected by CI from a vault. The
+/// variable of an account is named after it, see [`EnvKeyStore::var_name`], and holds its
+/// secret key, e.g. `ed25519:...`. Keys of accounts created through the worker are kept in
+/// memory, as with an [`InMemoryKeyStore`].
+#[derive(Clone)]
+pub struct EnvKeyStore {
+    prefix: String,
+    created: InMemoryKeyStore,
+}
+
+impl EnvKeyStore {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            created: InMemoryKeyStore::new(),
+        }
+    }
+
+    /// Variable holding the key of `account_id`: the prefix, followed by the account id in
+    /// upper case with `.` and `-` replaced by `_`. With the prefix `NEAR_KEY_`, the key of
+    /// `alice.testnet` is read from `NEAR_KEY_ALICE_TESTNET`.
+    pub fn var_name(&self, account_id: &AccountId) -> String {
+        let account_id: String = account_id
+            .to_string()
+            .chars()
+            .map(|c| match c {
+                '.' | '-' => '_',
+                c => c.to_ascii_uppercase(),
+            })
+            .collect();
+        format!("{}{}", self.prefix, account_id)
+    }
+}
+
+impl KeyStore for EnvKeyStore {
+    fn load(&self, account_id: &AccountId) -> anyhow::Result<Option<InMemorySigner>> {
+        let name = self.var_name(account_id);
+        let secret_key = match std::env::var(&name) {
+            Ok(secret_key) => secret_key,
+            Err(_) => return self.created.load(account_id),
+        };
+        let secret_key: SecretKey = secret_key
+            .trim()
+            .parse()
+            .map_err(|err| anyhow!("{} does not hold a valid secret key: {}", name, err))?;
+        Ok(Some(InMemorySigner::from_secret_key(
+            account_id.clone(),
+            secret_key,
+        )))
+    }
+
+    fn store(&self, signer: &InMemorySigner) -> anyhow::Result<()> {
+        self.created.store(signer)
+    }
+}
@@ -1,14 +1,84 @@
+mod account;
+mod block;
+mod compile;
+mod creator;
+mod economics;
+mod error;
+mod event;
 mod exports;
+mod fixture;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "near-sdk")]
+pub mod interop;
+mod keystore;
+mod message;
+mod network;
+mod pool;
+mod race;
+mod result;
 mod rpc;
 mod runtime;
+mod seed;
+mod spoon;
+#[cfg(feature = "standards")]
+pub mod standards;
+mod status;
+mod trace;
+mod wasm;
+mod worker;
 
 #[cfg(not(test))] // Work around for rust-lang/rust#62127
+pub use workspaces_macros::contract_methods;
 pub use workspaces_macros::main;
 pub use workspaces_macros::test;
 
+pub use account::{
+    Account, AccountCredentials, CallBuilder, Contract, CreateAccountBuilder, ViewBuilder,
+};
+pub use block::{Block, Chunk};
+pub use compile::{compile_project, Compile};
+pub use creator::{AccountCreator, SubaccountCreator};
+pub use economics::{ActionKind, Fee, ProtocolCosts};
+pub use error::{
+    AccessKeyRejected, AccountAlreadyExists, AccountLocked, HelperError, KeyRejection,
+};
+pub use event::{Event, ExpectedEvent};
 pub use exports::*;
+pub use fixture::{with_fixture, Cached, Fixture};
+pub use keystore::{EnvKeyStore, FileKeyStore, InMemoryKeyStore, KeyStore};
+pub use message::{sign_message, verify_message, MessagePayload};
+pub use network::{
+    AllowDevAccountCreation, AllowStateChanges, Custom, GenesisConfig, Mainnet, Network,
+    NetworkClient, NetworkInfo, ReadOnly, Sandbox, SandboxBuilder, SandboxVersion, Testnet,
+    TopLevelAccountCreator,
+};
+pub use pool::{PooledWorker, SandboxPool};
+pub use race::{Race, RaceOutcome};
+pub use result::{
+    ExecutionDiff, ExecutionOutcome, ExecutionResult, GasProfile, OutcomeGasProfile, ReceiptCall,
+    ReceiptNode, ViewResult,
+};
 pub use rpc::api::*;
-pub use runtime::{with_sandbox, with_testnet, SandboxRuntime, TestnetRuntime};
+pub use rpc::client::Client;
+pub use rpc::guard::AccountGuard;
+pub use rpc::metrics::{MethodMetrics, RpcMetrics};
+pub use rpc::retry::{ReplacementPolicy, RetryPolicy};
+pub use rpc::types::{
+    AccessKeyInfo, AccountInfo, AccountPatch, ContractCode, Gas, NearBalance, StatePrefix,
+    StateRecords,
+};
+pub use runtime::context::register_tx_hook;
+pub use runtime::{
+    with_mainnet, with_sandbox, with_testnet, MainnetRuntime, SandboxRuntime, TestnetRuntime,
+};
+pub use seed::run_seed;
+pub use spoon::{KnownContract, REF_FINANCE, USDT, WRAP_NEAR};
+pub use status::TransactionStatus;
+pub use worker::{
+    mainnet, sandbox, testnet, Delegation, ImportContractBuilder, RewardFeeFraction,
+    SandboxSnapshot, Worker,
+};
 
 // Used for generated code, Not a public API
 #[doc(hidden)]
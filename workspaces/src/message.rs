@@ -0,0 +1,59 @@
+//! Off-chain message signing as specified by NEP-413. Lets backends which authenticate
+//! users through signed messages be tested alongside the contracts they talk to.
+
+use rand::Rng;
+
+use near_crypto::{PublicKey, Signature, Signer};
+use near_primitives::borsh::{self, BorshSerialize};
+
+/// Prefix prepended to every NEP-413 payload before hashing: `2^31 + 413`. This makes it
+/// impossible for a signed message to also be a valid signed transaction.
+const NEP413_TAG: u32 = (1 << 31) + 413;
+
+/// The message to be signed, along with the data binding it to a specific recipient.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize)]
+pub struct MessagePayload {
+    pub message: String,
+    /// 32 bytes of randomness, used by the recipient to prevent replay attacks.
+    pub nonce: [u8; 32],
+    /// The recipient the message is intended for, e.g. `alice.near` or `myapp.com`.
+    pub recipient: String,
+    pub callback_url: Option<String>,
+}
+
+impl MessagePayload {
+    /// Creates a payload for `recipient` with a freshly generated random nonce.
+    pub fn new(message: impl Into<String>, recipient: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            nonce: rand::thread_rng().gen(),
+            recipient: recipient.into(),
+            callback_url: None,
+        }
+    }
+
+    /// The bytes which get signed: the sha256 hash of the tag followed by the payload.
+    fn signable_bytes(&self) -> Vec<u8> {
+        let mut bytes = NEP413_TAG
+            .try_to_vec()
+            .expect("serializing into a Vec should never fail");
+        self.serialize(&mut bytes)
+            .expect("serializing into a Vec should never fail");
+        near_primitives::hash::hash(&bytes).as_ref().to_vec()
+    }
+}
+
+/// Sign `payload` with the given signer, the same way a wallet would for `signMessage`.
+pub fn sign_message(signer: &dyn Signer, payload: &MessagePayload) -> Signature {
+    signer.sign(&payload.signable_bytes())
+}
+
+/// Verify that `signature` was produced by signing `payload` with the secret key
+/// belonging to `public_key`.
+pub fn verify_message(
+    public_key: &PublicKey,
+    payload: &MessagePayload,
+    signature: &Signature,
+) -> bool {
+    signature.verify(&payload.signable_bytes(), public_key)
+}
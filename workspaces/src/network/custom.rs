@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use near_crypto::{InMemorySigner, PublicKey};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionOutcomeView;
+
+use super::{
+    AllowDevAccountCreation, AllowStateChanges, NetworkClient, NetworkInfo, TopLevelAccountCreator,
+};
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::runtime::local;
+use crate::{ExecutionResult, InitCall};
+
+/// RPC endpoint of the network, e.g. `http://localhost:3030` for a localnet.
+const RPC_URL_ENV: &str = "NEAR_RPC_URL";
+/// Id of the network, used to pick the default keystore directory. Defaults to `custom`.
+const NETWORK_ID_ENV: &str = "NEAR_NETWORK_ID";
+/// Key file of the account used to create top level accounts, such as the
+/// `validator_key.json` of a localnet node.
+const ROOT_ACCOUNT_KEY_FILE_ENV: &str = "NEAR_ROOT_ACCOUNT_KEY_FILE";
+/// Overrides the directory where credentials of created accounts are stored.
+const KEYSTORE_PATH_ENV: &str = "NEAR_KEYSTORE_PATH";
+
+const DEFAULT_NETWORK_ID: &str = "custom";
+
+/// Any network reachable over RPC which is not built in, such as a self-hosted localnet,
+/// betanet or a private shard.
+///
+/// Top level and dev accounts are created by the root account, the same way the sandbox
+/// does with its validator key. Without a root account, only existing accounts can be used.
+pub struct Custom {
+    client: Client,
+    network_id: String,
+    root_account: Option<InMemorySigner>,
+    keystore_path: Option<PathBuf>,
+}
+
+impl Custom {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(rpc_url.into()),
+            network_id: DEFAULT_NETWORK_ID.to_string(),
+            root_account: None,
+            keystore_path: None,
+        }
+    }
+
+    /// Configure the network from the `NEAR_RPC_URL`, `NEAR_NETWORK_ID`,
+    /// `NEAR_ROOT_ACCOUNT_KEY_FILE` and `NEAR_KEYSTORE_PATH` environment variables. Only
+    /// `NEAR_RPC_URL` is required.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let rpc_url = std::env::var(RPC_URL_ENV)
+            .map_err(|_| anyhow!("{} must be set to connect to a custom network", RPC_URL_ENV))?;
+
+        let mut network = Self::new(rpc_url);
+        if let Ok(network_id) = std::env::var(NETWORK_ID_ENV) {
+            network = network.with_network_id(network_id);
+        }
+        if let Some(path) = std::env::var_os(ROOT_ACCOUNT_KEY_FILE_ENV) {
+            network = network.with_root_account(InMemorySigner::from_file(&PathBuf::from(path)));
+        }
+        if let Some(path) = std::env::var_os(KEYSTORE_PATH_ENV) {
+            network = network.with_keystore(path);
+        }
+
+        Ok(network)
+    }
+
+    pub fn with_network_id(mut self, network_id: impl Into<String>) -> Self {
+        self.network_id = network_id.into();
+        self
+    }
+
+    /// Account which pays for and creates top level and dev accounts.
+    pub fn with_root_account(mut self, signer: InMemorySigner) -> Self {
+        self.root_account = Some(signer);
+        self
+    }
+
+    /// Directory to store the credentials of created accounts in. Defaults to
+    /// `~/.near-credentials/<network_id>`.
+    pub fn with_keystore(mut self, path: impl Into<PathBuf>) -> Self {
+        self.keystore_path = Some(path.into());
+        self
+    }
+
+    pub fn network_id(&self) -> &str {
+        &self.network_id
+    }
+
+    fn root_signer(&self) -> anyhow::Result<&InMemorySigner> {
+        self.root_account.as_ref().ok_or_else(|| {
+            anyhow!(
+                "network {} has no root account to create accounts with",
+                self.network_id
+            )
+        })
+    }
+}
+
+impl NetworkClient for Custom {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl NetworkInfo for Custom {
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        match &self.keystore_path {
+            Some(path) => Ok(path.clone()),
+            None => tool::credentials_dir(&self.network_id),
+        }
+    }
+}
+
+impl AllowStateChanges for Custom {}
+
+impl AllowDevAccountCreation for Custom {}
+
+#[async_trait]
+impl TopLevelAccountCreator for Custom {
+    async fn create_tla(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<Option<ExecutionResult>> {
+        let outcome = local::create_top_level_account(
+            &self.client,
+            self.root_signer()?,
+            new_account_id,
+            new_account_pk,
+        )
+        .await?;
+        Ok(Some(outcome))
+    }
+
+    async fn create_tla_and_deploy(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        _signer: &InMemorySigner,
+        code: Vec<u8>,
+        init: Option<InitCall>,
+    ) -> anyhow::Result<FinalExecutionOutcomeView> {
+        local::create_tla_and_deploy(
+            &self.client,
+            self.root_signer()?,
+            new_account_id,
+            new_account_pk,
+            code,
+            init,
+        )
+        .await
+    }
+}
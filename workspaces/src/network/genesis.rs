@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde_json::Value;
+
+use near_crypto::PublicKey;
+use near_primitives::account::{AccessKey, Account};
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::{AccountId, Balance, BlockHeightDelta, Gas};
+use near_primitives::version::ProtocolVersion;
+
+use crate::NearBalance;
+
+/// Overrides of the genesis a sandbox starts from, see
+/// [`SandboxBuilder::genesis`](crate::SandboxBuilder::genesis). Anything left unset keeps the
+/// value `near-sandbox init` generated.
+#[derive(Debug, Clone, Default)]
+pub struct GenesisConfig {
+    epoch_length: Option<BlockHeightDelta>,
+    gas_price: Option<Balance>,
+    gas_limit: Option<Gas>,
+    protocol_version: Option<ProtocolVersion>,
+    validator_stake: Option<NearBalance>,
+    accounts: Vec<(AccountId, PublicKey, NearBalance)>,
+}
+
+impl GenesisConfig {
+    /// Number of blocks per epoch. Short epochs make epoch-based logic, such as staking
+    /// rewards, quick to exercise along with [`Worker::fast_forward`](crate::Worker::fast_forward).
+    pub fn epoch_length(mut self, epoch_length: BlockHeightDelta) -> Self {
+        self.epoch_length = Some(epoch_length);
+        self
+    }
+
+    /// Minimum gas price in yoctoNEAR, which is also the price the sandbox starts at.
+    pub fn gas_price(mut self, gas_price: Balance) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    /// Maximum gas burnt per chunk. The sandbox has a single shard, so this is also the
+    /// maximum gas per block.
+    pub fn gas_limit(mut self, gas_limit: Gas) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Protocol version to start at. The sandbox binary has to support it.
+    pub fn protocol_version(mut self, protocol_version: ProtocolVersion) -> Self {
+        self.protocol_version = Some(protocol_version);
+        self
+    }
+
+    /// Stake of the sandbox's single validator, the root account.
+    pub fn validator_stake(mut self, stake: NearBalance) -> Self {
+        self.validator_stake = Some(stake);
+        self
+    }
+
+    /// Fund `account_id` with `balance` from genesis, with `public_key` as its full access
+    /// key.
+    pub fn add_account(
+        mut self,
+        account_id: AccountId,
+        public_key: PublicKey,
+        balance: NearBalance,
+    ) -> Self {
+        self.accounts.push((account_id, public_key, balance));
+        self
+    }
+
+    /// Rewrite the genesis in `home_dir` with the overrides.
+    pub(crate) fn apply(&self, home_dir: &Path) -> anyhow::Result<()> {
+        let path = home_dir.join("genesis.json");
+        let mut genesis: Value = serde_json::from_slice(&fs::read(&path)?)?;
+
+        if let Some(epoch_length) = self.epoch_length {
+            genesis["epoch_length"] = epoch_length.into();
+        }
+        if let Some(gas_price) = self.gas_price {
+            genesis["min_gas_price"] = gas_price.to_string().into();
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            genesis["gas_limit"] = gas_limit.into();
+        }
+        if let Some(protocol_version) = self.protocol_version {
+            genesis["protocol_version"] = protocol_version.into();
+        }
+
+        let mut total_supply: Balance = genesis["total_supply"]
+            .as_str()
+            .ok_or_else(|| anyhow!("genesis has no total supply"))?
+            .parse()?;
+        let mut records: Vec<StateRecord> = serde_json::from_value(genesis["records"].take())?;
+
+        if let Some(stake) = self.validator_stake {
+            let stake = stake.to_yoctonear();
+            let validator = &mut genesis["validators"][0];
+            let validator_id: AccountId = serde_json::from_value(validator["account_id"].clone())?;
+            validator["amount"] = stake.to_string().into();
+
+            let account = records
+                .iter_mut()
+                .find_map(|record| match record {
+                    StateRecord::Account {
+                        account_id,
+                        account,
+                    } if *account_id == validator_id => Some(account),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow!("genesis has no account for validator {}", validator_id))?;
+            total_supply = total_supply - account.locked() + stake;
+            account.set_locked(stake);
+        }
+
+        for (account_id, public_key, balance) in &self.accounts {
+            let balance = balance.to_yoctonear();
+            total_supply += balance;
+            records.push(StateRecord::Account {
+                account_id: account_id.clone(),
+                // Storage usage is computed by the node as it applies the genesis:
+                account: Account::new(balance, 0, CryptoHash::default(), 0),
+            });
+            records.push(StateRecord::AccessKey {
+                account_id: account_id.clone(),
+                public_key: public_key.clone(),
+                access_key: AccessKey::full_access(),
+            });
+        }
+
+        genesis["records"] = serde_json::to_value(&records)?;
+        // The genesis is rejected unless its total supply adds up:
+        genesis["total_supply"] = total_supply.to_string().into();
+        fs::write(&path, serde_json::to_vec_pretty(&genesis)?)?;
+        Ok(())
+    }
+}
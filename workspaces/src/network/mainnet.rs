@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use super::{NetworkClient, NetworkInfo};
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::runtime::MainnetRuntime;
+
+/// Read-only connection to mainnet. Allows querying contracts, such as spooning their state
+/// into a sandbox, but refuses to send any transaction.
+pub struct Mainnet {
+    client: Client,
+}
+
+impl Mainnet {
+    pub fn new() -> Self {
+        Self::with_rpc(MainnetRuntime::RPC_URL)
+    }
+
+    /// Connect to a mainnet archival node instead, which is required to query state at
+    /// blocks older than a few epochs.
+    pub fn archival() -> Self {
+        Self::with_rpc(MainnetRuntime::ARCHIVAL_RPC_URL)
+    }
+
+    fn with_rpc(rpc_url: &str) -> Self {
+        let client = Client::new(rpc_url.to_string())
+            .with_explorer_url(Some(MainnetRuntime::EXPLORER_URL))
+            .read_only(true);
+
+        Self { client }
+    }
+}
+
+impl Default for Mainnet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkClient for Mainnet {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl NetworkInfo for Mainnet {
+    fn name(&self) -> &'static str {
+        "mainnet"
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        tool::credentials_dir(self.name())
+    }
+}
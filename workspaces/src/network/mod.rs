@@ -0,0 +1,79 @@
+//! Networks a [`Worker`](crate::Worker) can be connected to. Each network owns its RPC
+//! client, and for the sandbox the node process itself, so that several networks can be
+//! used concurrently without any runtime context being entered.
+
+mod custom;
+mod genesis;
+mod mainnet;
+mod read_only;
+mod sandbox;
+mod testnet;
+
+pub use self::custom::Custom;
+pub use self::genesis::GenesisConfig;
+pub use self::mainnet::Mainnet;
+pub use self::read_only::ReadOnly;
+pub use self::sandbox::{Sandbox, SandboxBuilder, SandboxVersion};
+pub use self::testnet::Testnet;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use near_crypto::{InMemorySigner, PublicKey};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionOutcomeView;
+
+use crate::rpc::client::Client;
+use crate::{AccountCreator, ExecutionResult, InitCall};
+
+pub trait NetworkClient {
+    fn client(&self) -> &Client;
+}
+
+pub trait NetworkInfo {
+    /// Name of the network, such as `sandbox` or `testnet`.
+    fn name(&self) -> &'static str;
+
+    /// Directory where the credentials of accounts created on this network are stored.
+    fn keystore_path(&self) -> anyhow::Result<PathBuf>;
+}
+
+#[async_trait]
+pub trait TopLevelAccountCreator {
+    /// Creates a top level account. While in sandbox, we can grab the `ExecutionOutcomeView`,
+    /// but on networks where a helper account creator is used instead, it isn't provided.
+    async fn create_tla(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<Option<ExecutionResult>>;
+
+    async fn create_tla_and_deploy(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        signer: &InMemorySigner,
+        code: Vec<u8>,
+        init: Option<InitCall>,
+    ) -> anyhow::Result<FinalExecutionOutcomeView>;
+
+    /// Creates dev accounts instead, once the network's own way of creating them, such as
+    /// the testnet helper, is unavailable. There is none by default.
+    fn fallback_creator(&self) -> Option<Arc<dyn AccountCreator>> {
+        None
+    }
+}
+
+/// Marker for networks through which transactions can be sent. Workers of networks without
+/// it, such as [`Mainnet`] or any [`ReadOnly`] network, only expose queries, so code holding
+/// such a worker can't change any state by construction.
+pub trait AllowStateChanges {}
+
+/// Marker for networks on which dev accounts can be freely created.
+pub trait AllowDevAccountCreation {}
+
+pub trait Network: NetworkClient + NetworkInfo + Send + Sync {}
+
+impl<T> Network for T where T: NetworkClient + NetworkInfo + Send + Sync {}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+
+use super::{NetworkClient, NetworkInfo};
+use crate::rpc::client::Client;
+
+/// Query-only view of another network, such as `ReadOnly::new(Testnet::new()?)`. Has no
+/// keystore, and none of the methods sending transactions are available on its workers,
+/// which makes it safe to use workspaces' query APIs from analytics or monitoring tooling
+/// running against production networks.
+pub struct ReadOnly<N> {
+    network: N,
+    client: Client,
+}
+
+impl<N> ReadOnly<N>
+where
+    N: NetworkClient + NetworkInfo,
+{
+    pub fn new(network: N) -> Self {
+        let client = network.client().clone().read_only(true);
+        Self { network, client }
+    }
+}
+
+impl<N> NetworkClient for ReadOnly<N> {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl<N> NetworkInfo for ReadOnly<N>
+where
+    N: NetworkInfo,
+{
+    fn name(&self) -> &'static str {
+        self.network.name()
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        Err(anyhow!(
+            "{} is connected to read-only and has no keystore",
+            self.name()
+        ))
+    }
+}
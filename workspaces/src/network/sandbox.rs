@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use near_crypto::{InMemorySigner, PublicKey};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionOutcomeView;
+
+use super::{
+    AllowDevAccountCreation, AllowStateChanges, GenesisConfig, NetworkClient, NetworkInfo,
+    TopLevelAccountCreator,
+};
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::runtime::local::{self, SandboxServer};
+use crate::{ExecutionResult, InitCall};
+
+/// A local sandbox node, spun up on free ports. The node is shut down once this is dropped.
+pub struct Sandbox {
+    server: SandboxServer,
+    client: Client,
+}
+
+impl Sandbox {
+    pub fn new() -> anyhow::Result<Self> {
+        Self::builder().build()
+    }
+
+    /// Spin up a sandbox starting from the state dumped by
+    /// [`Worker::dump_genesis`](crate::Worker::dump_genesis) into `genesis_dir`.
+    pub fn from_genesis(genesis_dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::builder().genesis_dir(genesis_dir).build()
+    }
+
+    /// Configure the sandbox before it starts, such as its genesis.
+    pub fn builder() -> SandboxBuilder {
+        SandboxBuilder::default()
+    }
+
+    /// Home directory of the sandbox node, unique to this instance.
+    pub fn home_dir(&self) -> &Path {
+        self.server.home_dir()
+    }
+
+    /// Address of the node's RPC endpoint, e.g. to reach it through [`crate::Custom`] or
+    /// external tools.
+    pub fn rpc_addr(&self) -> &str {
+        self.client.rpc_addr()
+    }
+
+    pub(crate) fn root_signer(&self) -> InMemorySigner {
+        local::root_account(self.server.home_dir())
+    }
+
+    pub(crate) fn bin_path(&self) -> Option<&Path> {
+        self.server.bin_path()
+    }
+
+    /// See [`SandboxServer::while_stopped`].
+    pub(crate) fn while_stopped<R>(
+        &self,
+        f: impl FnOnce(&Path) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        self.server.while_stopped(f)
+    }
+}
+
+/// Version of a running sandbox node, as returned by
+/// [`Worker::sandbox_version`](crate::Worker::sandbox_version). Its `Display` is meant for
+/// test logs, e.g. `near-sandbox 1.26.0 (build 1.26.0-rc.1, protocol 51)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxVersion {
+    /// Release of nearcore the binary was built from.
+    pub version: String,
+    /// Build identifier, usually the `git describe` of the nearcore commit.
+    pub build: String,
+    /// Protocol version the node currently runs at.
+    pub protocol_version: u32,
+    /// Latest protocol version the binary supports.
+    pub latest_protocol_version: u32,
+    /// The binary the node was started from.
+    pub binary: Option<PathBuf>,
+}
+
+impl SandboxVersion {
+    /// Whether the nearcore release is `version` or later, e.g. to skip cases depending on
+    /// a newer node. Versions are compared by their numeric `major.minor.patch` parts.
+    pub fn at_least(&self, version: &str) -> bool {
+        numeric_parts(&self.version) >= numeric_parts(version)
+    }
+}
+
+impl std::fmt::Display for SandboxVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "near-sandbox {} (build {}, protocol {})",
+            self.version, self.build, self.protocol_version
+        )
+    }
+}
+
+fn numeric_parts(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .take_while(|part| !part.is_empty())
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Builder of a [`Sandbox`], which is spun up once [`SandboxBuilder::build`] is called.
+#[derive(Debug, Default)]
+pub struct SandboxBuilder {
+    genesis_dir: Option<PathBuf>,
+    genesis_config: Option<GenesisConfig>,
+}
+
+impl SandboxBuilder {
+    /// Start from the state dumped by [`Worker::dump_genesis`](crate::Worker::dump_genesis)
+    /// into `genesis_dir`, see [`Sandbox::from_genesis`].
+    pub fn genesis_dir(mut self, genesis_dir: impl AsRef<Path>) -> Self {
+        self.genesis_dir = Some(genesis_dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Override parts of the genesis, e.g. to test protocol-dependent contracts such as
+    /// epoch-based staking:
+    ///
+    /// ```ignore
+    /// let sandbox = Sandbox::builder()
+    ///     .genesis(|genesis| genesis.epoch_length(10).validator_stake(stake))
+    ///     .build()?;
+    /// ```
+    ///
+    /// Overrides apply on top of a genesis dump as well.
+    pub fn genesis(mut self, configure: impl FnOnce(GenesisConfig) -> GenesisConfig) -> Self {
+        self.genesis_config = Some(configure(self.genesis_config.unwrap_or_default()));
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Sandbox> {
+        let mut server = SandboxServer::default();
+        if let Some(genesis_dir) = self.genesis_dir {
+            server = server.with_genesis(genesis_dir);
+        }
+        if let Some(genesis_config) = self.genesis_config {
+            server = server.with_genesis_config(genesis_config);
+        }
+        server.start()?;
+        let client = Client::new(format!("http://localhost:{}", server.rpc_port)).seeded_keys(true);
+
+        Ok(Sandbox { server, client })
+    }
+}
+
+impl NetworkClient for Sandbox {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl NetworkInfo for Sandbox {
+    fn name(&self) -> &'static str {
+        "sandbox"
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        tool::credentials_dir(self.name())
+    }
+}
+
+impl AllowStateChanges for Sandbox {}
+
+impl AllowDevAccountCreation for Sandbox {}
+
+#[async_trait]
+impl TopLevelAccountCreator for Sandbox {
+    async fn create_tla(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<Option<ExecutionResult>> {
+        let outcome = local::create_top_level_account(
+            &self.client,
+            &self.root_signer(),
+            new_account_id,
+            new_account_pk,
+        )
+        .await?;
+        Ok(Some(outcome))
+    }
+
+    async fn create_tla_and_deploy(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        _signer: &InMemorySigner,
+        code: Vec<u8>,
+        init: Option<InitCall>,
+    ) -> anyhow::Result<FinalExecutionOutcomeView> {
+        local::create_tla_and_deploy(
+            &self.client,
+            &self.root_signer(),
+            new_account_id,
+            new_account_pk,
+            code,
+            init,
+        )
+        .await
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use url::Url;
+
+use near_crypto::{InMemorySigner, PublicKey};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionOutcomeView;
+
+use super::{
+    AllowDevAccountCreation, AllowStateChanges, NetworkClient, NetworkInfo, TopLevelAccountCreator,
+};
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::runtime::online;
+use crate::runtime::TestnetRuntime;
+use crate::{Account, AccountCreator, ExecutionResult, InitCall, SubaccountCreator};
+
+/// Key file of the account funding implicit accounts, see [`Testnet::with_funder`].
+const FUNDER_KEY_FILE_ENV: &str = "NEAR_TESTNET_FUNDER_KEY_FILE";
+/// Comma separated helper URLs to create accounts with, see [`Testnet::with_helper_urls`].
+const HELPER_URLS_ENV: &str = "NEAR_TESTNET_HELPER_URLS";
+/// Key file of the account to fall back to, see [`Testnet::with_root_account`].
+const ROOT_ACCOUNT_KEY_FILE_ENV: &str = "NEAR_TESTNET_ROOT_ACCOUNT_KEY_FILE";
+
+pub struct Testnet {
+    client: Client,
+    helper_urls: Vec<Url>,
+    funder: Option<InMemorySigner>,
+    root_account: Option<InMemorySigner>,
+}
+
+impl Testnet {
+    /// Connect to testnet. If `NEAR_TESTNET_HELPER_URLS`, `NEAR_TESTNET_FUNDER_KEY_FILE` or
+    /// `NEAR_TESTNET_ROOT_ACCOUNT_KEY_FILE` are set, they configure the helpers, the funder
+    /// and the root account, see [`Testnet::with_helper_urls`], [`Testnet::with_funder`] and
+    /// [`Testnet::with_root_account`].
+    pub fn new() -> anyhow::Result<Self> {
+        let client = Client::new(TestnetRuntime::RPC_URL.to_string())
+            .with_explorer_url(Some(TestnetRuntime::EXPLORER_URL));
+        let helper_urls = match std::env::var(HELPER_URLS_ENV) {
+            Ok(urls) => urls
+                .split(',')
+                .map(|url| {
+                    Url::parse(url.trim())
+                        .map_err(|err| anyhow!("invalid {} {:?}: {}", HELPER_URLS_ENV, url, err))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Err(_) => vec![Url::parse(TestnetRuntime::HELPER_URL).unwrap()],
+        };
+        let funder = std::env::var_os(FUNDER_KEY_FILE_ENV)
+            .map(|path| tool::signer_from_file(&PathBuf::from(path)))
+            .transpose()?;
+        let root_account = std::env::var_os(ROOT_ACCOUNT_KEY_FILE_ENV)
+            .map(|path| tool::signer_from_file(&PathBuf::from(path)))
+            .transpose()?;
+
+        Ok(Self {
+            client,
+            helper_urls,
+            funder,
+            root_account,
+        })
+    }
+
+    /// Helpers, or faucets, to create accounts with, instead of the testnet helper alone.
+    /// They are tried in order, moving on to the next one while a helper is unavailable, see
+    /// [`HelperError::is_unavailable`](crate::HelperError::is_unavailable).
+    pub fn with_helper_urls(mut self, helper_urls: impl IntoIterator<Item = Url>) -> Self {
+        self.helper_urls = helper_urls.into_iter().collect();
+        self
+    }
+
+    /// Account which funds implicit accounts created through
+    /// [`Worker::create_implicit_account`](crate::Worker::create_implicit_account). Creating
+    /// those doesn't rely on the testnet helper, so tests can keep running while it is down.
+    pub fn with_funder(mut self, signer: InMemorySigner) -> Self {
+        self.funder = Some(signer);
+        self
+    }
+
+    pub(crate) fn funder(&self) -> Option<&InMemorySigner> {
+        self.funder.as_ref()
+    }
+
+    /// Funded account whose subaccounts are created as dev accounts instead, once none of
+    /// the helpers is available, such as `dev-....ci-runner.testnet` for `ci-runner.testnet`.
+    pub fn with_root_account(mut self, signer: InMemorySigner) -> Self {
+        self.root_account = Some(signer);
+        self
+    }
+
+    pub(crate) fn helper_urls(&self) -> &[Url] {
+        &self.helper_urls
+    }
+}
+
+impl NetworkClient for Testnet {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl NetworkInfo for Testnet {
+    fn name(&self) -> &'static str {
+        "testnet"
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        tool::credentials_dir(self.name())
+    }
+}
+
+impl AllowStateChanges for Testnet {}
+
+impl AllowDevAccountCreation for Testnet {}
+
+#[async_trait]
+impl TopLevelAccountCreator for Testnet {
+    async fn create_tla(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<Option<ExecutionResult>> {
+        online::create_top_level_account(
+            &self.client,
+            &self.helper_urls,
+            new_account_id,
+            new_account_pk,
+        )
+        .await
+    }
+
+    async fn create_tla_and_deploy(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        signer: &InMemorySigner,
+        code: Vec<u8>,
+        init: Option<InitCall>,
+    ) -> anyhow::Result<FinalExecutionOutcomeView> {
+        online::create_tla_and_deploy(
+            &self.client,
+            &self.helper_urls,
+            new_account_id,
+            new_account_pk,
+            signer,
+            code,
+            init,
+        )
+        .await
+    }
+
+    fn fallback_creator(&self) -> Option<Arc<dyn AccountCreator>> {
+        let root = self.root_account.clone()?;
+        let root = Account::new(root.account_id.clone(), root, self.client.clone());
+        Some(Arc::new(SubaccountCreator::new(root)))
+    }
+}
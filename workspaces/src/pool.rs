@@ -0,0 +1,137 @@
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use futures::future::try_join_all;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{Sandbox, SandboxSnapshot, Worker};
+
+/// Sandboxes started ahead of time and leased to one test at a time, so that a large suite
+/// pays the startup cost of `size` nodes rather than one per test, while every test still
+/// starts from a fresh chain. Each sandbox is reset with [`Worker::restore`] before being
+/// leased again. Cloning a pool is cheap and the clones share the same sandboxes:
+///
+/// ```ignore
+/// let pool = SandboxPool::new(4).await?;
+/// try_join_all(cases.iter().map(|case| async {
+///     let worker = pool.lease().await?;
+///     run_case(&worker, case).await
+/// }))
+/// .await?;
+/// ```
+///
+/// The sandboxes are shut down once the pool and all its leases are dropped.
+#[derive(Clone)]
+pub struct SandboxPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    idle: Mutex<Vec<PoolEntry>>,
+    available: Arc<Semaphore>,
+    size: usize,
+}
+
+struct PoolEntry {
+    worker: Worker<Sandbox>,
+    /// State the sandbox started with, which it is reset to.
+    snapshot: SandboxSnapshot,
+    /// Whether a previous lease might have changed the state.
+    dirty: bool,
+}
+
+impl SandboxPool {
+    /// Start `size` sandboxes, concurrently. More sandboxes means less waiting for a lease
+    /// in highly parallel suites, at the cost of the memory and startup time of each node.
+    pub async fn new(size: usize) -> anyhow::Result<Self> {
+        if size == 0 {
+            return Err(anyhow!("a sandbox pool needs at least one sandbox"));
+        }
+
+        let entries = try_join_all((0..size).map(|_| async {
+            let sandbox = tokio::task::spawn_blocking(Sandbox::new).await??;
+            let worker = Worker::new(sandbox);
+            let snapshot = worker.snapshot().await?;
+            Ok::<_, anyhow::Error>(PoolEntry {
+                worker,
+                snapshot,
+                dirty: false,
+            })
+        }))
+        .await?;
+
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                idle: Mutex::new(entries),
+                available: Arc::new(Semaphore::new(size)),
+                size,
+            }),
+        })
+    }
+
+    /// Number of sandboxes in the pool.
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Wait for a sandbox to be free, and lease it until the returned worker is dropped. The
+    /// sandbox is in the state it started with, whatever previous leases did with it.
+    pub async fn lease(&self) -> anyhow::Result<PooledWorker> {
+        let permit = self.inner.available.clone().acquire_owned().await?;
+        let entry = self
+            .inner
+            .idle
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("a sandbox is idle for every available permit");
+
+        // Holding the entry in the lease right away puts it back in the pool, still dirty, if
+        // resetting it fails or this future is dropped half way through. The snapshot is kept
+        // around meanwhile, for the reset which carries on in the background, see
+        // `Worker::restore`.
+        let mut lease = PooledWorker {
+            entry: Some(entry),
+            pool: self.inner.clone(),
+            _permit: permit,
+        };
+        let entry = lease.entry.as_mut().expect("entry is only taken on drop");
+        if entry.dirty {
+            entry.worker.restore(&entry.snapshot).await?;
+            entry.dirty = false;
+        }
+        Ok(lease)
+    }
+}
+
+/// A sandbox leased from a [`SandboxPool`], returned to the pool once this is dropped. Derefs
+/// to the [`Worker`] of the sandbox, whose clones shouldn't outlive the lease: the next lease
+/// resets the sandbox under them.
+pub struct PooledWorker {
+    entry: Option<PoolEntry>,
+    pool: Arc<PoolInner>,
+    // Released after the entry is back in the pool, see `Drop`:
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledWorker {
+    type Target = Worker<Sandbox>;
+
+    fn deref(&self) -> &Worker<Sandbox> {
+        &self
+            .entry
+            .as_ref()
+            .expect("entry is only taken on drop")
+            .worker
+    }
+}
+
+impl Drop for PooledWorker {
+    fn drop(&mut self) {
+        if let Some(mut entry) = self.entry.take() {
+            entry.dirty = true;
+            self.pool.idle.lock().unwrap().push(entry);
+        }
+    }
+}
@@ -1 +1,110 @@
+pub use anyhow;
+pub use serde_json;
 pub use tokio;
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use crate::trace;
+
+/// Narrows down the networks a test listing several of them runs against, as a comma
+/// separated list of names, e.g. `sandbox` on CI runners without access to testnet.
+const NETWORKS_ENV: &str = "NEAR_WORKSPACES_NETWORKS";
+
+/// What a test body running against several networks can return.
+pub trait TestOutcome {
+    fn failure(&self) -> Option<String>;
+
+    /// Returned when every network of the test got skipped.
+    fn skipped() -> Self;
+}
+
+impl TestOutcome for () {
+    fn failure(&self) -> Option<String> {
+        None
+    }
+
+    fn skipped() -> Self {}
+}
+
+impl<E: std::fmt::Debug> TestOutcome for Result<(), E> {
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().map(|err| format!("{:?}", err))
+    }
+
+    fn skipped() -> Self {
+        Ok(())
+    }
+}
+
+/// Run the same test body against each network in turn, as generated for
+/// `#[workspaces::test(sandbox, testnet)]`. Every network runs even if an earlier one failed,
+/// and the result of each is reported before failing the test as a whole.
+pub fn run_on_networks<O: TestOutcome>(runs: Vec<(&str, Box<dyn FnOnce() -> O>)>) -> O {
+    let enabled = std::env::var(NETWORKS_ENV).ok();
+    let mut failed = Vec::new();
+    let mut outcome = None;
+    for (network, run) in runs {
+        if let Some(enabled) = &enabled {
+            if !enabled.split(',').any(|name| name.trim() == network) {
+                trace::info!("[{}] skipped, not listed in {}", network, NETWORKS_ENV);
+                continue;
+            }
+        }
+
+        match panic::catch_unwind(AssertUnwindSafe(run)) {
+            Ok(result) => match result.failure() {
+                None => {
+                    trace::info!("[{}] passed", network);
+                    outcome = Some(result);
+                }
+                Some(err) => {
+                    trace::warn!("[{}] failed: {}", network, err);
+                    failed.push(network);
+                }
+            },
+            // The panic message itself was already printed by the panic hook:
+            Err(_) => {
+                trace::warn!("[{}] panicked", network);
+                failed.push(network);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        panic!("test failed on: {}", failed.join(", "));
+    }
+    outcome.unwrap_or_else(O::skipped)
+}
+
+/// Run a test body up to `flaky_retries` more times while it fails, as generated for
+/// `#[workspaces::test(flaky_retries = 2)]`, returning the outcome of the first passing run or
+/// of the last one. Every run sets up its own runtime and workers, which are torn down even
+/// if it panics.
+pub fn run_with_retries<O: TestOutcome>(flaky_retries: usize, mut run: impl FnMut() -> O) -> O {
+    let runs = flaky_retries + 1;
+    for attempt in 1..runs {
+        match panic::catch_unwind(AssertUnwindSafe(&mut run)) {
+            Ok(result) => match result.failure() {
+                None => return result,
+                Some(err) => trace::warn!("run {}/{} failed, retrying: {}", attempt, runs, err),
+            },
+            Err(_) => trace::warn!("run {}/{} panicked, retrying", attempt, runs),
+        }
+    }
+    run()
+}
+
+/// Fail the test once `timeout` elapses, as generated for
+/// `#[workspaces::test(timeout = "120s")]`. The body is dropped first, tearing down the
+/// workers it set up.
+pub async fn with_timeout<F: Future>(timeout: Option<Duration>, body: F) -> F::Output {
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, body).await {
+            Ok(output) => output,
+            Err(_) => panic!("test timed out after {:?}", timeout),
+        },
+        None => body.await,
+    }
+}
@@ -0,0 +1,194 @@
+use futures::future::try_join_all;
+use serde::Serialize;
+
+use near_primitives::transaction::{Action, FunctionCallAction, SignedTransaction, TransferAction};
+use near_primitives::types::AccountId;
+
+use crate::rpc::client::Client;
+use crate::{Account, ExecutionResult, NearBalance, TransactionStatus, DEFAULT_CALL_FN_GAS};
+
+/// Conflicting transactions of a single account, submitted at once to check how a contract or
+/// backend handles the one the network ends up accepting, e.g. a wallet double-spending:
+///
+/// ```ignore
+/// let outcome = Race::same_nonce(&alice)
+///     .transfer(bob.id(), amount)
+///     .transfer(carol.id(), amount)
+///     .run()
+///     .await?;
+/// let winner = outcome.assert_single_winner();
+/// ```
+pub struct Race<'a> {
+    account: &'a Account,
+    same_nonce: bool,
+    txs: Vec<Result<(AccountId, Action), String>>,
+}
+
+impl<'a> Race<'a> {
+    /// Transactions signed with the same nonce, of which the protocol includes at most one.
+    pub fn same_nonce(account: &'a Account) -> Self {
+        Self {
+            account,
+            same_nonce: true,
+            txs: Vec::new(),
+        }
+    }
+
+    /// Transactions with consecutive nonces, all of which are included, e.g. to withdraw the
+    /// same funds from a contract twice and check that only one of them succeeds. Transactions
+    /// the signer can't pay for are never included, so all of them have to be affordable.
+    pub fn concurrent(account: &'a Account) -> Self {
+        Self {
+            account,
+            same_nonce: false,
+            txs: Vec::new(),
+        }
+    }
+
+    pub fn transfer(mut self, receiver_id: &AccountId, amount: NearBalance) -> Self {
+        let tx = amount
+            .check_sane()
+            .map(|()| {
+                let action = Action::Transfer(TransferAction {
+                    deposit: amount.to_yoctonear(),
+                });
+                (receiver_id.clone(), action)
+            })
+            .map_err(|err| err.to_string());
+        self.txs.push(tx);
+        self
+    }
+
+    /// A call to `method_name` with JSON `args`, attaching `deposit`.
+    pub fn call<T: Serialize>(
+        mut self,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: T,
+        deposit: NearBalance,
+    ) -> Self {
+        let tx = serde_json::to_vec(&args)
+            .map(|args| {
+                let action = Action::FunctionCall(FunctionCallAction {
+                    method_name: method_name.to_string(),
+                    args,
+                    gas: DEFAULT_CALL_FN_GAS,
+                    deposit: deposit.to_yoctonear(),
+                });
+                (contract_id.clone(), action)
+            })
+            .map_err(|err| format!("serde_json error: {:?}", err));
+        self.txs.push(tx);
+        self
+    }
+
+    /// Sign all the transactions, broadcast them concurrently, and wait for them to be
+    /// executed. Transactions which lost a same-nonce race are never included, so they have
+    /// no result.
+    pub async fn run(self) -> Result<RaceOutcome, String> {
+        let txs = self.txs.into_iter().collect::<Result<Vec<_>, _>>()?;
+        let client = self.account.client();
+        let signer = self.account.signer();
+
+        let mut signed = Vec::new();
+        let mut nonce_and_hash = None;
+        for (receiver_id, action) in txs {
+            let (nonce, block_hash) = match nonce_and_hash {
+                Some(nonce_and_hash) if self.same_nonce => nonce_and_hash,
+                _ => {
+                    client
+                        .next_nonce(self.account.id().clone(), signer.public_key())
+                        .await?
+                }
+            };
+            nonce_and_hash = Some((nonce, block_hash));
+            signed.push(SignedTransaction::from_actions(
+                nonce,
+                self.account.id().clone(),
+                receiver_id,
+                signer,
+                vec![action],
+                block_hash,
+            ));
+        }
+
+        let statuses = try_join_all(
+            signed
+                .into_iter()
+                .map(|tx| client.send_tx_async(tx, signer)),
+        )
+        .await?;
+        let results = if self.same_nonce {
+            wait_for_winner(client, statuses).await?
+        } else {
+            let results = try_join_all(statuses.into_iter().map(TransactionStatus::wait)).await?;
+            results.into_iter().map(Some).collect()
+        };
+        Ok(RaceOutcome { results })
+    }
+}
+
+/// Poll all `statuses` until one of them got included, which rules out all the others.
+async fn wait_for_winner(
+    client: &Client,
+    statuses: Vec<TransactionStatus>,
+) -> Result<Vec<Option<ExecutionResult>>, String> {
+    if statuses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let policy = client.retry_policy("tx");
+    let mut attempt = 1;
+    loop {
+        for (i, status) in statuses.iter().enumerate() {
+            if status.status().await?.is_some() {
+                let mut results: Vec<_> = statuses.iter().map(|_| None).collect();
+                results[i] = Some(status.clone().wait().await?);
+                return Ok(results);
+            }
+        }
+        if attempt >= policy.attempts() {
+            return Err(format!(
+                "none of {} racing transactions got included after {} attempts",
+                statuses.len(),
+                attempt
+            ));
+        }
+        tokio::time::sleep(policy.delay(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// Results of the transactions of a [`Race`], in the order they were added to it.
+#[derive(Debug, Clone)]
+pub struct RaceOutcome {
+    results: Vec<Option<ExecutionResult>>,
+}
+
+impl RaceOutcome {
+    /// Result of the `index`th transaction, or `None` if it was never included.
+    pub fn result(&self, index: usize) -> Option<&ExecutionResult> {
+        self.results.get(index).and_then(Option::as_ref)
+    }
+
+    /// Indices of the transactions which were included and succeeded.
+    pub fn winners(&self) -> impl Iterator<Item = usize> + '_ {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.as_ref().map_or(false, ExecutionResult::is_success))
+            .map(|(index, _)| index)
+    }
+
+    /// Asserts that exactly one transaction succeeded, and returns its index.
+    pub fn assert_single_winner(&self) -> usize {
+        let winners: Vec<usize> = self.winners().collect();
+        match winners[..] {
+            [winner] => winner,
+            _ => panic!(
+                "expected a single successful transaction out of {}, got {:?}",
+                self.results.len(),
+                winners
+            ),
+        }
+    }
+}
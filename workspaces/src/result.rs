@@ -0,0 +1,958 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::anyhow;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::errors::TxExecutionError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, Balance, BlockHeight, Gas};
+use near_primitives::views::{
+    ActionView, ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
+    FinalExecutionStatus, ReceiptEnumView, ReceiptView,
+};
+
+use crate::event::event_stream;
+use crate::rpc::tool;
+use crate::{Event, ExpectedEvent, NearBalance};
+
+/// Result of executing a transaction, along with the outcomes of all the receipts it
+/// spawned.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ExecutionResult {
+    status: FinalExecutionStatus,
+    transaction_hash: CryptoHash,
+    transaction_outcome: ExecutionOutcome,
+    receipt_outcomes: Vec<ExecutionOutcome>,
+    /// Explorer of the network the transaction was executed on, if it has one.
+    explorer_url: Option<&'static str>,
+    /// Explanation of the failure to add to error messages, such as the methods the contract
+    /// has when the called one doesn't exist.
+    hint: Option<String>,
+    raw: FinalExecutionOutcomeView,
+}
+
+/// Outcome of a single transaction or receipt.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ExecutionOutcome {
+    /// Hash of the transaction, or id of the receipt, this is the outcome of.
+    pub id: CryptoHash,
+    /// The account the outcome was executed on, i.e. the contract that emitted the logs.
+    pub executor_id: AccountId,
+    pub logs: Vec<String>,
+    pub gas_burnt: Gas,
+    /// What the burnt gas cost. Receipts can execute in later blocks than the transaction,
+    /// at a different gas price.
+    pub tokens_burnt: NearBalance,
+    /// Block the outcome was included in.
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    /// Receipts spawned by this outcome, such as cross-contract calls and their callbacks,
+    /// see [`ExecutionResult::children`].
+    pub receipt_ids: Vec<CryptoHash>,
+    status: ExecutionStatusView,
+}
+
+impl ExecutionOutcome {
+    /// Price per unit of gas the outcome was charged, in yoctoNEAR. `None` if no gas was
+    /// burnt. The pinned protocol version has no priority fees, so this is the block's
+    /// gas price.
+    pub fn gas_price(&self) -> Option<Balance> {
+        if self.gas_burnt == 0 {
+            return None;
+        }
+        Some(self.tokens_burnt.to_yoctonear() / Balance::from(self.gas_burnt))
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, ExecutionStatusView::Failure(_))
+    }
+
+    /// NEP-297 events among the logs of this outcome, such as one yielded by
+    /// [`Worker::stream_outcomes`](crate::Worker::stream_outcomes).
+    pub fn events(&self) -> Vec<Event> {
+        self.logs
+            .iter()
+            .filter_map(|log| Event::from_log(self.executor_id.clone(), log))
+            .collect()
+    }
+
+    pub fn is_same_block(&self, other: &ExecutionOutcome) -> bool {
+        self.block_hash == other.block_hash
+    }
+
+    /// How many blocks after `other` this outcome executed, or `None` if it executed before
+    /// it.
+    pub fn blocks_after(&self, other: &ExecutionOutcome) -> Option<u64> {
+        self.block_height.checked_sub(other.block_height)
+    }
+
+    /// Panics unless this outcome executed in the same block as `other`.
+    pub fn assert_same_block(&self, other: &ExecutionOutcome) {
+        if !self.is_same_block(other) {
+            panic!(
+                "expected outcomes on {} and {} in the same block, but they executed at heights {} and {}",
+                self.executor_id, other.executor_id, self.block_height, other.block_height
+            );
+        }
+    }
+
+    /// Panics unless this outcome executed in a later block than `other`.
+    pub fn assert_later_block(&self, other: &ExecutionOutcome) {
+        if self.block_height <= other.block_height {
+            panic!(
+                "expected outcome on {} to execute after the one on {}, but they executed at heights {} and {}",
+                self.executor_id, other.executor_id, self.block_height, other.block_height
+            );
+        }
+    }
+
+    /// Why the outcome failed, if it did.
+    pub fn failure(&self) -> Option<&TxExecutionError> {
+        match &self.status {
+            ExecutionStatusView::Failure(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ExecutionOutcome {
+    fn new(
+        view: &ExecutionOutcomeWithIdView,
+        block_heights: &HashMap<CryptoHash, BlockHeight>,
+    ) -> Self {
+        Self {
+            id: view.id,
+            executor_id: view.outcome.executor_id.clone(),
+            logs: view.outcome.logs.clone(),
+            gas_burnt: view.outcome.gas_burnt,
+            tokens_burnt: NearBalance::from_yoctonear(view.outcome.tokens_burnt),
+            block_hash: view.block_hash,
+            block_height: block_heights[&view.block_hash],
+            receipt_ids: view.outcome.receipt_ids.clone(),
+            status: view.outcome.status.clone(),
+        }
+    }
+}
+
+impl ExecutionResult {
+    /// Execution status. Contains the result in case of successful execution.
+    pub fn status(&self) -> &FinalExecutionStatus {
+        &self.status
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, FinalExecutionStatus::SuccessValue(_))
+    }
+
+    /// Hash of the transaction that was executed.
+    pub fn transaction_hash(&self) -> &CryptoHash {
+        &self.transaction_hash
+    }
+
+    /// Link to the transaction on the block explorer of the network, if it has one.
+    pub fn explorer_link(&self) -> Option<String> {
+        self.explorer_url
+            .map(|explorer_url| tool::explorer_link(&self.transaction_hash, explorer_url))
+    }
+
+    pub fn transaction_outcome(&self) -> &ExecutionOutcome {
+        &self.transaction_outcome
+    }
+
+    /// Outcomes of the receipts spawned by the transaction, in execution order.
+    pub fn receipts(&self) -> &[ExecutionOutcome] {
+        &self.receipt_outcomes
+    }
+
+    /// The transaction outcome followed by all receipt outcomes, in execution order.
+    pub fn outcomes(&self) -> impl Iterator<Item = &ExecutionOutcome> {
+        std::iter::once(&self.transaction_outcome).chain(self.receipt_outcomes.iter())
+    }
+
+    /// Outcomes which failed. A transaction can succeed overall while some of its receipts
+    /// failed, such as a cross-contract call whose failure was handled in a callback.
+    pub fn failures(&self) -> Vec<&ExecutionOutcome> {
+        self.outcomes().filter(|o| o.is_failure()).collect()
+    }
+
+    /// The receipt which failed first, in execution order. When a cross-contract call fails,
+    /// this is the failing call itself, rather than any callback failing because of it.
+    pub fn failed_receipt(&self) -> Option<&ExecutionOutcome> {
+        self.receipt_outcomes.iter().find(|o| o.is_failure())
+    }
+
+    /// Outcome of the transaction or receipt `id`, if it is part of this result.
+    pub fn outcome(&self, id: &CryptoHash) -> Option<&ExecutionOutcome> {
+        self.outcomes().find(|o| &o.id == id)
+    }
+
+    /// Outcomes of the receipts spawned by `outcome`, following the tree of receipts down
+    /// from [`ExecutionResult::transaction_outcome`].
+    pub fn children(&self, outcome: &ExecutionOutcome) -> Vec<&ExecutionOutcome> {
+        outcome
+            .receipt_ids
+            .iter()
+            .filter_map(|id| self.outcome(id))
+            .collect()
+    }
+
+    /// Total gas burnt by the transaction and all of its receipts.
+    pub fn total_gas_burnt(&self) -> Gas {
+        self.outcomes().map(|o| o.gas_burnt).sum()
+    }
+
+    /// Tokens burnt across the transaction and all of its receipts, each at the gas price of
+    /// the block it executed in.
+    pub fn total_cost(&self) -> NearBalance {
+        NearBalance::from_yoctonear(self.outcomes().map(|o| o.tokens_burnt.to_yoctonear()).sum())
+    }
+
+    /// Logs of the transaction and all of its receipts, in execution order.
+    pub fn logs(&self) -> Vec<&str> {
+        self.outcomes()
+            .flat_map(|o| o.logs.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Logs emitted by `account_id` across all receipts of the transaction. Useful
+    /// when several contracts are involved and their logs interleave.
+    pub fn logs_for(&self, account_id: &AccountId) -> Vec<&str> {
+        self.outcomes()
+            .filter(|o| &o.executor_id == account_id)
+            .flat_map(|o| o.logs.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Logs across all receipts of the transaction which contain `pattern`.
+    pub fn logs_matching(&self, pattern: &str) -> Vec<&str> {
+        self.logs()
+            .into_iter()
+            .filter(|log| log.contains(pattern))
+            .collect()
+    }
+
+    /// NEP-297 events emitted across all receipts, ordered by the block their receipt
+    /// executed in, e.g. to check the payload of the events of a token contract:
+    ///
+    /// ```ignore
+    /// let events = result.events();
+    /// assert_eq!(events[0].event, "ft_transfer");
+    /// let transfers: Vec<FtTransfer> = events[0].data_as()?;
+    /// ```
+    pub fn events(&self) -> Vec<Event> {
+        event_stream(self.outcomes())
+    }
+
+    /// Panics unless the NEP-297 events emitted across all receipts include `expected` in that
+    /// order, with any other events in between, e.g. to lock down the order in which
+    /// off-chain indexers see the events of several contracts:
+    ///
+    /// ```ignore
+    /// result.assert_events_in_order([
+    ///     ExpectedEvent::from("nep141:ft_transfer").emitted_by(token.id()),
+    ///     ExpectedEvent::new("swap").emitted_by(dex.id()),
+    /// ]);
+    /// ```
+    ///
+    /// Events are ordered by the block their receipt executed in, see [`ExpectedEvent`] for
+    /// how they are matched.
+    pub fn assert_events_in_order<E: Into<ExpectedEvent>>(
+        &self,
+        expected: impl IntoIterator<Item = E>,
+    ) {
+        let events = event_stream(self.outcomes());
+        let mut remaining = events.iter();
+        for (index, expected) in expected.into_iter().map(Into::into).enumerate() {
+            if !remaining.any(|event| expected.matches(event)) {
+                let emitted: Vec<String> = events.iter().map(ToString::to_string).collect();
+                panic!(
+                    "transaction {} did not emit {} after the {} events expected before it; events emitted: [{}]",
+                    self.describe(),
+                    expected,
+                    index,
+                    emitted.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Bytes returned by the called method. Errors out if the transaction did not succeed.
+    pub fn raw_value(&self) -> anyhow::Result<Vec<u8>> {
+        match &self.status {
+            FinalExecutionStatus::SuccessValue(value) => Ok(base64::decode(value)?),
+            status => Err(anyhow!(
+                "transaction {} did not succeed: {:?}{}",
+                self.describe(),
+                status,
+                self.hint()
+            )),
+        }
+    }
+
+    /// Deserializes the JSON value returned by the called method.
+    pub fn json<T: DeserializeOwned>(&self) -> anyhow::Result<T> {
+        Ok(serde_json::from_slice(&self.raw_value()?)?)
+    }
+
+    /// Deserializes the Borsh value returned by the called method.
+    pub fn borsh<T: BorshDeserialize>(&self) -> anyhow::Result<T> {
+        Ok(T::try_from_slice(&self.raw_value()?)?)
+    }
+
+    /// Panics if the transaction did not succeed. On networks with a block explorer, the
+    /// panic message links to the transaction.
+    pub fn assert_success(&self) {
+        if !self.is_success() {
+            panic!(
+                "transaction {} failed: {:?}{}",
+                self.describe(),
+                self.status,
+                self.hint()
+            );
+        }
+    }
+
+    /// Panics unless the JSON value returned by the called method holds `expected` at
+    /// `pointer`, a JSON pointer such as `/tokens/0/owner_id`.
+    pub fn assert_json_at(&self, pointer: &str, expected: impl Serialize) {
+        let value = self
+            .json::<Value>()
+            .unwrap_or_else(|err| panic!("could not decode returned JSON: {:?}", err));
+        assert_json_at(&value, pointer, expected);
+    }
+
+    /// Outcomes executed on `account_id`, in execution order.
+    pub fn outcomes_for(&self, account_id: &AccountId) -> Vec<&ExecutionOutcome> {
+        self.outcomes()
+            .filter(|o| &o.executor_id == account_id)
+            .collect()
+    }
+
+    /// Number of blocks between the transaction and its last receipt. Zero if everything
+    /// executed in a single block.
+    pub fn block_span(&self) -> u64 {
+        self.outcomes()
+            .map(|o| o.block_height)
+            .max()
+            .unwrap_or(self.transaction_outcome.block_height)
+            - self.transaction_outcome.block_height
+    }
+
+    /// Compare with the result of executing the same call elsewhere, such as on another
+    /// version of the contract, e.g. to validate an upgrade.
+    pub fn diff(&self, other: &ExecutionResult) -> ExecutionDiff {
+        let receipts = |result: &ExecutionResult| -> Vec<AccountId> {
+            result
+                .receipts()
+                .iter()
+                .map(|o| o.executor_id.clone())
+                .collect()
+        };
+        let (before, after) = (receipts(self), receipts(other));
+
+        let logs = self.logs();
+        let other_logs = other.logs();
+
+        ExecutionDiff {
+            status: if self.status != other.status {
+                Some((self.status.clone(), other.status.clone()))
+            } else {
+                None
+            },
+            gas_burnt: (self.total_gas_burnt(), other.total_gas_burnt()),
+            logs_removed: missing_from(&logs, &other_logs),
+            logs_added: missing_from(&other_logs, &logs),
+            receipts: if before != after {
+                Some((before, after))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Gas burnt per outcome and per cost, e.g. to track gas regressions of a contract:
+    ///
+    /// ```ignore
+    /// let result = contract.call("set_status").args_json(args).transact().await?;
+    /// println!("{}", result.gas_profile());
+    /// ```
+    pub fn gas_profile(&self) -> GasProfile {
+        GasProfile::new(&self.raw)
+    }
+
+    /// The outcome as returned by the RPC node, for fields this type doesn't expose.
+    pub fn raw(&self) -> &FinalExecutionOutcomeView {
+        &self.raw
+    }
+
+    pub fn into_inner(self) -> FinalExecutionOutcomeView {
+        self.raw
+    }
+
+    fn describe(&self) -> String {
+        tool::describe_tx(&self.transaction_hash, self.explorer_url)
+    }
+
+    pub(crate) fn with_hint(mut self, hint: String) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Whether the transaction failed because the called method doesn't exist.
+    pub(crate) fn is_method_not_found(&self) -> bool {
+        self.receipt_outcomes
+            .first()
+            .and_then(ExecutionOutcome::failure)
+            .map_or(false, |err| format!("{:?}", err).contains("MethodNotFound"))
+    }
+
+    fn hint(&self) -> String {
+        self.hint
+            .as_ref()
+            .map(|hint| format!(": {}", hint))
+            .unwrap_or_default()
+    }
+}
+
+impl ExecutionResult {
+    /// Builds the result from the RPC view. `block_heights` must contain the height of every
+    /// block the outcomes were included in, see [`ExecutionResult::block_hashes`].
+    pub(crate) fn new(
+        view: FinalExecutionOutcomeView,
+        block_heights: &HashMap<CryptoHash, BlockHeight>,
+        explorer_url: Option<&'static str>,
+    ) -> Self {
+        ExecutionResult {
+            status: view.status.clone(),
+            transaction_hash: view.transaction.hash,
+            transaction_outcome: ExecutionOutcome::new(&view.transaction_outcome, block_heights),
+            receipt_outcomes: view
+                .receipts_outcome
+                .iter()
+                .map(|outcome| ExecutionOutcome::new(outcome, block_heights))
+                .collect(),
+            explorer_url,
+            hint: None,
+            raw: view,
+        }
+    }
+
+    /// Function calls among `receipts`, the receipts of this result, in execution order.
+    pub(crate) fn receipt_calls(&self, receipts: Vec<ReceiptView>) -> Vec<ReceiptCall> {
+        let order: HashMap<CryptoHash, usize> = self
+            .raw
+            .receipts_outcome
+            .iter()
+            .enumerate()
+            .map(|(index, outcome)| (outcome.id, index))
+            .collect();
+
+        let mut calls: Vec<(usize, ReceiptCall)> = Vec::new();
+        for receipt in receipts {
+            let actions = match receipt.receipt {
+                ReceiptEnumView::Action { actions, .. } => actions,
+                ReceiptEnumView::Data { .. } => continue,
+            };
+            let index = order
+                .get(&receipt.receipt_id)
+                .copied()
+                .unwrap_or(usize::MAX);
+            calls.extend(actions.into_iter().filter_map(|action| match action {
+                ActionView::FunctionCall {
+                    method_name,
+                    gas,
+                    deposit,
+                    ..
+                } => Some((
+                    index,
+                    ReceiptCall {
+                        receipt_id: receipt.receipt_id,
+                        predecessor_id: receipt.predecessor_id.clone(),
+                        receiver_id: receipt.receiver_id.clone(),
+                        method_name,
+                        deposit: NearBalance::from_yoctonear(deposit),
+                        gas,
+                    },
+                )),
+                _ => None,
+            }));
+        }
+        // Stable, so calls batched in a single receipt keep their order:
+        calls.sort_by_key(|(index, _)| *index);
+        calls.into_iter().map(|(_, call)| call).collect()
+    }
+
+    /// Tree of `receipts`, the receipts of this result, down from the receipt the transaction
+    /// was converted to.
+    pub(crate) fn receipt_tree(&self, receipts: Vec<ReceiptView>) -> anyhow::Result<ReceiptNode> {
+        let mut receipts: HashMap<CryptoHash, ReceiptView> = receipts
+            .into_iter()
+            .map(|receipt| (receipt.receipt_id, receipt))
+            .collect();
+        let root = self
+            .transaction_outcome
+            .receipt_ids
+            .first()
+            .ok_or_else(|| anyhow!("transaction {} spawned no receipt", self.transaction_hash))?;
+        self.receipt_node(root, &mut receipts)
+    }
+
+    fn receipt_node(
+        &self,
+        id: &CryptoHash,
+        receipts: &mut HashMap<CryptoHash, ReceiptView>,
+    ) -> anyhow::Result<ReceiptNode> {
+        let outcome = self
+            .outcome(id)
+            .ok_or_else(|| anyhow!("no outcome for receipt {}", id))?;
+        let receipt = receipts
+            .remove(id)
+            .ok_or_else(|| anyhow!("node returned no receipt {}", id))?;
+        let actions = match receipt.receipt {
+            ReceiptEnumView::Action { actions, .. } => actions,
+            ReceiptEnumView::Data { .. } => Vec::new(),
+        };
+        let children = outcome
+            .receipt_ids
+            .iter()
+            .map(|child| self.receipt_node(child, receipts))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(ReceiptNode {
+            receipt_id: receipt.receipt_id,
+            predecessor_id: receipt.predecessor_id,
+            receiver_id: receipt.receiver_id,
+            actions,
+            outcome: outcome.clone(),
+            children,
+        })
+    }
+
+    /// Hashes of the blocks the outcomes of `view` were included in, one per outcome.
+    pub(crate) fn block_hashes(view: &FinalExecutionOutcomeView) -> Vec<CryptoHash> {
+        std::iter::once(&view.transaction_outcome)
+            .chain(view.receipts_outcome.iter())
+            .map(|outcome| outcome.block_hash)
+            .collect()
+    }
+}
+
+/// A function call made by a receipt of a transaction, as returned by
+/// [`Worker::receipt_calls`](crate::Worker::receipt_calls): the call of the transaction
+/// itself, and every cross-contract call down its promise chain.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ReceiptCall {
+    pub receipt_id: CryptoHash,
+    /// The account making the call: the signer for the call of the transaction itself, and
+    /// the calling contract for cross-contract calls.
+    pub predecessor_id: AccountId,
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    /// Deposit attached to the call, as it was forwarded to the receiver.
+    pub deposit: NearBalance,
+    /// Gas attached to the call, not the gas it burnt.
+    pub gas: Gas,
+}
+
+/// A receipt of a transaction, along with its outcome and the receipts it spawned, as
+/// returned by [`Worker::receipt_tree`](crate::Worker::receipt_tree).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ReceiptNode {
+    pub receipt_id: CryptoHash,
+    /// The account which sent the receipt: the signer for the receipt of the transaction
+    /// itself, the calling contract for cross-contract calls, and `system` for refunds.
+    pub predecessor_id: AccountId,
+    pub receiver_id: AccountId,
+    pub actions: Vec<ActionView>,
+    pub outcome: ExecutionOutcome,
+    /// Receipts spawned by this one, such as cross-contract calls and their callbacks.
+    pub children: Vec<ReceiptNode>,
+}
+
+impl ReceiptNode {
+    /// This receipt followed by all receipts down its tree, depth first.
+    pub fn flatten(&self) -> Vec<&ReceiptNode> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.flatten());
+        }
+        nodes
+    }
+
+    /// The receipt down this tree which failed first. When a cross-contract call fails, this
+    /// is the failing call itself, rather than any callback failing because of it.
+    pub fn find_failure(&self) -> Option<&ReceiptNode> {
+        self.flatten()
+            .into_iter()
+            .filter(|node| node.outcome.is_failure())
+            .min_by_key(|node| node.outcome.block_height)
+    }
+
+    /// Name of the first method this receipt calls, if it is a function call.
+    pub fn method_name(&self) -> Option<&str> {
+        self.actions.iter().find_map(|action| match action {
+            ActionView::FunctionCall { method_name, .. } => Some(method_name.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Differences between two [`ExecutionResult`]s, see [`ExecutionResult::diff`]. Displays
+/// as a readable report.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ExecutionDiff {
+    /// Both statuses, holding the return values, if they differ.
+    pub status: Option<(FinalExecutionStatus, FinalExecutionStatus)>,
+    /// Total gas burnt by both executions.
+    pub gas_burnt: (Gas, Gas),
+    /// Logs only emitted by the first execution.
+    pub logs_removed: Vec<String>,
+    /// Logs only emitted by the second execution.
+    pub logs_added: Vec<String>,
+    /// Executors of the receipts of both executions, if they differ.
+    pub receipts: Option<(Vec<AccountId>, Vec<AccountId>)>,
+}
+
+impl ExecutionDiff {
+    /// Whether both executions behaved the same, apart from the gas they burnt.
+    pub fn is_same_behavior(&self) -> bool {
+        self.status.is_none()
+            && self.logs_removed.is_empty()
+            && self.logs_added.is_empty()
+            && self.receipts.is_none()
+    }
+}
+
+impl std::fmt::Display for ExecutionDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((before, after)) = &self.status {
+            writeln!(
+                f,
+                "status: {} -> {}",
+                describe_status(before),
+                describe_status(after)
+            )?;
+        }
+
+        let (before, after) = self.gas_burnt;
+        write!(f, "gas burnt: {} -> {}", before, after)?;
+        if before != 0 {
+            let change = (after as f64 - before as f64) / before as f64 * 100.0;
+            write!(f, " ({:+.1}%)", change)?;
+        }
+        writeln!(f)?;
+
+        if let Some((before, after)) = &self.receipts {
+            writeln!(f, "receipts: {:?} -> {:?}", before, after)?;
+        }
+
+        if !self.logs_removed.is_empty() || !self.logs_added.is_empty() {
+            writeln!(f, "logs:")?;
+            for log in &self.logs_removed {
+                writeln!(f, "  - {}", log)?;
+            }
+            for log in &self.logs_added {
+                writeln!(f, "  + {}", log)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Gas burnt by an execution, broken down by outcome and by cost, see
+/// [`ExecutionResult::gas_profile`]. Displays as a readable report.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GasProfile {
+    pub total_gas_burnt: Gas,
+    /// One entry per outcome, the transaction's first, then the receipts' in execution order.
+    pub outcomes: Vec<OutcomeGasProfile>,
+    /// Gas burnt per cost, such as `STORAGE_WRITE_BASE` or `FUNCTION_CALL`, summed over all
+    /// outcomes.
+    pub costs: BTreeMap<String, Gas>,
+}
+
+/// Gas burnt by a single transaction or receipt.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct OutcomeGasProfile {
+    pub executor_id: AccountId,
+    pub gas_burnt: Gas,
+    /// Gas burnt per cost, as reported by the node. Empty for the transaction itself, whose
+    /// gas is only that of converting it into a receipt.
+    pub costs: BTreeMap<String, Gas>,
+}
+
+impl GasProfile {
+    fn new(view: &FinalExecutionOutcomeView) -> Self {
+        let outcomes: Vec<_> = std::iter::once(&view.transaction_outcome)
+            .chain(view.receipts_outcome.iter())
+            .map(|outcome| OutcomeGasProfile {
+                executor_id: outcome.outcome.executor_id.clone(),
+                gas_burnt: outcome.outcome.gas_burnt,
+                costs: outcome.outcome.metadata.gas_profile.iter().flatten().fold(
+                    BTreeMap::new(),
+                    |mut costs, cost| {
+                        *costs.entry(cost.cost.clone()).or_insert(0) += cost.gas_used;
+                        costs
+                    },
+                ),
+            })
+            .collect();
+
+        let mut costs = BTreeMap::new();
+        for (cost, gas) in outcomes.iter().flat_map(|outcome| &outcome.costs) {
+            *costs.entry(cost.clone()).or_insert(0) += gas;
+        }
+
+        Self {
+            total_gas_burnt: outcomes.iter().map(|outcome| outcome.gas_burnt).sum(),
+            outcomes,
+            costs,
+        }
+    }
+}
+
+impl std::fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "gas burnt: {}", self.total_gas_burnt)?;
+        for outcome in &self.outcomes {
+            writeln!(f, "  {}: {}", outcome.executor_id, outcome.gas_burnt)?;
+            write_costs(f, &outcome.costs, "    ")?;
+        }
+        if !self.costs.is_empty() {
+            writeln!(f, "by cost:")?;
+            write_costs(f, &self.costs, "  ")?;
+        }
+        Ok(())
+    }
+}
+
+/// Costs from the most to the least expensive, names aligned.
+fn write_costs(
+    f: &mut std::fmt::Formatter<'_>,
+    costs: &BTreeMap<String, Gas>,
+    indent: &str,
+) -> std::fmt::Result {
+    let mut costs: Vec<_> = costs.iter().collect();
+    costs.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let width = costs.iter().map(|(cost, _)| cost.len()).max().unwrap_or(0);
+    for (cost, gas) in costs {
+        writeln!(f, "{}{:width$} {}", indent, cost, gas, width = width)?;
+    }
+    Ok(())
+}
+
+/// Entries of `logs` which are not in `other`, counting repeated entries separately.
+fn missing_from(logs: &[&str], other: &[&str]) -> Vec<String> {
+    let mut other = other.to_vec();
+    logs.iter()
+        .filter(|log| match other.iter().position(|o| o == *log) {
+            Some(i) => {
+                other.remove(i);
+                false
+            }
+            None => true,
+        })
+        .map(|log| log.to_string())
+        .collect()
+}
+
+/// Status with its return value decoded, as text when it is valid UTF-8.
+fn describe_status(status: &FinalExecutionStatus) -> String {
+    match status {
+        FinalExecutionStatus::SuccessValue(value) => match base64::decode(value) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => format!("returned {}", text),
+                Err(_) => format!("returned base64 {}", value),
+            },
+            Err(_) => format!("returned base64 {}", value),
+        },
+        status => format!("{:?}", status),
+    }
+}
+
+/// Value returned by a view method, along with the logs it emitted.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ViewResult {
+    pub result: Vec<u8>,
+    pub logs: Vec<String>,
+}
+
+impl ViewResult {
+    /// Deserializes the JSON value returned by the view method.
+    pub fn json<T: DeserializeOwned>(&self) -> anyhow::Result<T> {
+        Ok(serde_json::from_slice(&self.result)?)
+    }
+
+    /// Deserializes the Borsh value returned by the view method.
+    pub fn borsh<T: BorshDeserialize>(&self) -> anyhow::Result<T> {
+        Ok(T::try_from_slice(&self.result)?)
+    }
+
+    /// Panics unless the JSON value returned by the view method holds `expected` at
+    /// `pointer`, a JSON pointer such as `/tokens/0/owner_id`.
+    pub fn assert_json_at(&self, pointer: &str, expected: impl Serialize) {
+        let value = self
+            .json::<Value>()
+            .unwrap_or_else(|err| panic!("could not decode returned JSON: {:?}", err));
+        assert_json_at(&value, pointer, expected);
+    }
+}
+
+fn assert_json_at(value: &Value, pointer: &str, expected: impl Serialize) {
+    let expected = serde_json::to_value(expected).expect("expected value is not valid JSON");
+    let actual = match value.pointer(pointer) {
+        Some(actual) => actual,
+        None => {
+            // Point at the deepest part of the path which does exist, which is usually where
+            // the expected shape went wrong:
+            let mut found = "";
+            for (i, _) in pointer.match_indices('/').skip(1) {
+                if value.pointer(&pointer[..i]).is_none() {
+                    break;
+                }
+                found = &pointer[..i];
+            }
+            panic!(
+                "nothing at {} in returned JSON, deepest existing path is {:?}:\n{}",
+                pointer,
+                found,
+                pretty(value.pointer(found).unwrap_or(value))
+            );
+        }
+    };
+
+    if actual != &expected {
+        panic!(
+            "mismatch at {} in returned JSON\nexpected:\n{}\nactual:\n{}",
+            pointer,
+            pretty(&expected),
+            pretty(actual)
+        );
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+// Serialization of results into a stable schema of plain JSON types, independent of how
+// near-primitives represents them. Fields may be added, but existing ones keep their name
+// and meaning. Hashes are base58 strings, balances are yoctoNEAR amounts as decimal strings
+// (they don't fit in a JSON number), and returned values are base64 strings.
+
+/// `{"type": "success", "value": <base64>}`, `{"type": "failure", "error": <string>}` or
+/// `{"type": "pending"}`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StatusSchema {
+    Success { value: Option<String> },
+    Failure { error: String },
+    Pending,
+}
+
+impl From<&FinalExecutionStatus> for StatusSchema {
+    fn from(status: &FinalExecutionStatus) -> Self {
+        match status {
+            FinalExecutionStatus::SuccessValue(value) => StatusSchema::Success {
+                value: Some(value.clone()),
+            },
+            FinalExecutionStatus::Failure(err) => StatusSchema::Failure {
+                error: err.to_string(),
+            },
+            FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {
+                StatusSchema::Pending
+            }
+        }
+    }
+}
+
+impl From<&ExecutionStatusView> for StatusSchema {
+    fn from(status: &ExecutionStatusView) -> Self {
+        match status {
+            ExecutionStatusView::SuccessValue(value) => StatusSchema::Success {
+                value: Some(value.clone()),
+            },
+            // Execution continues in another receipt, which holds the actual value:
+            ExecutionStatusView::SuccessReceiptId(_) => StatusSchema::Success { value: None },
+            ExecutionStatusView::Failure(err) => StatusSchema::Failure {
+                error: err.to_string(),
+            },
+            ExecutionStatusView::Unknown => StatusSchema::Pending,
+        }
+    }
+}
+
+/// Serializes as `{"executor_id", "logs", "gas_burnt", "tokens_burnt", "block_hash",
+/// "block_height", "status"}`.
+impl Serialize for ExecutionOutcome {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Schema<'a> {
+            executor_id: String,
+            logs: &'a [String],
+            gas_burnt: Gas,
+            tokens_burnt: String,
+            block_hash: String,
+            block_height: BlockHeight,
+            status: StatusSchema,
+        }
+
+        Schema {
+            executor_id: self.executor_id.to_string(),
+            logs: &self.logs,
+            gas_burnt: self.gas_burnt,
+            tokens_burnt: self.tokens_burnt.to_yoctonear().to_string(),
+            block_hash: self.block_hash.to_string(),
+            block_height: self.block_height,
+            status: (&self.status).into(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes as `{"transaction_hash", "status", "total_gas_burnt", "total_cost",
+/// "transaction_outcome", "receipts"}`, with outcomes serialized as [`ExecutionOutcome`].
+impl Serialize for ExecutionResult {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Schema<'a> {
+            transaction_hash: String,
+            status: StatusSchema,
+            total_gas_burnt: Gas,
+            total_cost: String,
+            transaction_outcome: &'a ExecutionOutcome,
+            receipts: &'a [ExecutionOutcome],
+        }
+
+        Schema {
+            transaction_hash: self.transaction_hash.to_string(),
+            status: (&self.status).into(),
+            total_gas_burnt: self.total_gas_burnt(),
+            total_cost: self.total_cost().to_yoctonear().to_string(),
+            transaction_outcome: &self.transaction_outcome,
+            receipts: &self.receipt_outcomes,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes as `{"result": <base64>, "logs"}`.
+impl Serialize for ViewResult {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Schema<'a> {
+            result: String,
+            logs: &'a [String],
+        }
+
+        Schema {
+            result: base64::encode(&self.result),
+            logs: &self.logs,
+        }
+        .serialize(serializer)
+    }
+}
@@ -1,174 +1,151 @@
-use super::tool;
-use super::types::{AccountInfo, NearBalance};
-
-use anyhow::anyhow;
-use std::collections::HashMap;
-use std::path::Path;
-
-use crate::runtime::context::MISSING_RUNTIME_ERROR;
-use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
-use near_jsonrpc_client::methods::{
-    self,
-    sandbox_patch_state::{RpcSandboxPatchStateRequest, RpcSandboxPatchStateResponse},
+use super::types::{
+    AccessKeyInfo, AccountInfo, ContractCode, NearBalance, StatePrefix, StateRecords,
 };
-use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryRequest};
+
+use serde::de::DeserializeOwned;
+use std::future::Future;
+
+use crate::runtime::context;
+use crate::trace;
+use crate::{
+    AccountAlreadyExists, Block, Chunk, ExecutionResult, HelperError, RetryPolicy, ViewResult,
+};
+use near_crypto::{InMemorySigner, PublicKey, Signer};
+use near_jsonrpc_client::methods::sandbox_patch_state::RpcSandboxPatchStateResponse;
 use near_primitives::borsh::BorshSerialize;
-use near_primitives::state_record::StateRecord;
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{Action, FunctionCallAction};
 use near_primitives::types::{
-    AccountId, Balance, BlockReference, Finality, FunctionArgs, Gas, StoreKey,
+    AccountId, Balance, BlockId, BlockReference, FunctionArgs, Gas, ShardId,
 };
-use near_primitives::views::{FinalExecutionOutcomeView, FinalExecutionStatus, QueryRequest};
 
 pub(crate) const NEAR_BASE: Balance = 1_000_000_000_000_000_000_000_000;
-const ERR_INVALID_VARIANT: &str =
-    "Incorrect variant retrieved while querying: maybe a bug in RPC code?";
-const DEV_ACCOUNT_SEED: &str = "testificate";
-const DEFAULT_CALL_FN_GAS: Gas = 10000000000000;
-
-#[derive(PartialEq, Eq, Clone, Debug)]
-pub struct CallExecutionResult {
-    /// Execution status. Contains the result in case of successful execution.
-    pub status: FinalExecutionStatus,
-    /// Total gas burnt by the call execution
-    pub total_gas_burnt: Gas,
+pub(crate) const DEFAULT_CALL_FN_GAS: Gas = 10000000000000;
+
+/// A function call executed within the same transaction as a contract deployment, so the
+/// contract is never observable in a deployed-but-uninitialized state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitCall {
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub deposit: NearBalance,
 }
 
-impl From<FinalExecutionOutcomeView> for CallExecutionResult {
-    fn from(transaction_result: FinalExecutionOutcomeView) -> Self {
-        CallExecutionResult {
-            status: transaction_result.status,
-            total_gas_burnt: transaction_result.transaction_outcome.outcome.gas_burnt
-                + transaction_result
-                    .receipts_outcome
-                    .iter()
-                    .map(|t| t.outcome.gas_burnt)
-                    .sum::<u64>(),
+impl InitCall {
+    pub fn new(method_name: impl Into<String>, args: Vec<u8>) -> Self {
+        Self {
+            method_name: method_name.into(),
+            args,
+            deposit: NearBalance::default(),
         }
     }
+
+    pub fn deposit(mut self, deposit: NearBalance) -> Self {
+        self.deposit = deposit;
+        self
+    }
+
+    pub(crate) fn into_action(self) -> Action {
+        Action::FunctionCall(FunctionCallAction {
+            method_name: self.method_name,
+            args: self.args,
+            gas: DEFAULT_CALL_FN_GAS,
+            deposit: self.deposit.to_yoctonear(),
+        })
+    }
 }
 
 pub async fn display_account_info(account_id: AccountId) -> Result<AccountInfo, String> {
-    let query_resp = tool::json_client()
-        .call(&RpcQueryRequest {
-            block_reference: Finality::Final.into(),
-            request: QueryRequest::ViewAccount {
-                account_id: account_id.clone(),
-            },
-        })
-        .await
-        .map_err(|err| err.to_string())?;
-
-    let account_view = match query_resp.kind {
-        QueryResponseKind::ViewAccount(result) => result,
-        _ => return Err("Error call result".to_owned()),
-    };
-
-    Ok(AccountInfo {
-        account_id,
-        block_height: query_resp.block_height,
-        block_hash: query_resp.block_hash,
-        balance: NearBalance::from_yoctonear(account_view.amount),
-        stake: NearBalance::from_yoctonear(account_view.locked),
-        used_storage_bytes: account_view.storage_usage,
-    })
+    context::worker().display_account_info(account_id).await
+}
+
+/// Same as [`display_account_info`] for several accounts at once, fetched in a single
+/// round trip where the RPC endpoint supports JSON-RPC batching.
+pub async fn display_account_infos(
+    account_ids: Vec<AccountId>,
+) -> Result<Vec<AccountInfo>, String> {
+    context::worker().display_account_infos(account_ids).await
 }
 
 pub async fn transfer_near(
     signer: &dyn Signer,
     signer_id: AccountId,
     receiver_id: AccountId,
-    amount_yocto: Balance,
-) -> Result<CallExecutionResult, String> {
-    let (access_key, _, block_hash) =
-        tool::access_key(signer_id.clone(), signer.public_key()).await?;
-
-    let tx = SignedTransaction::send_money(
-        access_key.nonce + 1,
-        signer_id,
-        receiver_id,
-        signer,
-        amount_yocto,
-        block_hash,
-    );
-
-    let transaction_info = tool::send_tx(tx).await?;
-    Ok(transaction_info.into())
+    amount: NearBalance,
+) -> Result<ExecutionResult, String> {
+    context::worker()
+        .transfer_near(signer, signer_id, receiver_id, amount)
+        .await
 }
 
+/// Positional form of a function call. The [`crate::Contract`] and [`crate::Account`]
+/// handles returned by a [`crate::Worker`] offer a builder for this instead.
 pub async fn call(
     signer: &dyn Signer,
     signer_id: AccountId,
     contract_id: AccountId,
     method_name: String,
     args: Vec<u8>,
-    deposit: Option<Balance>,
-) -> Result<CallExecutionResult, String> {
-    let (access_key, _, block_hash) =
-        tool::access_key(signer_id.clone(), signer.public_key()).await?;
-    let tx = SignedTransaction::call(
-        access_key.nonce + 1,
-        signer_id,
-        contract_id,
-        signer,
-        deposit.unwrap_or(0),
-        method_name,
-        args,
-        DEFAULT_CALL_FN_GAS,
-        block_hash,
-    );
-    let transaction_info = tool::send_tx(tx).await?;
-    Ok(transaction_info.into())
+    deposit: Option<NearBalance>,
+) -> Result<ExecutionResult, String> {
+    context::worker()
+        .call(signer, signer_id, contract_id, method_name, args, deposit)
+        .await
 }
 
+/// Query a view method. See [`crate::Worker::view`].
 pub async fn view(
     contract_id: AccountId,
     method_name: String,
     args: FunctionArgs,
-) -> Result<serde_json::Value, String> {
-    let query_resp = tool::json_client()
-        .call(&RpcQueryRequest {
-            block_reference: Finality::Final.into(),
-            request: QueryRequest::CallFunction {
-                account_id: contract_id,
-                method_name,
-                args,
-            },
-        })
+) -> Result<ViewResult, String> {
+    context::worker().view(contract_id, method_name, args).await
+}
+
+/// Calls a view method following the `from_index`/`limit` pagination convention used by
+/// the NEAR standards (e.g. `nft_tokens`). See [`crate::Worker::view_paginated`].
+pub async fn view_paginated<T: DeserializeOwned>(
+    contract_id: AccountId,
+    method_name: String,
+    args: serde_json::Value,
+    page_size: u64,
+) -> Result<Vec<T>, String> {
+    context::worker()
+        .view_paginated(contract_id, method_name, args, page_size)
         .await
-        .map_err(|err| format!("Failed to fetch query for view method: {:?}", err))?;
+}
 
-    let call_result = match query_resp.kind {
-        QueryResponseKind::CallResult(result) => result.result,
-        _ => return Err("Error call result".to_string()),
-    };
+/// Balance, storage usage and code hash of `account_id`.
+pub async fn view_account(account_id: AccountId) -> Result<AccountInfo, String> {
+    context::worker().view_account(account_id).await
+}
+
+/// Wasm code deployed to `account_id`.
+pub async fn view_code(account_id: AccountId) -> Result<Vec<u8>, String> {
+    context::worker().view_code(account_id).await
+}
 
-    let call_result_str = String::from_utf8(call_result).map_err(|e| e.to_string())?;
-    let serde_call_result: serde_json::Value = serde_json::from_str(&call_result_str)
-        .map_err(|err| format!("serde_json error: {:?}", err))?;
+pub async fn view_access_key_list(account_id: AccountId) -> Result<Vec<AccessKeyInfo>, String> {
+    context::worker().view_access_key_list(account_id).await
+}
 
-    Ok(serde_call_result)
+/// The block at `block_reference`, a block height or hash through `BlockReference::BlockId`,
+/// or e.g. `BlockReference::Finality(Finality::Final)` for the latest final block.
+pub async fn view_block(block_reference: BlockReference) -> Result<Block, String> {
+    context::worker().view_block(block_reference).await
 }
 
+pub async fn view_chunk(block_id: BlockId, shard_id: ShardId) -> Result<Chunk, String> {
+    context::worker().view_chunk(block_id, shard_id).await
+}
+
+/// State of the contract, optionally restricted to keys starting with `prefix`. State too
+/// large for a single query is fetched in pages, as described on
+/// [`Worker::view_state`](crate::Worker::view_state).
 pub async fn view_state(
     contract_id: AccountId,
-    prefix: Option<StoreKey>,
-) -> anyhow::Result<HashMap<String, Vec<u8>>> {
-    let query_resp = tool::json_client()
-        .call(&methods::query::RpcQueryRequest {
-            block_reference: BlockReference::Finality(Finality::Final),
-            request: QueryRequest::ViewState {
-                account_id: contract_id,
-                prefix: prefix.unwrap_or_else(|| vec![].into()),
-            },
-        })
-        .await
-        .map_err(|err| anyhow!("Failed to query state: {:?}", err))?;
-
-    match query_resp.kind {
-        QueryResponseKind::ViewState(state) => tool::into_state_map(&state.values),
-        _ => Err(anyhow!(ERR_INVALID_VARIANT)),
-    }
+    prefix: Option<StatePrefix>,
+) -> anyhow::Result<StateRecords> {
+    context::worker().view_state(contract_id, prefix).await
 }
 
 pub async fn patch_state<T>(
@@ -179,26 +156,33 @@ pub async fn patch_state<T>(
 where
     T: BorshSerialize,
 {
+    let value = T::try_to_vec(value).unwrap();
+    patch_state_bytes(account_id, key, value).await
+}
+
+/// Same as [`patch_state`], but takes an already serialized value, such as the raw
+/// bytes returned from [`view_state`]. The key can be binary as well.
+pub async fn patch_state_bytes(
+    account_id: AccountId,
+    key: impl AsRef<[u8]>,
+    value: Vec<u8>,
+) -> Result<RpcSandboxPatchStateResponse, String> {
     // Patch state only exists within sandbox
     crate::runtime::assert_within(&["sandbox"]);
 
-    let value = T::try_to_vec(value).unwrap();
-    let state = StateRecord::Data {
-        account_id,
-        data_key: key.into(),
-        value,
-    };
-    let records = vec![state];
-
-    let query_resp = tool::json_client()
-        .call(&RpcSandboxPatchStateRequest { records })
+    context::client()
+        .patch_state_bytes(account_id, key, value)
         .await
-        .map_err(|err| format!("Failed to patch state: {:?}", err));
+}
 
-    // TODO: Similar to `tool::send_tx`. Exponential Backoff required, so have this wait for state to be patched.
-    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+/// Advance the sandbox by `delta_height` blocks. See [`crate::Worker::fast_forward`].
+pub async fn fast_forward(delta_height: u64) -> Result<(), String> {
+    // Fast forwarding only exists within sandbox
+    if !crate::runtime::assert_within(&["sandbox"]) {
+        return Err("fast_forward is only available within sandbox".to_string());
+    }
 
-    query_resp
+    context::client().sandbox_fast_forward(delta_height).await
 }
 
 pub async fn create_account(
@@ -206,23 +190,25 @@ pub async fn create_account(
     signer_id: AccountId,
     new_account_id: AccountId,
     new_account_pk: PublicKey,
-    deposit: Option<Balance>,
-) -> anyhow::Result<CallExecutionResult> {
-    let (access_key, _, block_hash) = tool::access_key(signer_id.clone(), signer.public_key())
+    deposit: Option<NearBalance>,
+) -> anyhow::Result<ExecutionResult> {
+    context::worker()
+        .create_account(signer, signer_id, new_account_id, new_account_pk, deposit)
+        .await
+}
+
+/// Creates an account with exactly zero balance, relying on the creator to cover its storage.
+/// If the protocol doesn't allow this, the error reports how much balance the account
+/// would have needed for its storage instead of a raw execution failure.
+pub async fn create_account_zero_balance(
+    signer: &dyn Signer,
+    signer_id: AccountId,
+    new_account_id: AccountId,
+    new_account_pk: PublicKey,
+) -> anyhow::Result<ExecutionResult> {
+    context::worker()
+        .create_account_zero_balance(signer, signer_id, new_account_id, new_account_pk)
         .await
-        .map_err(|e| anyhow!(e))?;
-
-    let signed_tx = SignedTransaction::create_account(
-        access_key.nonce + 1,
-        signer_id,
-        new_account_id,
-        deposit.unwrap_or(NEAR_BASE),
-        new_account_pk,
-        signer,
-        block_hash,
-    );
-    let transaction_info = tool::send_tx(signed_tx).await.map_err(|e| anyhow!(e))?;
-    Ok(transaction_info.into())
 }
 
 /// Creates a top level account. While in sandbox, we can grab the `ExecutionOutcomeView`, but
@@ -231,9 +217,9 @@ pub async fn create_account(
 pub async fn create_top_level_account(
     new_account_id: AccountId,
     new_account_pk: PublicKey,
-) -> anyhow::Result<Option<CallExecutionResult>> {
-    let rt = crate::runtime::context::current().expect(MISSING_RUNTIME_ERROR);
-    rt.create_top_level_account(new_account_id, new_account_pk)
+) -> anyhow::Result<Option<ExecutionResult>> {
+    context::worker()
+        .create_top_level_account(new_account_id, new_account_pk)
         .await
 }
 
@@ -241,49 +227,66 @@ pub async fn delete_account(
     account_id: AccountId,
     signer: &dyn Signer,
     beneficiary_id: AccountId,
-) -> Result<CallExecutionResult, String> {
-    let (access_key, _, block_hash) =
-        tool::access_key(account_id.clone(), signer.public_key()).await?;
-
-    let signed_tx = SignedTransaction::delete_account(
-        access_key.nonce + 1,
-        account_id.clone(),
-        account_id,
-        beneficiary_id,
-        signer,
-        block_hash,
-    );
-    let transaction_info = tool::send_tx(signed_tx).await?;
-    Ok(transaction_info.into())
+) -> Result<ExecutionResult, String> {
+    context::worker()
+        .delete_account(account_id, signer, beneficiary_id)
+        .await
+}
+
+/// Retry an idempotent operation end-to-end per `policy`, backing off between attempts as
+/// [`RetryPolicy::delay`] says. Only use this for operations that are safe to repeat after
+/// they partially or even fully went through, such as setup steps which check whether they
+/// already happened before doing anything. Errors a retry can't fix, an id that is taken
+/// ([`AccountAlreadyExists`]) or refused by the testnet helper
+/// ([`HelperError::InvalidAccountId`]), are returned right away.
+pub async fn retry_idempotent<F, Fut, T>(policy: &RetryPolicy, mut op: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let attempts = policy.attempts();
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < attempts && !is_permanent(&err) => {
+                trace::warn!(
+                    "idempotent operation failed (attempt {}/{}), retrying: {:?}",
+                    attempt,
+                    attempts,
+                    err
+                );
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
 }
 
-fn dev_generate() -> (AccountId, InMemorySigner) {
-    let account_id = tool::random_account_id();
-    let signer = InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, DEV_ACCOUNT_SEED);
-    signer.write_to_file(&tool::credentials_filepath(account_id.clone()).unwrap());
-    (account_id, signer)
+fn is_permanent(err: &anyhow::Error) -> bool {
+    err.is::<AccountAlreadyExists>()
+        || matches!(
+            err.downcast_ref::<HelperError>(),
+            Some(HelperError::InvalidAccountId { .. })
+        )
 }
 
 pub async fn dev_create() -> anyhow::Result<(AccountId, InMemorySigner)> {
-    let (account_id, signer) = dev_generate();
-    let outcome = create_top_level_account(account_id.clone(), signer.public_key()).await?;
-    dbg!(outcome);
-    Ok((account_id, signer))
+    let account = context::worker().dev_create().await?;
+    Ok(account.into_parts())
 }
 
-pub async fn dev_deploy(
-    contract_file: impl AsRef<Path>,
+pub async fn dev_deploy(code: impl ContractCode) -> anyhow::Result<(AccountId, InMemorySigner)> {
+    let contract = context::worker().dev_deploy(code).await?;
+    Ok(contract.into_account().into_parts())
+}
+
+/// Same as [`dev_deploy`], but also calls `init` in the same transaction as the deployment.
+pub async fn dev_deploy_and_init(
+    code: impl ContractCode,
+    init: InitCall,
 ) -> anyhow::Result<(AccountId, InMemorySigner)> {
-    let (account_id, signer) = dev_generate();
-    let outcome = crate::runtime::context::current()
-        .expect(MISSING_RUNTIME_ERROR)
-        .create_tla_and_deploy(
-            account_id.clone(),
-            signer.public_key(),
-            &signer,
-            contract_file,
-        )
-        .await?;
-    dbg!(outcome);
-    Ok((account_id, signer))
+    let contract = context::worker().dev_deploy_and_init(code, init).await?;
+    Ok(contract.into_account().into_parts())
 }
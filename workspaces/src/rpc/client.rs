@@ -0,0 +1,1490 @@
+// TODO: Remove this when near-jsonrpc-client crate no longer defaults to deprecation for
+//       warnings about unstable API.
+#![allow(deprecated)]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use futures::future::BoxFuture;
+use tokio::sync::{oneshot, Mutex as AsyncMutex, OnceCell, OwnedSemaphorePermit, Semaphore};
+
+use near_crypto::{InMemorySigner, KeyType, PublicKey, SecretKey, Signer};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::{
+        self,
+        sandbox_patch_state::{RpcSandboxPatchStateRequest, RpcSandboxPatchStateResponse},
+    },
+    JsonRpcClient,
+};
+use near_jsonrpc_primitives::types::{
+    chunks::ChunkReference,
+    query::{QueryResponseKind, RpcQueryError, RpcQueryRequest, RpcQueryResponse},
+    transactions::{RpcTransactionError, TransactionInfo},
+};
+use near_primitives::account::{AccessKey, AccessKeyPermission};
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_record::StateRecord;
+use near_primitives::transaction::{
+    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
+    FunctionCallAction, SignedTransaction, TransferAction,
+};
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockId, BlockReference, Finality, FunctionArgs, Gas, Nonce,
+};
+use near_primitives::views::{
+    AccessKeyInfoView, AccessKeyView, AccountView, BlockView, ChunkView, FinalExecutionOutcomeView,
+    FinalExecutionStatus, QueryRequest, ReceiptView,
+};
+
+use super::features::{NodeFeatures, SandboxFeature};
+use super::metrics::RpcMetrics;
+use super::retry::{RetryPolicies, RetryPolicy};
+use super::tool;
+use super::types::{AccessKeyInfo, AccountInfo, NearBalance, StatePrefix, StateRecords};
+use crate::seed;
+use crate::trace;
+use crate::wasm;
+use crate::{
+    AccessKeyRejected, AccountLocked, ExecutionResult, KeyRejection, TransactionStatus, ViewResult,
+    NEAR_BASE,
+};
+
+const ERR_INVALID_VARIANT: &str =
+    "Incorrect variant retrieved while querying: maybe a bug in RPC code?";
+
+pub(crate) type TxHook =
+    Arc<dyn Fn(ExecutionResult) -> BoxFuture<'static, anyhow::Result<()>> + Send + Sync>;
+pub(crate) type TxHooks = Arc<Mutex<Vec<TxHook>>>;
+
+/// Caps on the number of RPC requests in flight at once, shared by all clones of a client.
+/// `None` means unlimited.
+#[derive(Default)]
+pub(crate) struct Limits {
+    queries: Option<Arc<Semaphore>>,
+    transactions: Option<Arc<Semaphore>>,
+}
+
+tokio::task_local! {
+    // Set while hooks are running, so transactions sent from within hooks don't trigger
+    // the hooks again.
+    static IN_TX_HOOK: bool;
+}
+
+/// How long a block hash fetched along with a nonce is reused for signing. Transactions are
+/// only valid for a limited number of blocks after the block they reference.
+const NONCE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Attempts at sending a transaction whose cached nonce turned out to be stale.
+const NONCE_RESYNC_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone, Copy)]
+struct CachedNonce {
+    nonce: Nonce,
+    block_hash: CryptoHash,
+    fetched_at: Instant,
+}
+
+/// A key whose access key and a recent block hash are waiting to be fetched by the next
+/// batch, see [`Client::fetch_access_key`].
+type KeyFetch = (
+    AccountId,
+    PublicKey,
+    oneshot::Sender<Result<(AccessKeyView, CryptoHash), String>>,
+);
+
+/// Connection to the RPC endpoint of a network, along with the per-network settings that
+/// affect how requests are sent to it.
+#[derive(Clone)]
+pub struct Client {
+    rpc_addr: String,
+    explorer_url: Option<&'static str>,
+    read_only: bool,
+    /// Whether keys are generated from the run seed, see [`Client::random_secret_key`].
+    seeded_keys: bool,
+    hooks: TxHooks,
+    limits: Arc<Mutex<Limits>>,
+    retry: Arc<Mutex<RetryPolicies>>,
+    /// Last nonce used by each key, so that transactions don't have to query it first, and
+    /// concurrent ones get distinct nonces. See [`Client::next_nonce`].
+    nonces: Arc<Mutex<HashMap<(AccountId, PublicKey), CachedNonce>>>,
+    key_fetches: Arc<Mutex<Vec<KeyFetch>>>,
+    features: Arc<OnceCell<NodeFeatures>>,
+    metrics: Arc<Mutex<RpcMetrics>>,
+    /// Held by changes to the sandbox outside of transactions for as long as they run, see
+    /// [`Client::run_to_completion`].
+    sandbox_changes: Arc<AsyncMutex<()>>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("rpc_addr", &self.rpc_addr)
+            .field("explorer_url", &self.explorer_url)
+            .field("read_only", &self.read_only)
+            .finish()
+    }
+}
+
+impl Client {
+    pub(crate) fn new(rpc_addr: String) -> Self {
+        Self {
+            rpc_addr,
+            explorer_url: None,
+            read_only: false,
+            seeded_keys: false,
+            hooks: TxHooks::default(),
+            limits: Arc::default(),
+            retry: Arc::default(),
+            nonces: Arc::default(),
+            key_fetches: Arc::default(),
+            features: Arc::default(),
+            metrics: Arc::default(),
+            sandbox_changes: Arc::default(),
+        }
+    }
+
+    pub(crate) fn with_explorer_url(mut self, explorer_url: Option<&'static str>) -> Self {
+        self.explorer_url = explorer_url;
+        self
+    }
+
+    /// Refuse to send any transaction through this client.
+    pub(crate) fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Generate keys from the run seed. Only for networks where that's safe, like the sandbox.
+    pub(crate) fn seeded_keys(mut self, seeded_keys: bool) -> Self {
+        self.seeded_keys = seeded_keys;
+        self
+    }
+
+    /// Key for a new account: derived from the run seed where that's safe, and truly random
+    /// otherwise.
+    pub(crate) fn random_secret_key(&self) -> SecretKey {
+        if self.seeded_keys {
+            seed::secret_key()
+        } else {
+            SecretKey::from_random(KeyType::ED25519)
+        }
+    }
+
+    /// Fail with a `FeatureNotSupported` error if the node lacks `feature`. What the node
+    /// supports is detected on the first call.
+    pub(crate) async fn require(&self, feature: SandboxFeature) -> Result<(), String> {
+        let features = self
+            .features
+            .get_or_try_init(|| NodeFeatures::detect(self))
+            .await?;
+        features.check(feature).map_err(|err| err.to_string())
+    }
+
+    pub(crate) fn with_hooks(mut self, hooks: TxHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub(crate) fn rpc_addr(&self) -> &str {
+        &self.rpc_addr
+    }
+
+    pub(crate) fn explorer_url(&self) -> Option<&'static str> {
+        self.explorer_url
+    }
+
+    pub(crate) fn json_client(&self) -> JsonRpcClient {
+        JsonRpcClient::connect(&self.rpc_addr)
+    }
+
+    /// Send `request` for the RPC `method`, recording its latency in the metrics.
+    pub(crate) async fn rpc<M: methods::RpcMethod>(
+        &self,
+        method: &str,
+        request: M,
+    ) -> Result<M::Response, JsonRpcError<M::Error>> {
+        let start = Instant::now();
+        let result = self.json_client().call(request).await;
+        self.record(method, start, result.is_ok());
+        trace::debug!(
+            method,
+            latency = ?start.elapsed(),
+            success = result.is_ok(),
+            "rpc request to {}",
+            self.rpc_addr
+        );
+        result
+    }
+
+    fn record(&self, method: &str, start: Instant, success: bool) {
+        self.metrics
+            .lock()
+            .unwrap()
+            .record(method, start.elapsed(), !success);
+    }
+
+    pub(crate) fn metrics(&self) -> RpcMetrics {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    pub(crate) fn reset_metrics(&self) {
+        *self.metrics.lock().unwrap() = RpcMetrics::default();
+    }
+
+    pub(crate) fn register_tx_hook(&self, hook: TxHook) {
+        self.hooks.lock().unwrap().push(hook);
+    }
+
+    /// Limit the number of queries and transactions in flight at once. Requests already
+    /// waiting on a previous limit keep waiting on it.
+    pub(crate) fn limit_concurrency(&self, queries: Option<usize>, transactions: Option<usize>) {
+        let mut limits = self.limits.lock().unwrap();
+        limits.queries = queries.map(|limit| Arc::new(Semaphore::new(limit)));
+        limits.transactions = transactions.map(|limit| Arc::new(Semaphore::new(limit)));
+    }
+
+    pub(crate) fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.retry.lock().unwrap().default = policy;
+    }
+
+    pub(crate) fn set_method_retry_policy(&self, method: &str, policy: RetryPolicy) {
+        self.retry
+            .lock()
+            .unwrap()
+            .methods
+            .insert(method.to_string(), policy);
+    }
+
+    /// Policy for the RPC `method`, such as `broadcast_tx_commit`.
+    pub(crate) fn retry_policy(&self, method: &str) -> RetryPolicy {
+        self.retry.lock().unwrap().get(method)
+    }
+
+    /// Wait for a query slot, held until the returned permit is dropped.
+    async fn query_permit(&self) -> Option<OwnedSemaphorePermit> {
+        let semaphore = self.limits.lock().unwrap().queries.clone();
+        acquire(semaphore).await
+    }
+
+    /// Wait for a transaction slot, held until the returned permit is dropped.
+    async fn transaction_permit(&self) -> Option<OwnedSemaphorePermit> {
+        let semaphore = self.limits.lock().unwrap().transactions.clone();
+        acquire(semaphore).await
+    }
+
+    pub(crate) async fn access_key(
+        &self,
+        account_id: AccountId,
+        pk: PublicKey,
+    ) -> Result<(AccessKeyView, BlockHeight, CryptoHash), String> {
+        let permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &methods::query::RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccessKey {
+                        account_id: account_id.clone(),
+                        public_key: pk,
+                    },
+                },
+            )
+            .await;
+        drop(permit);
+
+        let query_resp = match query_resp {
+            Ok(query_resp) => query_resp,
+            Err(err) => {
+                // Every transaction fetches its key first, so this is where signing from a
+                // locked account fails:
+                if let Ok(keys) = self.access_key_list(account_id.clone()).await {
+                    if keys.is_empty() {
+                        return Err(AccountLocked { account_id }.to_string());
+                    }
+                }
+                return Err(format!("Failed to fetch public key info: {:?}", err));
+            }
+        };
+
+        match query_resp.kind {
+            QueryResponseKind::AccessKey(access_key) => {
+                Ok((access_key, query_resp.block_height, query_resp.block_hash))
+            }
+            _ => Err("Could not retrieve access key".to_owned()),
+        }
+    }
+
+    /// Send several queries as a single JSON-RPC batch request, saving a round trip per query.
+    /// Falls back to sending them one by one if the endpoint does not support batching.
+    pub(crate) async fn batch_query(
+        &self,
+        requests: Vec<RpcQueryRequest>,
+    ) -> Result<Vec<RpcQueryResponse>, String> {
+        let _permit = self.query_permit().await;
+        let batch: Vec<_> = requests
+            .iter()
+            .enumerate()
+            .map(|(id, request)| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "query",
+                    "params": request,
+                })
+            })
+            .collect();
+
+        // TODO(maybe): need this in near-jsonrpc-client as well:
+        let start = Instant::now();
+        let resp: Result<serde_json::Value, _> = async {
+            reqwest::Client::new()
+                .post(&self.rpc_addr)
+                .json(&batch)
+                .send()
+                .await?
+                .json()
+                .await
+        }
+        .await;
+        self.record("query", start, resp.is_ok());
+        let resp = resp.map_err(|err| format!("Failed to send batch query: {:?}", err))?;
+
+        let mut responses = match resp {
+            serde_json::Value::Array(responses) if responses.len() == requests.len() => responses,
+            _ => {
+                let mut responses = Vec::with_capacity(requests.len());
+                for request in &requests {
+                    let resp = self
+                        .rpc("query", request)
+                        .await
+                        .map_err(|err| format!("Failed to query: {:?}", err))?;
+                    responses.push(resp);
+                }
+                return Ok(responses);
+            }
+        };
+
+        // Responses to a batch are allowed to come back in any order:
+        responses.sort_by_key(|resp| resp["id"].as_u64());
+        responses
+            .into_iter()
+            .map(|mut resp| {
+                if let Some(err) = resp.get("error") {
+                    return Err(format!("Failed to query: {}", err));
+                }
+                serde_json::from_value(resp["result"].take())
+                    .map_err(|err| format!("Failed to parse query response: {:?}", err))
+            })
+            .collect()
+    }
+
+    /// The protocol and runtime config of the node, as the raw JSON returned by the
+    /// `EXPERIMENTAL_protocol_config` method.
+    pub(crate) async fn protocol_config(&self) -> Result<serde_json::Value, String> {
+        self.experimental(
+            "EXPERIMENTAL_protocol_config",
+            serde_json::json!({ "finality": "final" }),
+        )
+        .await
+        .map_err(|err| format!("Failed to query protocol config: {}", err))
+    }
+
+    /// Receipts spawned by the transaction `hash` of `signer_id`, along with their actions,
+    /// which the outcomes returned by `broadcast_tx_commit` leave out.
+    pub(crate) async fn tx_receipts(
+        &self,
+        hash: CryptoHash,
+        signer_id: AccountId,
+    ) -> Result<Vec<ReceiptView>, String> {
+        let mut result = self
+            .experimental(
+                "EXPERIMENTAL_tx_status",
+                serde_json::json!([hash.to_string(), signer_id]),
+            )
+            .await
+            .map_err(|err| format!("Failed to fetch receipts of {}: {}", hash, err))?;
+        serde_json::from_value(result["receipts"].take())
+            .map_err(|err| format!("Failed to parse receipts of {}: {:?}", hash, err))
+    }
+
+    /// Call an `EXPERIMENTAL_` method, which near-jsonrpc-client doesn't support, returning
+    /// the JSON result.
+    async fn experimental(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let _permit = self.query_permit().await;
+        // TODO(maybe): need this in near-jsonrpc-client as well:
+        let start = Instant::now();
+        let resp: Result<serde_json::Value, _> = async {
+            reqwest::Client::new()
+                .post(&self.rpc_addr)
+                .json(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": "dontcare",
+                    "method": method,
+                    "params": params,
+                }))
+                .send()
+                .await?
+                .json()
+                .await
+        }
+        .await;
+        self.record(method, start, resp.is_ok());
+        let mut resp = resp.map_err(|err| format!("{:?}", err))?;
+
+        if let Some(err) = resp.get("error") {
+            return Err(err.to_string());
+        }
+        Ok(resp["result"].take())
+    }
+
+    pub(crate) async fn view_account(&self, account_id: AccountId) -> Result<AccountView, String> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &methods::query::RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccount { account_id },
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to fetch account: {:?}", err))?;
+
+        match query_resp.kind {
+            QueryResponseKind::ViewAccount(account) => Ok(account),
+            _ => Err("Could not retrieve account".to_owned()),
+        }
+    }
+
+    /// Same as [`Client::view_account`], but `None` if the account doesn't exist rather than
+    /// an error, which is kept for any other failure.
+    pub(crate) async fn view_account_if_exists(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Option<AccountView>, String> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &methods::query::RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccount { account_id },
+                },
+            )
+            .await;
+
+        match query_resp {
+            Ok(RpcQueryResponse {
+                kind: QueryResponseKind::ViewAccount(account),
+                ..
+            }) => Ok(Some(account)),
+            Ok(_) => Err("Could not retrieve account".to_owned()),
+            Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                RpcQueryError::UnknownAccount { .. },
+            ))) => Ok(None),
+            Err(err) => Err(format!("Failed to fetch account: {:?}", err)),
+        }
+    }
+
+    pub(crate) async fn access_key_list(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Vec<AccessKeyInfoView>, String> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &methods::query::RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccessKeyList { account_id },
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to fetch access key list: {:?}", err))?;
+
+        match query_resp.kind {
+            QueryResponseKind::AccessKeyList(list) => Ok(list.keys),
+            _ => Err("Could not retrieve access key list".to_owned()),
+        }
+    }
+
+    pub(crate) async fn view_access_key_list(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Vec<AccessKeyInfo>, String> {
+        let keys = self.access_key_list(account_id).await?;
+        Ok(keys
+            .into_iter()
+            .map(|key| AccessKeyInfo {
+                public_key: key.public_key,
+                access_key: key.access_key.into(),
+            })
+            .collect())
+    }
+
+    pub(crate) fn describe_tx(&self, tx_hash: &CryptoHash) -> String {
+        tool::describe_tx(tx_hash, self.explorer_url)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                tx = %tx.get_hash(),
+                signer = %tx.transaction.signer_id,
+                receiver = %tx.transaction.receiver_id,
+            )
+        )
+    )]
+    pub(crate) async fn send_tx(
+        &self,
+        tx: SignedTransaction,
+    ) -> Result<FinalExecutionOutcomeView, String> {
+        if self.read_only {
+            return Err(format!(
+                "cannot send transactions through the read-only RPC at {}",
+                self.rpc_addr
+            ));
+        }
+
+        let tx_hash = tx.get_hash();
+        // Only the broadcast counts towards the transaction limit, so that hooks sending
+        // transactions of their own can't deadlock waiting on their caller's slot:
+        let permit = self.transaction_permit().await;
+        let policy = self.retry_policy("broadcast_tx_commit");
+        let mut attempt = 1;
+        let transaction_info_result = loop {
+            let transaction_info_result = self
+                .rpc(
+                    "broadcast_tx_commit",
+                    &methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
+                        signed_transaction: tx.clone(),
+                    },
+                )
+                .await;
+
+            match transaction_info_result {
+                Err(ref err) if is_transient(err) && attempt < policy.attempts() => {
+                    trace::warn!(
+                        "transaction {} failed (attempt {}/{}), retrying: {:?}",
+                        self.describe_tx(&tx_hash),
+                        attempt,
+                        policy.attempts(),
+                        err
+                    );
+                    tokio::time::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                _ => break transaction_info_result,
+            }
+        };
+        drop(permit);
+
+        let transaction_info = transaction_info_result.map_err(|e| {
+            let err = format!("Error transaction {}: {:?}", self.describe_tx(&tx_hash), e);
+            match KeyRejection::from_error(&err) {
+                Some(reason) => AccessKeyRejected {
+                    account_id: tx.transaction.signer_id.clone(),
+                    public_key: tx.transaction.public_key.clone(),
+                    reason,
+                }
+                .to_string(),
+                None => err,
+            }
+        })?;
+
+        // Queries are made against final blocks, so the transaction is only visible to them,
+        // and its nonce to the next transaction of its signer, once all its outcomes are:
+        let result = self.into_result(transaction_info.clone()).await?;
+        let last_height = result
+            .outcomes()
+            .map(|outcome| outcome.block_height)
+            .max()
+            .unwrap_or_default();
+        trace::debug!(
+            gas_burnt = result.total_gas_burnt(),
+            success = result.is_success(),
+            "transaction executed in block #{}",
+            last_height
+        );
+        self.wait_until_final(last_height).await?;
+
+        self.run_tx_hooks(result).await.map_err(|e| {
+            format!(
+                "Hook failed after transaction {}: {:?}",
+                self.describe_tx(&tx_hash),
+                e
+            )
+        })?;
+
+        Ok(transaction_info)
+    }
+
+    pub(crate) async fn run_tx_hooks(&self, result: ExecutionResult) -> anyhow::Result<()> {
+        if IN_TX_HOOK.try_with(|in_hook| *in_hook).unwrap_or(false) {
+            return Ok(());
+        }
+
+        let hooks = self.hooks.lock().unwrap().clone();
+        IN_TX_HOOK
+            .scope(true, async move {
+                for hook in hooks {
+                    hook(result.clone()).await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            })
+            .await
+    }
+
+    /// Converts the outcome into a result which knows how to link to the transaction, looking
+    /// up the height of every block its receipts were included in.
+    pub(crate) async fn into_result(
+        &self,
+        outcome: FinalExecutionOutcomeView,
+    ) -> Result<ExecutionResult, String> {
+        let mut block_heights = HashMap::new();
+        for block_hash in ExecutionResult::block_hashes(&outcome) {
+            if !block_heights.contains_key(&block_hash) {
+                block_heights.insert(block_hash, self.block_height(block_hash).await?);
+            }
+        }
+        Ok(ExecutionResult::new(
+            outcome,
+            &block_heights,
+            self.explorer_url,
+        ))
+    }
+
+    pub(crate) async fn view_block(
+        &self,
+        block_reference: BlockReference,
+    ) -> Result<BlockView, String> {
+        let _permit = self.query_permit().await;
+        self.rpc(
+            "block",
+            &methods::block::RpcBlockRequest { block_reference },
+        )
+        .await
+        .map_err(|err| format!("Failed to query block: {:?}", err))
+    }
+
+    pub(crate) async fn view_chunk(
+        &self,
+        chunk_reference: ChunkReference,
+    ) -> Result<ChunkView, String> {
+        let _permit = self.query_permit().await;
+        self.rpc(
+            "chunk",
+            &methods::chunk::RpcChunkRequest { chunk_reference },
+        )
+        .await
+        .map_err(|err| format!("Failed to query chunk: {:?}", err))
+    }
+
+    /// Height of the latest block with the given finality.
+    pub(crate) async fn latest_block_height(
+        &self,
+        finality: Finality,
+    ) -> Result<BlockHeight, String> {
+        let _permit = self.query_permit().await;
+        let block = self
+            .rpc(
+                "block",
+                &methods::block::RpcBlockRequest {
+                    block_reference: BlockReference::Finality(finality),
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to query latest block: {:?}", err))?;
+        Ok(block.header.height)
+    }
+
+    /// Poll the final block, backing off as configured for `block`, until it reaches `height`.
+    pub(crate) async fn wait_until_final(&self, height: BlockHeight) -> Result<(), String> {
+        let policy = self.retry_policy("block");
+        let mut attempt = 1;
+        loop {
+            let final_height = self.latest_block_height(Finality::Final).await?;
+            if final_height >= height {
+                return Ok(());
+            }
+            if attempt >= policy.attempts() {
+                return Err(format!(
+                    "block {} did not become final after {} attempts, final block is {}",
+                    height, attempt, final_height
+                ));
+            }
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    pub(crate) async fn block_height(&self, block_hash: CryptoHash) -> Result<BlockHeight, String> {
+        let _permit = self.query_permit().await;
+        let block = self
+            .rpc(
+                "block",
+                &methods::block::RpcBlockRequest {
+                    block_reference: BlockReference::BlockId(BlockId::Hash(block_hash)),
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to query block {}: {:?}", block_hash, err))?;
+        Ok(block.header.height)
+    }
+
+    pub(crate) async fn display_account_info(
+        &self,
+        account_id: AccountId,
+    ) -> Result<AccountInfo, String> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccount {
+                        account_id: account_id.clone(),
+                    },
+                },
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+        into_account_info(account_id, query_resp)
+    }
+
+    pub(crate) async fn display_account_infos(
+        &self,
+        account_ids: Vec<AccountId>,
+    ) -> Result<Vec<AccountInfo>, String> {
+        let requests = account_ids
+            .iter()
+            .map(|account_id| RpcQueryRequest {
+                block_reference: Finality::Final.into(),
+                request: QueryRequest::ViewAccount {
+                    account_id: account_id.clone(),
+                },
+            })
+            .collect();
+
+        let query_resps = self.batch_query(requests).await?;
+        account_ids
+            .into_iter()
+            .zip(query_resps)
+            .map(|(account_id, query_resp)| into_account_info(account_id, query_resp))
+            .collect()
+    }
+
+    pub(crate) async fn transfer_near(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        receiver_id: AccountId,
+        amount: NearBalance,
+    ) -> Result<ExecutionResult, String> {
+        amount.check_sane().map_err(|e| e.to_string())?;
+        let action = Action::Transfer(TransferAction {
+            deposit: amount.to_yoctonear(),
+        });
+        let transaction_info = self
+            .sign_and_send(signer, signer_id, receiver_id, vec![action])
+            .await?;
+        self.into_result(transaction_info).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(contract = %contract_id, method = %method_name, gas = gas)
+        )
+    )]
+    pub(crate) async fn call(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        contract_id: AccountId,
+        method_name: String,
+        args: Vec<u8>,
+        deposit: Option<NearBalance>,
+        gas: Gas,
+    ) -> Result<ExecutionResult, String> {
+        let deposit = deposit.unwrap_or_default();
+        deposit.check_sane().map_err(|e| e.to_string())?;
+        let action = Action::FunctionCall(FunctionCallAction {
+            method_name: method_name.clone(),
+            args,
+            gas,
+            deposit: deposit.to_yoctonear(),
+        });
+        let transaction_info = self
+            .sign_and_send(signer, signer_id, contract_id.clone(), vec![action])
+            .await?;
+        let result = self.into_result(transaction_info).await?;
+        if result.is_method_not_found() {
+            let hint = self.method_not_found(&contract_id, &method_name).await;
+            return Ok(result.with_hint(hint));
+        }
+        Ok(result)
+    }
+
+    /// Explains that `contract_id` has no method `method_name`, listing the methods it does
+    /// have, read from its code, along with the closest one to `method_name` if it looks like
+    /// a typo of it.
+    pub(crate) async fn method_not_found(
+        &self,
+        contract_id: &AccountId,
+        method_name: &str,
+    ) -> String {
+        let not_found = format!("{} has no method `{}`", contract_id, method_name);
+        let methods = match self.view_code(contract_id.clone()).await {
+            Ok(code) => match wasm::exported_functions(&code) {
+                Ok(methods) => methods,
+                Err(_) => return not_found,
+            },
+            Err(_) => return not_found,
+        };
+
+        match wasm::closest_match(method_name, &methods) {
+            Some(closest) => format!(
+                "{}, did you mean `{}`? Its methods are: {}",
+                not_found,
+                closest,
+                methods.join(", ")
+            ),
+            None => format!("{}. Its methods are: {}", not_found, methods.join(", ")),
+        }
+    }
+
+    /// Same as [`Client::call`], but returns as soon as the transaction is submitted.
+    pub(crate) async fn call_async(
+        &self,
+        signer: &InMemorySigner,
+        signer_id: AccountId,
+        contract_id: AccountId,
+        method_name: String,
+        args: Vec<u8>,
+        deposit: Option<NearBalance>,
+        gas: Gas,
+    ) -> Result<TransactionStatus, String> {
+        let deposit = deposit.unwrap_or_default();
+        deposit.check_sane().map_err(|e| e.to_string())?;
+        let (nonce, block_hash) = self
+            .next_nonce(signer_id.clone(), signer.public_key())
+            .await?;
+        let tx = SignedTransaction::call(
+            nonce,
+            signer_id,
+            contract_id,
+            signer,
+            deposit.to_yoctonear(),
+            method_name,
+            args,
+            gas,
+            block_hash,
+        );
+        self.send_tx_async(tx, signer).await
+    }
+
+    /// Nonce for the next transaction of `signer_id`'s key `pk`, along with a recent block
+    /// hash. Nonces are taken from the cache, incremented atomically, and only queried when
+    /// the key isn't cached yet or its block hash got old. Transactions sent by another
+    /// process or client make the cached nonce stale, which [`Client::sign_and_send`]
+    /// recovers from.
+    pub(crate) async fn next_nonce(
+        &self,
+        signer_id: AccountId,
+        pk: PublicKey,
+    ) -> Result<(Nonce, CryptoHash), String> {
+        let key = (signer_id, pk);
+        if let Some(cached) = self.nonces.lock().unwrap().get_mut(&key) {
+            if cached.fetched_at.elapsed() < NONCE_CACHE_TTL {
+                cached.nonce += 1;
+                return Ok((cached.nonce, cached.block_hash));
+            }
+        }
+
+        let (access_key, block_hash) = self.fetch_access_key(key.0.clone(), key.1.clone()).await?;
+        let mut nonces = self.nonces.lock().unwrap();
+        let cached = nonces.entry(key).or_insert(CachedNonce {
+            nonce: 0,
+            block_hash,
+            fetched_at: Instant::now(),
+        });
+        // Concurrent transactions might have taken nonces in the meantime:
+        cached.nonce = cached.nonce.max(access_key.nonce) + 1;
+        cached.block_hash = block_hash;
+        cached.fetched_at = Instant::now();
+        Ok((cached.nonce, cached.block_hash))
+    }
+
+    /// The access key `pk` of `account_id` along with a recent block hash, fetched in the same
+    /// JSON-RPC batch as the keys of the other transactions being signed concurrently, such
+    /// as those of several accounts sent with `try_join_all`.
+    async fn fetch_access_key(
+        &self,
+        account_id: AccountId,
+        pk: PublicKey,
+    ) -> Result<(AccessKeyView, CryptoHash), String> {
+        let (sender, receiver) = oneshot::channel();
+        self.key_fetches
+            .lock()
+            .unwrap()
+            .push((account_id, pk, sender));
+
+        // Let the other futures polled along with this one queue their keys first:
+        tokio::task::yield_now().await;
+        let batch = std::mem::take(&mut *self.key_fetches.lock().unwrap());
+        if !batch.is_empty() {
+            // Spawned, so that dropping this future doesn't fail the others of the batch:
+            let client = self.clone();
+            tokio::spawn(async move { client.fetch_access_keys(batch).await });
+        }
+
+        receiver
+            .await
+            .map_err(|_| "access key fetch was cancelled".to_string())?
+    }
+
+    async fn fetch_access_keys(&self, batch: Vec<KeyFetch>) {
+        if batch.len() > 1 {
+            let requests = batch
+                .iter()
+                .map(|(account_id, pk, _)| RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewAccessKey {
+                        account_id: account_id.clone(),
+                        public_key: pk.clone(),
+                    },
+                })
+                .collect();
+            // A single missing key fails the whole batch, those are fetched one by one below
+            // to tell which one it was:
+            if let Ok(query_resps) = self.batch_query(requests).await {
+                for ((_, _, sender), query_resp) in batch.into_iter().zip(query_resps) {
+                    let result = match query_resp.kind {
+                        QueryResponseKind::AccessKey(access_key) => {
+                            Ok((access_key, query_resp.block_hash))
+                        }
+                        _ => Err("Could not retrieve access key".to_owned()),
+                    };
+                    let _ = sender.send(result);
+                }
+                return;
+            }
+        }
+
+        futures::future::join_all(
+            batch
+                .into_iter()
+                .map(|(account_id, pk, sender)| async move {
+                    let result = self
+                        .access_key(account_id, pk)
+                        .await
+                        .map(|(access_key, _, block_hash)| (access_key, block_hash));
+                    let _ = sender.send(result);
+                }),
+        )
+        .await;
+    }
+
+    /// Query the nonce of `signer_id`'s key `pk` again on its next transaction.
+    fn forget_nonce(&self, signer_id: AccountId, pk: PublicKey) {
+        self.nonces.lock().unwrap().remove(&(signer_id, pk));
+    }
+
+    /// Forget all the cached nonces, once the chain no longer has the transactions which
+    /// used them, such as after the sandbox state was restored.
+    pub(crate) fn forget_nonces(&self) {
+        self.nonces.lock().unwrap().clear();
+    }
+
+    /// Sign a transaction of `actions` with the next nonce of `signer`, and send it. If the
+    /// node rejects the cached nonce or block hash as stale, they are queried again and the
+    /// transaction is signed again. Concurrent transactions of the same key can still
+    /// overtake each other, hence a few attempts.
+    pub(crate) async fn sign_and_send(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        receiver_id: AccountId,
+        actions: Vec<Action>,
+    ) -> Result<FinalExecutionOutcomeView, String> {
+        let mut attempt = 1;
+        loop {
+            let (nonce, block_hash) = self
+                .next_nonce(signer_id.clone(), signer.public_key())
+                .await?;
+            let tx = SignedTransaction::from_actions(
+                nonce,
+                signer_id.clone(),
+                receiver_id.clone(),
+                signer,
+                actions.clone(),
+                block_hash,
+            );
+            match self.send_tx(tx).await {
+                Err(err) if attempt < NONCE_RESYNC_ATTEMPTS && is_stale_nonce(&err) => {
+                    self.forget_nonce(signer_id.clone(), signer.public_key());
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Broadcast `tx` without waiting for it. The status returned can replace it if it gets
+    /// stuck, re-signing it with `signer`.
+    pub(crate) async fn send_tx_async(
+        &self,
+        tx: SignedTransaction,
+        signer: &InMemorySigner,
+    ) -> Result<TransactionStatus, String> {
+        if self.read_only {
+            return Err(format!(
+                "cannot send transactions through the read-only RPC at {}",
+                self.rpc_addr
+            ));
+        }
+
+        let transaction = tx.transaction.clone();
+        let _permit = self.transaction_permit().await;
+        let hash = self
+            .rpc(
+                "broadcast_tx_async",
+                &methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
+                    signed_transaction: tx,
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to send transaction: {:?}", err))?;
+        Ok(TransactionStatus::new(
+            self.clone(),
+            signer.clone(),
+            transaction,
+            hash,
+        ))
+    }
+
+    /// Outcome of the transaction `hash`, or `None` while it is still executing.
+    pub(crate) async fn tx_status(
+        &self,
+        signer_id: AccountId,
+        hash: CryptoHash,
+    ) -> Result<Option<FinalExecutionOutcomeView>, String> {
+        let _permit = self.query_permit().await;
+        let resp = self
+            .rpc(
+                "tx",
+                &methods::tx::RpcTransactionStatusRequest {
+                    transaction_info: TransactionInfo::TransactionId {
+                        hash,
+                        account_id: signer_id,
+                    },
+                },
+            )
+            .await;
+
+        match resp {
+            Ok(outcome) => match outcome.status {
+                FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => Ok(None),
+                _ => Ok(Some(outcome)),
+            },
+            Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                RpcTransactionError::UnknownTransaction { .. } | RpcTransactionError::TimeoutError,
+            ))) => Ok(None),
+            Err(err) => Err(format!(
+                "Failed to query transaction {}: {:?}",
+                self.describe_tx(&hash),
+                err
+            )),
+        }
+    }
+
+    /// Query a view method, leaving decoding the returned bytes to the caller.
+    pub(crate) async fn view_raw(
+        &self,
+        contract_id: AccountId,
+        method_name: String,
+        args: FunctionArgs,
+    ) -> Result<ViewResult, String> {
+        let permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::CallFunction {
+                        account_id: contract_id.clone(),
+                        method_name: method_name.clone(),
+                        args,
+                    },
+                },
+            )
+            .await;
+        drop(permit);
+
+        let query_resp = match query_resp {
+            Ok(query_resp) => query_resp,
+            Err(err) => {
+                let err = format!("Failed to fetch query for view method: {:?}", err);
+                if err.contains("MethodNotFound") {
+                    return Err(self.method_not_found(&contract_id, &method_name).await);
+                }
+                return Err(err);
+            }
+        };
+
+        match query_resp.kind {
+            QueryResponseKind::CallResult(result) => Ok(ViewResult {
+                result: result.result,
+                logs: result.logs,
+            }),
+            _ => Err("Error call result".to_string()),
+        }
+    }
+
+    pub(crate) async fn view_code(&self, account_id: AccountId) -> Result<Vec<u8>, String> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &RpcQueryRequest {
+                    block_reference: Finality::Final.into(),
+                    request: QueryRequest::ViewCode { account_id },
+                },
+            )
+            .await
+            .map_err(|err| format!("Failed to fetch code: {:?}", err))?;
+
+        match query_resp.kind {
+            QueryResponseKind::ViewCode(contract) => Ok(contract.code),
+            _ => Err(ERR_INVALID_VARIANT.to_string()),
+        }
+    }
+
+    /// State of `contract_id` under `prefix`. RPC nodes refuse to return state above a size
+    /// limit, in which case the prefix is split into the 256 prefixes one byte longer, each
+    /// fetched separately. A key equal to a split prefix itself is not covered by any of the
+    /// longer ones, and queries can't ask for it alone, so each split is warned about.
+    pub(crate) async fn view_state(
+        &self,
+        contract_id: AccountId,
+        prefix: Option<StatePrefix>,
+    ) -> anyhow::Result<StateRecords> {
+        let mut entries = Vec::new();
+        let mut pending = vec![prefix.unwrap_or_default().into_bytes()];
+        while let Some(prefix) = pending.pop() {
+            match self
+                .view_state_page(contract_id.clone(), prefix.clone())
+                .await
+            {
+                Ok(page) => entries.extend(page),
+                Err(err) if err.to_string().contains("TooLargeContractState") => {
+                    trace::warn!(
+                        "state of {} under prefix {:?} is too large for a single query, \
+                         fetching it in narrower pages, which leave out a key equal to it",
+                        contract_id,
+                        prefix,
+                    );
+                    pending.extend((0..=u8::MAX).map(|byte| {
+                        let mut narrower = prefix.clone();
+                        narrower.push(byte);
+                        narrower
+                    }));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    async fn view_state_page(
+        &self,
+        contract_id: AccountId,
+        prefix: Vec<u8>,
+    ) -> anyhow::Result<StateRecords> {
+        let _permit = self.query_permit().await;
+        let query_resp = self
+            .rpc(
+                "query",
+                &methods::query::RpcQueryRequest {
+                    block_reference: BlockReference::Finality(Finality::Final),
+                    request: QueryRequest::ViewState {
+                        account_id: contract_id,
+                        prefix: prefix.into(),
+                    },
+                },
+            )
+            .await
+            .map_err(|err| anyhow!("Failed to query state: {:?}", err))?;
+
+        match query_resp.kind {
+            QueryResponseKind::ViewState(state) => tool::into_state_records(&state.values),
+            _ => Err(anyhow!(ERR_INVALID_VARIANT)),
+        }
+    }
+
+    pub(crate) async fn patch_state_bytes(
+        &self,
+        account_id: AccountId,
+        key: impl AsRef<[u8]>,
+        value: Vec<u8>,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        let state = StateRecord::Data {
+            account_id,
+            data_key: key.as_ref().to_vec().into(),
+            value,
+        };
+        self.patch_state_records(vec![state]).await
+    }
+
+    /// Run `change`, which changes the state of a sandbox outside of any transaction, such as
+    /// a patch or a fast-forward, to completion even if the future awaiting this is dropped,
+    /// e.g. by a test timeout. Changes run one at a time, and each one waits for the previous
+    /// one to be done, so that a cancelled change is never seen half-applied by the next one.
+    /// Dropping the future awaiting this only stops waiting for `change`.
+    pub(crate) async fn run_to_completion<T>(
+        &self,
+        change: impl Future<Output = T> + Send + 'static,
+    ) -> T
+    where
+        T: Send + 'static,
+    {
+        let guard = self.sandbox_changes.clone().lock_owned().await;
+        let handle = tokio::spawn(async move {
+            let _guard = guard;
+            change.await
+        });
+        match handle.await {
+            Ok(output) => output,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => panic!("sandbox change did not complete: {}", err),
+        }
+    }
+
+    pub(crate) async fn patch_state_records(
+        &self,
+        records: Vec<StateRecord>,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        self.require(SandboxFeature::PatchState).await?;
+        let client = self.clone();
+        self.run_to_completion(async move {
+            // Patches are applied when the next block is produced:
+            let height = client.latest_block_height(Finality::None).await?;
+            let permit = client.transaction_permit().await;
+            let query_resp = client
+                .rpc(
+                    "sandbox_patch_state",
+                    &RpcSandboxPatchStateRequest { records },
+                )
+                .await
+                .map_err(|err| format!("Failed to patch state: {:?}", err))?;
+            drop(permit);
+
+            client.wait_until_final(height + 1).await?;
+            Ok(query_resp)
+        })
+        .await
+    }
+
+    /// Produce `delta_height` blocks on a sandbox node without waiting for them in real time.
+    pub(crate) async fn sandbox_fast_forward(&self, delta_height: u64) -> Result<(), String> {
+        self.require(SandboxFeature::FastForward).await?;
+        let client = self.clone();
+        self.run_to_completion(async move { client.fast_forward_blocks(delta_height).await })
+            .await
+    }
+
+    async fn fast_forward_blocks(&self, delta_height: u64) -> Result<(), String> {
+        let _permit = self.transaction_permit().await;
+        // TODO(maybe): need this in near-jsonrpc-client as well:
+        let start = Instant::now();
+        let resp: Result<serde_json::Value, String> = async {
+            reqwest::Client::new()
+                .post(&self.rpc_addr)
+                .json(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": "dontcare",
+                    "method": "sandbox_fast_forward",
+                    "params": { "delta_height": delta_height },
+                }))
+                .send()
+                .await
+                .map_err(|err| format!("Failed to fast forward: {:?}", err))?
+                .json()
+                .await
+                .map_err(|err| format!("Failed to read fast forward response: {:?}", err))
+        }
+        .await;
+        let failed = match &resp {
+            Ok(resp) => resp.get("error").is_some(),
+            Err(_) => true,
+        };
+        self.record("sandbox_fast_forward", start, !failed);
+
+        let resp = resp?;
+        if let Some(err) = resp.get("error") {
+            return Err(format!("Failed to fast forward: {}", err));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn create_account(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        deposit: Option<NearBalance>,
+    ) -> anyhow::Result<ExecutionResult> {
+        let deposit = deposit.unwrap_or_else(|| NearBalance::from_yoctonear(NEAR_BASE));
+        deposit.check_sane()?;
+        let actions = vec![
+            Action::CreateAccount(CreateAccountAction {}),
+            Action::Transfer(TransferAction {
+                deposit: deposit.to_yoctonear(),
+            }),
+            Action::AddKey(AddKeyAction {
+                public_key: new_account_pk,
+                access_key: AccessKey::full_access(),
+            }),
+        ];
+        let transaction_info = self
+            .sign_and_send(signer, signer_id, new_account_id, actions)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        self.into_result(transaction_info)
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+
+    pub(crate) async fn delete_account(
+        &self,
+        account_id: AccountId,
+        signer: &dyn Signer,
+        beneficiary_id: AccountId,
+    ) -> Result<ExecutionResult, String> {
+        let action = Action::DeleteAccount(DeleteAccountAction { beneficiary_id });
+        let transaction_info = self
+            .sign_and_send(signer, account_id.clone(), account_id, vec![action])
+            .await?;
+        self.into_result(transaction_info).await
+    }
+
+    pub(crate) async fn add_key(
+        &self,
+        account_id: AccountId,
+        signer: &dyn Signer,
+        public_key: PublicKey,
+        permission: AccessKeyPermission,
+    ) -> Result<ExecutionResult, String> {
+        self.key_actions(
+            account_id,
+            signer,
+            vec![Action::AddKey(AddKeyAction {
+                public_key,
+                access_key: AccessKey {
+                    nonce: 0,
+                    permission,
+                },
+            })],
+        )
+        .await
+    }
+
+    /// Delete all of `public_keys` at once, in a single transaction.
+    pub(crate) async fn delete_keys(
+        &self,
+        account_id: AccountId,
+        signer: &dyn Signer,
+        public_keys: Vec<PublicKey>,
+    ) -> Result<ExecutionResult, String> {
+        let actions = public_keys
+            .into_iter()
+            .map(|public_key| Action::DeleteKey(DeleteKeyAction { public_key }))
+            .collect();
+        self.key_actions(account_id, signer, actions).await
+    }
+
+    /// Apply `actions` to the keys of `account_id`, signed by the account itself.
+    async fn key_actions(
+        &self,
+        account_id: AccountId,
+        signer: &dyn Signer,
+        actions: Vec<Action>,
+    ) -> Result<ExecutionResult, String> {
+        let transaction_info = self
+            .sign_and_send(signer, account_id.clone(), account_id, actions)
+            .await?;
+        self.into_result(transaction_info).await
+    }
+}
+
+fn into_account_info(
+    account_id: AccountId,
+    query_resp: RpcQueryResponse,
+) -> Result<AccountInfo, String> {
+    let account_view = match query_resp.kind {
+        QueryResponseKind::ViewAccount(result) => result,
+        _ => return Err("Error call result".to_owned()),
+    };
+
+    Ok(AccountInfo {
+        account_id,
+        block_height: query_resp.block_height,
+        block_hash: query_resp.block_hash,
+        balance: NearBalance::from_yoctonear(account_view.amount),
+        stake: NearBalance::from_yoctonear(account_view.locked),
+        used_storage_bytes: account_view.storage_usage,
+        code_hash: account_view.code_hash,
+    })
+}
+
+async fn acquire(semaphore: Option<Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    match semaphore {
+        // The semaphores are never closed, so acquiring can't fail:
+        Some(semaphore) => semaphore.acquire_owned().await.ok(),
+        None => None,
+    }
+}
+
+/// Errors of a transaction signed with a cached nonce or block hash the chain moved past,
+/// e.g. after another client used the same key, or the key got deleted. `err` is the error
+/// of [`Client::send_tx`], which only keeps the error's debug representation.
+fn is_stale_nonce(err: &str) -> bool {
+    [
+        "InvalidNonce",
+        "NonceTooLarge",
+        "Expired",
+        "InvalidChain",
+        "AccessKeyNotFound",
+    ]
+    .iter()
+    .any(|kind| err.contains(kind))
+}
+
+/// Errors after which resending the same transaction may succeed: the node timing out while
+/// waiting for it, or the request not making it to the node.
+fn is_transient(err: &JsonRpcError<RpcTransactionError>) -> bool {
+    matches!(
+        err,
+        JsonRpcError::TransportError(_)
+            | JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                RpcTransactionError::TimeoutError
+            ))
+    )
+}
@@ -0,0 +1,119 @@
+//! Detection of the sandbox-only RPC methods the connected node supports, so that tests
+//! running against an outdated near-sandbox fail with an error naming the version they need,
+//! instead of an opaque "method not found" halfway through.
+
+use std::fmt;
+
+use near_jsonrpc_client::methods;
+
+use super::client::Client;
+
+/// JSON-RPC error code of calls to methods the node doesn't have.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// RPC methods only some sandbox nodes have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SandboxFeature {
+    PatchState,
+    FastForward,
+}
+
+impl SandboxFeature {
+    const ALL: [SandboxFeature; 2] = [SandboxFeature::PatchState, SandboxFeature::FastForward];
+
+    fn method(self) -> &'static str {
+        match self {
+            SandboxFeature::PatchState => "sandbox_patch_state",
+            SandboxFeature::FastForward => "sandbox_fast_forward",
+        }
+    }
+
+    /// First nearcore release whose sandbox has the method.
+    fn min_version(self) -> &'static str {
+        match self {
+            SandboxFeature::PatchState => "1.20.0",
+            SandboxFeature::FastForward => "1.26.0",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FeatureNotSupported {
+    feature: SandboxFeature,
+    node_version: String,
+}
+
+impl fmt::Display for FeatureNotSupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FeatureNotSupported: {} is not available on the connected node (version {}), \
+             it requires a near-sandbox built from nearcore {} or later. A newer one can be \
+             picked through NEAR_SANDBOX_VERSION or NEAR_SANDBOX_BIN_PATH",
+            self.feature.method(),
+            self.node_version,
+            self.feature.min_version()
+        )
+    }
+}
+
+impl std::error::Error for FeatureNotSupported {}
+
+/// The version of a node and the sandbox features it lacks, detected once per client.
+#[derive(Debug)]
+pub(crate) struct NodeFeatures {
+    version: String,
+    missing: Vec<SandboxFeature>,
+}
+
+impl NodeFeatures {
+    pub(crate) async fn detect(client: &Client) -> Result<Self, String> {
+        let status = client
+            .rpc("status", methods::status::RpcStatusRequest)
+            .await
+            .map_err(|err| format!("Failed to query node status: {:?}", err))?;
+
+        let mut missing = Vec::new();
+        for &feature in &SandboxFeature::ALL {
+            if !has_method(client.rpc_addr(), feature.method()).await? {
+                missing.push(feature);
+            }
+        }
+
+        Ok(Self {
+            version: status.version.version,
+            missing,
+        })
+    }
+
+    pub(crate) fn check(&self, feature: SandboxFeature) -> Result<(), FeatureNotSupported> {
+        if self.missing.contains(&feature) {
+            return Err(FeatureNotSupported {
+                feature,
+                node_version: self.version.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether the node has `method`, found out by calling it without its params: nodes having
+/// it reject the call as invalid without doing anything.
+async fn has_method(rpc_addr: &str, method: &str) -> Result<bool, String> {
+    let resp: serde_json::Value = reqwest::Client::new()
+        .post(rpc_addr)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "dontcare",
+            "method": method,
+            "params": {},
+        }))
+        .send()
+        .await
+        .map_err(|err| format!("Failed to probe {}: {:?}", method, err))?
+        .json()
+        .await
+        .map_err(|err| format!("Failed to read {} probe response: {:?}", method, err))?;
+
+    Ok(resp["error"]["code"].as_i64() != Some(METHOD_NOT_FOUND))
+}
@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use std::future::Future;
+
+use near_primitives::types::{AccountId, Balance};
+use near_primitives::views::AccessKeyPermissionView;
+
+use super::client::Client;
+use super::types::NearBalance;
+
+/// Snapshot of an account's access keys and balance. Checking the guard later on fails if
+/// either has changed, which catches accidental key additions or balance drains caused by
+/// buggy batch transactions.
+///
+/// ```ignore
+/// let guard = AccountGuard::snapshot(account_id).await?;
+/// // ... do work that should not touch `account_id` ...
+/// guard.check().await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountGuard {
+    client: Client,
+    account_id: AccountId,
+    balance: Balance,
+    keys: Vec<(String, AccessKeyPermissionView)>,
+    balance_tolerance: Balance,
+}
+
+impl AccountGuard {
+    pub async fn snapshot(account_id: AccountId) -> anyhow::Result<Self> {
+        Self::snapshot_with(crate::runtime::context::client(), account_id).await
+    }
+
+    pub(crate) async fn snapshot_with(
+        client: Client,
+        account_id: AccountId,
+    ) -> anyhow::Result<Self> {
+        let (balance, keys) = Self::fetch(&client, account_id.clone()).await?;
+        Ok(Self {
+            client,
+            account_id,
+            balance,
+            keys,
+            balance_tolerance: 0,
+        })
+    }
+
+    /// Allow the balance to decrease by up to `amount` before the check fails. Useful when
+    /// the guarded account signs transactions itself and pays for gas.
+    pub fn balance_tolerance(mut self, amount: NearBalance) -> Self {
+        self.balance_tolerance = amount.to_yoctonear();
+        self
+    }
+
+    /// Fails if the keys or balance of the account changed since the snapshot was taken.
+    pub async fn check(&self) -> anyhow::Result<()> {
+        let (balance, keys) = Self::fetch(&self.client, self.account_id.clone()).await?;
+
+        if keys != self.keys {
+            return Err(anyhow!(
+                "access keys of {} changed unexpectedly: before={:?}, after={:?}",
+                self.account_id,
+                self.keys,
+                keys,
+            ));
+        }
+
+        let within_tolerance =
+            balance <= self.balance && self.balance - balance <= self.balance_tolerance;
+        if balance != self.balance && !within_tolerance {
+            return Err(anyhow!(
+                "balance of {} changed unexpectedly: before={}, after={}",
+                self.account_id,
+                NearBalance::from_yoctonear(self.balance),
+                NearBalance::from_yoctonear(balance),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Run `task`, then check that the account was left untouched by it.
+    pub async fn scope<T>(&self, task: T) -> anyhow::Result<T::Output>
+    where
+        T: Future,
+    {
+        let output = task.await;
+        self.check().await?;
+        Ok(output)
+    }
+
+    async fn fetch(
+        client: &Client,
+        account_id: AccountId,
+    ) -> anyhow::Result<(Balance, Vec<(String, AccessKeyPermissionView)>)> {
+        let account = client
+            .view_account(account_id.clone())
+            .await
+            .map_err(|e| anyhow!(e))?;
+
+        // Nonces are expected to change whenever the account signs a transaction, so only
+        // the keys and their permissions are part of the snapshot.
+        let mut keys: Vec<_> = client
+            .access_key_list(account_id)
+            .await
+            .map_err(|e| anyhow!(e))?
+            .into_iter()
+            .map(|info| (info.public_key.to_string(), info.access_key.permission))
+            .collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok((account.amount, keys))
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Latency and error counters of the RPC requests sent through a worker, per RPC method,
+/// e.g. to track how much of a test suite's time is spent waiting on the chain, or to spot
+/// a degrading endpoint. Returned by [`Worker::metrics`](crate::Worker::metrics).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcMetrics {
+    methods: BTreeMap<String, MethodMetrics>,
+}
+
+/// Counters of a single RPC method. Retried requests count once per attempt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+}
+
+impl MethodMetrics {
+    pub fn mean_latency(&self) -> Duration {
+        match self.requests {
+            0 => Duration::default(),
+            requests => self.total_latency / requests as u32,
+        }
+    }
+}
+
+impl RpcMetrics {
+    /// Counters of `method`, such as `query` or `broadcast_tx_commit`, if it was called.
+    pub fn method(&self, method: &str) -> Option<&MethodMetrics> {
+        self.methods.get(method)
+    }
+
+    /// Counters of every method called, ordered by method name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MethodMetrics)> {
+        self.methods
+            .iter()
+            .map(|(method, metrics)| (method.as_str(), metrics))
+    }
+
+    /// Time spent waiting on RPC requests, summed over all methods. Requests sent in
+    /// parallel each count in full.
+    pub fn total_latency(&self) -> Duration {
+        self.methods
+            .values()
+            .map(|metrics| metrics.total_latency)
+            .sum()
+    }
+
+    pub(crate) fn record(&mut self, method: &str, latency: Duration, failed: bool) {
+        let metrics = self.methods.entry(method.to_string()).or_default();
+        metrics.requests += 1;
+        metrics.errors += u64::from(failed);
+        metrics.total_latency += latency;
+        metrics.max_latency = metrics.max_latency.max(latency);
+    }
+
+    /// The counters in the Prometheus text exposition format, to be served or pushed to a
+    /// Prometheus gateway by the caller.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let metrics: [(&str, &str, &str, fn(&MethodMetrics) -> String); 4] = [
+            (
+                "workspaces_rpc_requests_total",
+                "counter",
+                "RPC requests sent.",
+                |m| m.requests.to_string(),
+            ),
+            (
+                "workspaces_rpc_errors_total",
+                "counter",
+                "RPC requests which failed.",
+                |m| m.errors.to_string(),
+            ),
+            (
+                "workspaces_rpc_latency_seconds_total",
+                "counter",
+                "Time spent waiting on RPC requests.",
+                |m| m.total_latency.as_secs_f64().to_string(),
+            ),
+            (
+                "workspaces_rpc_latency_seconds_max",
+                "gauge",
+                "Slowest RPC request.",
+                |m| m.max_latency.as_secs_f64().to_string(),
+            ),
+        ];
+        for (name, kind, help, value) in metrics.iter() {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (method, metrics) in self.iter() {
+                let _ = writeln!(out, "{}{{method=\"{}\"}} {}", name, method, value(metrics));
+            }
+        }
+        out
+    }
+}
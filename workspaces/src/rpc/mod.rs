@@ -1,3 +1,8 @@
 pub mod api;
+pub(crate) mod client;
+pub(crate) mod features;
+pub(crate) mod guard;
+pub(crate) mod metrics;
+pub(crate) mod retry;
 pub(crate) mod tool;
-mod types;
+pub(crate) mod types;
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rand::Rng;
+
+use near_primitives::types::BlockHeightDelta;
+
+/// How RPC requests are retried when they time out or fail on the network, and how often
+/// the client polls while waiting for a transaction to become final. Delays grow
+/// exponentially from `initial_delay` up to `max_delay`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Total number of attempts, the first one included. At least one attempt is made.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Randomize each delay between half and all of its value, so that parallel tests
+    /// backing off don't all retry at the same moment. On by default.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Total number of attempts, as set with [`RetryPolicy::max_attempts`].
+    pub fn attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Delay to wait after the failed `attempt`, counting from 1.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        if self.jitter {
+            delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            delay
+        }
+    }
+}
+
+/// When a transaction sent without waiting counts as stuck, and how many times it is replaced
+/// before giving up, see
+/// [`TransactionStatus::wait_with_replacement`](crate::TransactionStatus::wait_with_replacement).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplacementPolicy {
+    stuck_after: BlockHeightDelta,
+    max_replacements: usize,
+}
+
+impl Default for ReplacementPolicy {
+    fn default() -> Self {
+        Self {
+            stuck_after: 10,
+            max_replacements: 3,
+        }
+    }
+}
+
+impl ReplacementPolicy {
+    /// Number of blocks produced since the block a transaction references after which it is
+    /// considered stuck. Transactions referencing a block older than the network's
+    /// transaction validity period are dropped, so this should be well below it.
+    pub fn stuck_after(mut self, blocks: BlockHeightDelta) -> Self {
+        self.stuck_after = blocks;
+        self
+    }
+
+    pub fn max_replacements(mut self, max_replacements: usize) -> Self {
+        self.max_replacements = max_replacements;
+        self
+    }
+
+    pub(crate) fn stuck_after_blocks(&self) -> BlockHeightDelta {
+        self.stuck_after
+    }
+
+    pub(crate) fn replacements(&self) -> usize {
+        self.max_replacements
+    }
+}
+
+/// The default policy along with the overrides of individual RPC methods, shared by all
+/// clones of a client.
+#[derive(Debug, Default)]
+pub(crate) struct RetryPolicies {
+    pub(crate) default: RetryPolicy,
+    pub(crate) methods: HashMap<String, RetryPolicy>,
+}
+
+impl RetryPolicies {
+    pub(crate) fn get(&self, method: &str) -> RetryPolicy {
+        self.methods.get(method).unwrap_or(&self.default).clone()
+    }
+}
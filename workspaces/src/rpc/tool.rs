@@ -1,96 +1,64 @@
-// TODO: Remove this when near-jsonrpc-client crate no longer defaults to deprecation for
-//       warnings about unstable API.
-#![allow(deprecated)]
-
-use std::collections::HashMap;
 use std::convert::TryInto;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use anyhow::anyhow;
 use chrono::Utc;
 use rand::Rng;
+use reqwest::StatusCode;
 use url::Url;
 
-use near_crypto::PublicKey;
-use near_jsonrpc_client::{
-    errors::{JsonRpcError, JsonRpcServerError},
-    methods, JsonRpcClient,
-};
-use near_jsonrpc_primitives::types::{query::QueryResponseKind, transactions::RpcTransactionError};
-use near_primitives::hash::CryptoHash;
-use near_primitives::transaction::SignedTransaction;
-use near_primitives::types::{AccountId, BlockHeight, Finality};
-use near_primitives::views::{AccessKeyView, FinalExecutionOutcomeView, QueryRequest, StateItem};
-
-use crate::runtime::context::MISSING_RUNTIME_ERROR;
-
-fn rt_current_addr() -> String {
-    crate::runtime::context::current()
-        .expect(MISSING_RUNTIME_ERROR)
-        .rpc_addr()
+use near_crypto::{InMemorySigner, KeyFile, PublicKey};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::types::AccountId;
+use near_primitives::views::{FinalExecutionOutcomeView, StateItem};
+
+use super::client::Client;
+use super::types::StateRecords;
+use crate::seed;
+use crate::trace;
+use crate::{AccountAlreadyExists, HelperError};
+
+/// Describes a transaction by its hash, along with a ready-to-click explorer link when
+/// the network has an explorer.
+pub(crate) fn describe_tx(tx_hash: &CryptoHash, explorer_url: Option<&str>) -> String {
+    match explorer_url {
+        Some(explorer_url) => format!("{} ({})", tx_hash, explorer_link(tx_hash, explorer_url)),
+        None => tx_hash.to_string(),
+    }
 }
 
-pub(crate) fn json_client() -> JsonRpcClient {
-    JsonRpcClient::connect(&rt_current_addr())
+/// Link to the transaction `tx_hash` on the block explorer at `explorer_url`.
+pub(crate) fn explorer_link(tx_hash: &CryptoHash, explorer_url: &str) -> String {
+    format!("{}/transactions/{}", explorer_url, tx_hash)
 }
 
-pub(crate) async fn access_key(
-    account_id: AccountId,
-    pk: PublicKey,
-) -> Result<(AccessKeyView, BlockHeight, CryptoHash), String> {
-    let query_resp = json_client()
-        .call(&methods::query::RpcQueryRequest {
-            block_reference: Finality::Final.into(),
-            request: QueryRequest::ViewAccessKey {
-                account_id,
-                public_key: pk,
-            },
-        })
-        .await
-        .map_err(|err| format!("Failed to fetch public key info: {:?}", err))?;
-
-    match query_resp.kind {
-        QueryResponseKind::AccessKey(access_key) => {
-            Ok((access_key, query_resp.block_height, query_resp.block_hash))
-        }
-        _ => Err("Could not retrieve access key".to_owned()),
-    }
+/// Signer of the key file at `path`, as written by `near-cli` or the sandbox. Unlike
+/// `InMemorySigner::from_file`, fails rather than panics on a missing or invalid file.
+pub(crate) fn signer_from_file(path: &Path) -> anyhow::Result<InMemorySigner> {
+    let key_file = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(serde_json::from_str::<KeyFile>(&json)?))
+        .map_err(|err| anyhow!("could not load key file {}: {}", path.display(), err))?;
+    Ok(InMemorySigner::from_secret_key(
+        key_file.account_id,
+        key_file.secret_key,
+    ))
 }
 
-pub(crate) async fn send_tx(tx: SignedTransaction) -> Result<FinalExecutionOutcomeView, String> {
-    let client = json_client();
-    let transaction_info_result = loop {
-        let transaction_info_result = client
-            .clone()
-            .call(&methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
-                signed_transaction: tx.clone(),
-            })
-            .await;
-
-        if let Err(ref err) = transaction_info_result {
-            if matches!(
-                err,
-                JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
-                    RpcTransactionError::TimeoutError
-                ))
-            ) {
-                eprintln!("transaction timeout: {:?}", err);
-                continue;
-            }
-        }
-
-        break transaction_info_result;
-    };
-
-    // TODO: remove this after adding exponential backoff
-    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-
-    transaction_info_result.map_err(|e| format!("Error transaction: {:?}", e))
+/// Directory where credentials of accounts on the network `network_name` are stored.
+pub(crate) fn credentials_dir(network_name: &str) -> anyhow::Result<PathBuf> {
+    let mut path = dirs::home_dir().ok_or_else(|| anyhow!("Could not get HOME_DIR".to_string()))?;
+    path.push(".near-credentials");
+    path.push(network_name);
+    Ok(path)
 }
 
-pub(crate) fn credentials_filepath(account_id: AccountId) -> anyhow::Result<PathBuf> {
-    let mut path = crate::runtime::context::current()
-        .expect(MISSING_RUNTIME_ERROR)
-        .keystore_path()?;
+pub(crate) fn credentials_filepath(
+    keystore_dir: &Path,
+    account_id: AccountId,
+) -> anyhow::Result<PathBuf> {
+    let mut path = keystore_dir.to_path_buf();
 
     // Create this path's directories if they don't exist:
     std::fs::create_dir_all(path.clone())?;
@@ -99,24 +67,16 @@ pub(crate) fn credentials_filepath(account_id: AccountId) -> anyhow::Result<Path
     Ok(path)
 }
 
-/// Convert `StateItem`s over to a Map<data_key, value_bytes> representation.
+/// Convert `StateItem`s over to ordered `StateRecords`, keeping keys as raw bytes.
 /// Assumes key and value are base64 encoded, so this also decodes them.
-pub(crate) fn into_state_map(
-    state_items: &[StateItem],
-) -> anyhow::Result<HashMap<String, Vec<u8>>> {
-    let decode = |s: &StateItem| {
-        Ok((
-            String::from_utf8(base64::decode(&s.key)?)?,
-            base64::decode(&s.value)?,
-        ))
-    };
+pub(crate) fn into_state_records(state_items: &[StateItem]) -> anyhow::Result<StateRecords> {
+    let decode = |s: &StateItem| Ok((base64::decode(&s.key)?, base64::decode(&s.value)?));
 
     state_items.iter().map(decode).collect()
 }
 
 pub(crate) fn random_account_id() -> AccountId {
-    let mut rng = rand::thread_rng();
-    let random_num = rng.gen_range(10000000000000usize..99999999999999);
+    let random_num = seed::with_rng(|rng| rng.gen_range(10000000000000usize..99999999999999));
     let account_id = format!("dev-{}-{}", Utc::now().format("%Y%m%d%H%M%S"), random_num);
     let account_id: AccountId = account_id
         .try_into()
@@ -125,23 +85,161 @@ pub(crate) fn random_account_id() -> AccountId {
     account_id
 }
 
-pub(crate) async fn url_create_account(
+/// Implicit account id controlled by `public_key`: the hex encoding of the key's bytes. Such
+/// an account comes into existence once it receives a transfer, without any helper or
+/// parent account creating it.
+pub(crate) fn implicit_account_id(public_key: &PublicKey) -> AccountId {
+    hex::encode(public_key.key_data())
+        .try_into()
+        .expect("could not convert implicit account into AccountId")
+}
+
+/// Attempts at a helper request, retried when it is rate limited, or fails on the network or
+/// the server side.
+const HELPER_ATTEMPTS: u32 = 3;
+/// Longest a rate limited helper request waits before being retried. Helpers asking to wait
+/// longer than this are given up on, so that the next helper can be tried instead.
+const MAX_HELPER_DELAY: Duration = Duration::from_secs(30);
+
+/// Ask the helpers at `helper_urls` to create `account_id`, in order, moving on to the next
+/// one while they are unavailable, see [`HelperError::is_unavailable`]. Returns the outcome
+/// of the transaction creating it, if the helper sent it back, which older helpers don't.
+pub(crate) async fn helpers_create_account(
+    client: &Client,
+    helper_urls: &[Url],
+    account_id: AccountId,
+    pk: PublicKey,
+) -> anyhow::Result<Option<FinalExecutionOutcomeView>> {
+    let mut last_err = None;
+    for helper_url in helper_urls {
+        if let Some(err) = &last_err {
+            trace::warn!("{}, trying the helper at {}", err, helper_url);
+        }
+        match url_create_account(client, helper_url.clone(), account_id.clone(), pk.clone()).await {
+            Err(err) if is_unavailable(&err) => last_err = Some(err),
+            // An unavailable helper might have created the account before failing:
+            Err(err) if last_err.is_some() && err.is::<AccountAlreadyExists>() => {
+                return if has_key(client, &account_id, &pk).await {
+                    Ok(None)
+                } else {
+                    Err(err)
+                };
+            }
+            result => return result,
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow!("no helper to create {} with", account_id)))
+}
+
+/// Whether `pk` is a key of `account_id`, telling an account created by an earlier attempt at
+/// the same request apart from one that was taken already.
+async fn has_key(client: &Client, account_id: &AccountId, pk: &PublicKey) -> bool {
+    client
+        .access_key(account_id.clone(), pk.clone())
+        .await
+        .is_ok()
+}
+
+/// Whether `err` says a helper is unavailable, see [`HelperError::is_unavailable`].
+pub(crate) fn is_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<HelperError>()
+        .map_or(false, HelperError::is_unavailable)
+}
+
+/// Ask the helper at `helper_url` to create `account_id`, retrying with backoff while it is
+/// rate limited or failing on its side.
+async fn url_create_account(
+    client: &Client,
     helper_url: Url,
     account_id: AccountId,
     pk: PublicKey,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<FinalExecutionOutcomeView>> {
     let helper_addr = helper_url.join("account")?;
+    let body = serde_json::to_vec(&serde_json::json!({
+        "newAccountId": account_id.to_string(),
+        "newAccountPublicKey": pk.to_string(),
+    }))?;
+    // Same for every retry of this request, so the helper can tell them apart from a
+    // different request for the same name:
+    let idempotency_key = hex::encode(hash(&body).0);
+
+    let mut attempt = 1;
+    loop {
+        let delay = Duration::from_secs(1 << (attempt - 1));
+        // TODO(maybe): need this in near-jsonrpc-client as well:
+        let resp = reqwest::Client::new()
+            .post(helper_addr.clone())
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", &idempotency_key)
+            .body(body.clone())
+            .send()
+            .await;
+
+        match resp {
+            Ok(resp) if resp.status().is_success() => {
+                let text = resp.text().await.unwrap_or_default();
+                return Ok(serde_json::from_str(&text).ok());
+            }
+            Ok(resp) => {
+                let status = resp.status();
+                let retry_after = resp
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+                let text = resp.text().await.unwrap_or_default();
+                // A previous attempt might have gone through without us seeing its response:
+                if text.contains("already exists") {
+                    if attempt > 1 && has_key(client, &account_id, &pk).await {
+                        return Ok(None);
+                    }
+                    return Err(AccountAlreadyExists { account_id }.into());
+                }
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    let wait = retry_after.unwrap_or(delay);
+                    if wait > MAX_HELPER_DELAY || attempt == HELPER_ATTEMPTS {
+                        return Err(HelperError::RateLimited { retry_after }.into());
+                    }
+                    trace::warn!("helper is rate limiting, retrying in {:?}", wait);
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                    continue;
+                }
+                let lowercase = text.to_lowercase();
+                if status == StatusCode::BAD_REQUEST
+                    && lowercase.contains("invalid")
+                    && lowercase.contains("account")
+                {
+                    return Err(HelperError::InvalidAccountId {
+                        account_id,
+                        message: text,
+                    }
+                    .into());
+                }
+                if !status.is_server_error() || attempt == HELPER_ATTEMPTS {
+                    return Err(HelperError::Failed {
+                        account_id,
+                        status: status.as_u16(),
+                        message: text,
+                    }
+                    .into());
+                }
+                trace::warn!("helper request failed with {}, retrying: {}", status, text);
+            }
+            Err(err) if attempt < HELPER_ATTEMPTS => {
+                trace::warn!("helper request failed, retrying: {:?}", err);
+            }
+            Err(err) => {
+                return Err(HelperError::Unreachable {
+                    url: helper_url.to_string(),
+                    message: err.to_string(),
+                }
+                .into())
+            }
+        }
 
-    // TODO(maybe): need this in near-jsonrpc-client as well:
-    let _resp = reqwest::Client::new()
-        .post(helper_addr)
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_vec(&serde_json::json!({
-            "newAccountId": account_id.to_string(),
-            "newAccountPublicKey": pk.to_string(),
-        }))?)
-        .send()
-        .await?;
-
-    Ok(())
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
 }
@@ -1,9 +1,25 @@
+use anyhow::anyhow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::iter::FromIterator;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use near_crypto::PublicKey;
+use near_primitives::account::AccessKey;
+use near_primitives::borsh::BorshDeserialize;
 use near_primitives::hash::CryptoHash;
-use near_primitives::types::AccountId;
+use near_primitives::types::{AccountId, StoreKey};
 
 const ONE_NEAR: u128 = 10u128.pow(24);
+const ONE_MILLINEAR: u128 = 10u128.pow(21);
+const ONE_GGAS: u64 = 10u64.pow(9);
+const ONE_TGAS: u64 = 10u64.pow(12);
+
+/// The total supply of NEAR is around a billion NEAR. Any amount above this is certainly
+/// a mistake, such as a mistyped exponent, and gets rejected before reaching the network.
+const MAX_SANE_BALANCE: u128 = 10u128.pow(9) * ONE_NEAR;
 
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct NearBalance {
     yoctonear_amount: u128,
 }
@@ -13,9 +29,100 @@ impl NearBalance {
         Self { yoctonear_amount }
     }
 
+    /// Whole NEAR amount. Errors out if the amount overflows or exceeds the NEAR supply.
+    pub fn from_near(near_amount: u128) -> anyhow::Result<Self> {
+        Self::from_units(near_amount, ONE_NEAR, "NEAR")
+    }
+
+    /// Amount in thousandths of a NEAR. Errors out if the amount overflows or exceeds the
+    /// NEAR supply.
+    pub fn from_millinear(millinear_amount: u128) -> anyhow::Result<Self> {
+        Self::from_units(millinear_amount, ONE_MILLINEAR, "milliNEAR")
+    }
+
+    fn from_units(amount: u128, unit: u128, unit_name: &str) -> anyhow::Result<Self> {
+        let balance = amount
+            .checked_mul(unit)
+            .map(Self::from_yoctonear)
+            .ok_or_else(|| anyhow!("{} {} overflows a u128 of yoctoNEAR", amount, unit_name))?;
+        balance.check_sane()?;
+        Ok(balance)
+    }
+
     pub fn to_yoctonear(&self) -> u128 {
         self.yoctonear_amount
     }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.yoctonear_amount
+            .checked_add(other.yoctonear_amount)
+            .map(Self::from_yoctonear)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.yoctonear_amount
+            .checked_sub(other.yoctonear_amount)
+            .map(Self::from_yoctonear)
+    }
+
+    /// Difference between the two amounts, whichever is larger.
+    pub fn abs_diff(self, other: Self) -> Self {
+        Self::from_yoctonear(
+            self.yoctonear_amount.max(other.yoctonear_amount)
+                - self.yoctonear_amount.min(other.yoctonear_amount),
+        )
+    }
+
+    /// Panics unless this balance is `amount` more than `before`, give or take `tolerance`.
+    /// Balances of accounts sending transactions also pay for gas, which a tolerance of a
+    /// few milliNEAR usually covers.
+    pub fn assert_increased_by(&self, before: Self, amount: Self, tolerance: Self) {
+        let expected = before
+            .checked_add(amount)
+            .unwrap_or_else(|| panic!("{} plus {} overflows", before, amount));
+        self.assert_close_to(expected, "increase", before, amount, tolerance);
+    }
+
+    /// Panics unless this balance is `amount` less than `before`, give or take `tolerance`.
+    pub fn assert_decreased_by(&self, before: Self, amount: Self, tolerance: Self) {
+        let expected = before
+            .checked_sub(amount)
+            .unwrap_or_else(|| panic!("{} can't decrease by {}", before, amount));
+        self.assert_close_to(expected, "decrease", before, amount, tolerance);
+    }
+
+    fn assert_close_to(
+        &self,
+        expected: Self,
+        change: &str,
+        before: Self,
+        amount: Self,
+        tolerance: Self,
+    ) {
+        if self.abs_diff(expected) > tolerance {
+            panic!(
+                "expected balance of {} to {} by {}, give or take {}, but it went to {} ({} yoctoNEAR off)",
+                before,
+                change,
+                amount,
+                tolerance,
+                self,
+                self.abs_diff(expected).yoctonear_amount
+            );
+        }
+    }
+
+    /// Errors out if this amount exceeds the total NEAR supply, which usually comes from a
+    /// mistyped exponent somewhere, e.g. sending 10^6 NEAR instead of 1.
+    pub fn check_sane(&self) -> anyhow::Result<()> {
+        if self.yoctonear_amount > MAX_SANE_BALANCE {
+            return Err(anyhow!(
+                "{} exceeds the total NEAR supply; is an exponent mistyped?",
+                self
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for NearBalance {
@@ -39,6 +146,59 @@ impl std::fmt::Display for NearBalance {
     }
 }
 
+/// Gas attached to a function call. Converts from and into the plain `u64` amounts nearcore
+/// works with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Gas {
+    gas: u64,
+}
+
+impl Gas {
+    /// Most gas a single function call can be given, 300 Tgas.
+    pub const MAX: Gas = Gas::from_tgas(300);
+
+    pub const fn from_gas(gas: u64) -> Self {
+        Self { gas }
+    }
+
+    /// Amount in billions of gas, the unit of most host function costs.
+    pub const fn from_ggas(ggas: u64) -> Self {
+        Self::from_gas(ggas.saturating_mul(ONE_GGAS))
+    }
+
+    /// Amount in trillions of gas. 1 Tgas is roughly a millisecond of compute.
+    pub const fn from_tgas(tgas: u64) -> Self {
+        Self::from_gas(tgas.saturating_mul(ONE_TGAS))
+    }
+
+    pub fn as_gas(&self) -> u64 {
+        self.gas
+    }
+}
+
+impl From<u64> for Gas {
+    fn from(gas: u64) -> Self {
+        Self::from_gas(gas)
+    }
+}
+
+impl From<Gas> for u64 {
+    fn from(gas: Gas) -> Self {
+        gas.as_gas()
+    }
+}
+
+impl std::fmt::Display for Gas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:0>3} Tgas",
+            self.gas / ONE_TGAS,
+            self.gas / ONE_GGAS % 1_000
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct AccountInfo {
     pub account_id: AccountId,
@@ -47,4 +207,274 @@ pub struct AccountInfo {
     pub balance: NearBalance,
     pub stake: NearBalance,
     pub used_storage_bytes: u64,
+    /// Hash of the deployed contract code, the default hash if there is none.
+    pub code_hash: CryptoHash,
+}
+
+/// An access key of an account, as returned by `view_access_key_list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessKeyInfo {
+    pub public_key: PublicKey,
+    pub access_key: AccessKey,
+}
+
+/// Account fields to overwrite in the sandbox. Fields left unset keep their current value,
+/// or their default for accounts which don't exist yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountPatch {
+    pub(crate) balance: Option<NearBalance>,
+    pub(crate) locked: Option<NearBalance>,
+    pub(crate) code_hash: Option<CryptoHash>,
+    pub(crate) storage_usage: Option<u64>,
+}
+
+impl AccountPatch {
+    pub fn balance(mut self, balance: NearBalance) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Balance locked for staking.
+    pub fn locked(mut self, locked: NearBalance) -> Self {
+        self.locked = Some(locked);
+        self
+    }
+
+    /// Hash of the contract code. Patching code through `patch_code` sets this already.
+    pub fn code_hash(mut self, code_hash: CryptoHash) -> Self {
+        self.code_hash = Some(code_hash);
+        self
+    }
+
+    pub fn storage_usage(mut self, storage_usage: u64) -> Self {
+        self.storage_usage = Some(storage_usage);
+        self
+    }
+}
+
+/// Prefix of the state keys to fetch with `view_state`, such as `STATE` for a near-sdk
+/// contract's top-level struct or a collection's prefix. The empty prefix matches the whole
+/// state. Converts from strings and byte slices, taken as the raw key bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatePrefix(Vec<u8>);
+
+impl StatePrefix {
+    pub fn raw(prefix: impl Into<Vec<u8>>) -> Self {
+        Self(prefix.into())
+    }
+
+    /// Prefix given in base64, the encoding RPC nodes use for state keys.
+    pub fn base64(encoded: &str) -> anyhow::Result<Self> {
+        Ok(Self(base64::decode(encoded)?))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<&str> for StatePrefix {
+    fn from(prefix: &str) -> Self {
+        Self::raw(prefix)
+    }
+}
+
+impl From<String> for StatePrefix {
+    fn from(prefix: String) -> Self {
+        Self::raw(prefix)
+    }
+}
+
+impl From<&[u8]> for StatePrefix {
+    fn from(prefix: &[u8]) -> Self {
+        Self::raw(prefix)
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for StatePrefix {
+    fn from(prefix: &[u8; N]) -> Self {
+        Self::raw(&prefix[..])
+    }
+}
+
+impl From<Vec<u8>> for StatePrefix {
+    fn from(prefix: Vec<u8>) -> Self {
+        Self(prefix)
+    }
+}
+
+impl From<StoreKey> for StatePrefix {
+    fn from(prefix: StoreKey) -> Self {
+        Self(prefix.into())
+    }
+}
+
+/// Contract state as returned by `view_state`, ordered by key. Keys are kept as raw bytes,
+/// since contracts are free to encode them however they like and they are not guaranteed
+/// to be valid UTF-8. Ordering follows the trie, so it is stable across queries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateRecords(BTreeMap<Vec<u8>, Vec<u8>>);
+
+impl StateRecords {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&[u8]> {
+        self.0.get(key.as_ref()).map(Vec::as_slice)
+    }
+
+    /// Borsh-decodes the value under `key`, such as a contract's `STATE`.
+    pub fn get_borsh<T: BorshDeserialize>(&self, key: impl AsRef<[u8]>) -> anyhow::Result<T> {
+        let key = key.as_ref();
+        let value = self
+            .get(key)
+            .ok_or_else(|| anyhow!("no state under key {:?}", String::from_utf8_lossy(key)))?;
+        Ok(T::try_from_slice(value)?)
+    }
+
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Option<Vec<u8>> {
+        self.0.remove(key.as_ref())
+    }
+
+    /// All records in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.0.iter().map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+
+    /// Records whose key starts with `prefix`, such as the entries of a near-sdk collection,
+    /// in key order.
+    pub fn prefix<'a>(&'a self, prefix: &'a [u8]) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+        self.0
+            .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+
+    /// Records strictly after `key` in key order. Useful to resume iterating over the state
+    /// from the last key seen, e.g. when comparing state fetched in pages.
+    pub fn after<'a>(&'a self, key: &'a [u8]) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+        self.0
+            .range::<[u8], _>((Bound::Excluded(key), Bound::Unbounded))
+            .map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+
+    /// Keys which are valid UTF-8, such as `STATE`, along with their values. Binary keys
+    /// are skipped.
+    pub fn utf8_keyed(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.iter()
+            .filter_map(|(k, v)| std::str::from_utf8(k).ok().map(|k| (k, v)))
+    }
+
+    /// Borsh-decodes the records under `prefix`, with the prefix stripped from their keys.
+    /// This matches how near-sdk collections such as `LookupMap` lay out their entries.
+    pub fn decode_prefixed<K, V>(&self, prefix: &[u8]) -> anyhow::Result<Vec<(K, V)>>
+    where
+        K: BorshDeserialize,
+        V: BorshDeserialize,
+    {
+        self.prefix(prefix)
+            .map(|(k, v)| {
+                let key = K::try_from_slice(&k[prefix.len()..])
+                    .map_err(|e| anyhow!("could not decode key {:?}: {}", k, e))?;
+                let value = V::try_from_slice(v)
+                    .map_err(|e| anyhow!("could not decode value of key {:?}: {}", k, e))?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    /// Keys added, removed or holding a different value in `other`, in key order.
+    pub fn changed_keys<'a>(&'a self, other: &'a StateRecords) -> Vec<&'a [u8]> {
+        let keys: BTreeSet<&[u8]> = self
+            .0
+            .keys()
+            .chain(other.0.keys())
+            .map(Vec::as_slice)
+            .collect();
+        keys.into_iter()
+            .filter(|key| self.get(key) != other.get(key))
+            .collect()
+    }
+
+    pub fn into_inner(self) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.0
+    }
+}
+
+impl FromIterator<(Vec<u8>, Vec<u8>)> for StateRecords {
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for StateRecords {
+    type Item = (Vec<u8>, Vec<u8>);
+    type IntoIter = std::collections::btree_map::IntoIter<Vec<u8>, Vec<u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Contract code to deploy: either a path to a wasm file, or the wasm bytes themselves, such
+/// as from `include_bytes!` or a build script. Strings are treated as paths.
+pub trait ContractCode {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>>;
+}
+
+impl ContractCode for &Path {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self)
+    }
+}
+
+impl ContractCode for PathBuf {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self)
+    }
+}
+
+impl ContractCode for &PathBuf {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self)
+    }
+}
+
+impl ContractCode for &str {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self)
+    }
+}
+
+impl ContractCode for String {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self)
+    }
+}
+
+impl ContractCode for &[u8] {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+impl<const N: usize> ContractCode for &[u8; N] {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+impl ContractCode for Vec<u8> {
+    fn into_wasm(self) -> std::io::Result<Vec<u8>> {
+        Ok(self)
+    }
 }
@@ -1,11 +1,20 @@
-use super::RuntimeFlavor;
+use super::{ContextNetwork, RuntimeFlavor};
 use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::FutureExt;
+
+use crate::rpc::client::{Client, TxHook, TxHooks};
+use crate::{ExecutionResult, Worker};
 
 pub const MISSING_RUNTIME_ERROR: &str =
-    "there is no runtime running: need to be ran from a NEAR runtime context";
+    "there is no runtime running: need to be ran from a NEAR runtime context, such as \
+     `#[workspaces::test]` or `with_sandbox`. Going through a `Worker` needs no such context";
 
 thread_local! {
     static RT_CONTEXT: RefCell<Option<RuntimeFlavor>> = RefCell::new(None);
+    static TX_HOOKS: RefCell<TxHooks> = RefCell::new(TxHooks::default());
 }
 
 pub(crate) fn current() -> Option<RuntimeFlavor> {
@@ -13,23 +22,61 @@ pub(crate) fn current() -> Option<RuntimeFlavor> {
 }
 
 pub(crate) fn enter(flavor: RuntimeFlavor) -> EnterGuard {
-    RT_CONTEXT.with(|ctx| {
-        let old = ctx.borrow_mut().replace(flavor);
-        EnterGuard(old)
-    })
+    let old_flavor = RT_CONTEXT.with(|ctx| ctx.borrow_mut().replace(flavor));
+    let old_hooks = TX_HOOKS.with(|hooks| hooks.replace(TxHooks::default()));
+    EnterGuard(old_flavor, old_hooks)
+}
+
+/// Replace the flavor of the runtime entered on this thread, keeping its hooks. Used when
+/// the runtime's node ends up at a different address than when it was entered.
+pub(crate) fn update(flavor: RuntimeFlavor) {
+    RT_CONTEXT.with(|ctx| *ctx.borrow_mut() = Some(flavor));
+}
+
+/// Client for the runtime entered on this thread, sharing the hooks registered within it.
+pub(crate) fn client() -> Client {
+    let flavor = current().expect(MISSING_RUNTIME_ERROR);
+    let hooks = TX_HOOKS.with(|hooks| hooks.borrow().clone());
+    flavor.client().with_hooks(hooks)
+}
+
+/// Worker acting on the runtime entered on this thread, backing the free functions.
+pub(crate) fn worker() -> Worker<ContextNetwork> {
+    Worker::new(ContextNetwork::current())
+}
+
+/// Register a hook to be ran after every transaction sent within the current runtime,
+/// such as asserting a protocol invariant through view calls. If the hook fails, the
+/// call which sent the transaction fails with it. Transactions sent from within a hook
+/// do not trigger the hooks again.
+pub fn register_tx_hook<F, Fut>(hook: F)
+where
+    F: Fn(ExecutionResult) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let hook: TxHook = Arc::new(move |result| hook(result).boxed());
+    TX_HOOKS.with(|hooks| hooks.borrow().lock().unwrap().push(hook));
 }
 
 // EnterGuard used for when entering into a new runtime context then
 // after dropping (when runtime ends), goes back to the previous
-// runtime context. Used for multi-threading too when a new thread is
-// spun up, but currently near Runtimes are single threaded only.
-#[derive(Debug)]
-pub(crate) struct EnterGuard(Option<RuntimeFlavor>);
+// runtime context and its hooks. Used for multi-threading too when a
+// new thread is spun up, but currently near Runtimes are single threaded only.
+pub(crate) struct EnterGuard(Option<RuntimeFlavor>, TxHooks);
+
+impl std::fmt::Debug for EnterGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EnterGuard").field(&self.0).finish()
+    }
+}
 
 impl Drop for EnterGuard {
     fn drop(&mut self) {
         RT_CONTEXT.with(|ctx| {
             *ctx.borrow_mut() = self.0.take();
         });
+        TX_HOOKS.with(|hooks| {
+            *hooks.borrow_mut() = std::mem::take(&mut self.1);
+        });
     }
 }
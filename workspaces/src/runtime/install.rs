@@ -0,0 +1,153 @@
+//! Locating the near-sandbox binary, which is downloaded for the host platform on first use
+//! and cached, so tests can run without building nearcore first.
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+
+use near_primitives::hash::hash;
+
+use crate::trace;
+
+/// Path of a near-sandbox binary to use instead of the downloaded one, e.g. one built from a
+/// local nearcore checkout.
+const SANDBOX_BIN_PATH_ENV: &str = "NEAR_SANDBOX_BIN_PATH";
+/// nearcore version to download the sandbox of, as `<branch>/<commit>`.
+const SANDBOX_VERSION_ENV: &str = "NEAR_SANDBOX_VERSION";
+/// Expected SHA-256 of the downloaded archive, hex encoded. Verified when set.
+const SANDBOX_SHA256_ENV: &str = "NEAR_SANDBOX_SHA256";
+
+/// Same nearcore revision as the one `near-primitives` is pinned to, so the sandbox speaks
+/// the protocol this crate is built against.
+const DEFAULT_SANDBOX_VERSION: &str = "master/88a9e74d4b99867e417fe199cbd74559fd4fc349";
+const BINARY_BASE_URL: &str = "https://s3-us-west-1.amazonaws.com/build.nearprotocol.com/nearcore";
+const BINARY_NAME: &str = "near-sandbox";
+/// Stores the SHA-256 of the cached binary, checked on every use to catch corrupted or
+/// tampered caches.
+const CHECKSUM_FILE: &str = "near-sandbox.sha256";
+
+/// Path of the near-sandbox binary, downloading it first if it isn't cached yet.
+pub(crate) fn sandbox_bin() -> anyhow::Result<PathBuf> {
+    if let Some(path) = env::var_os(SANDBOX_BIN_PATH_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let version =
+        env::var(SANDBOX_VERSION_ENV).unwrap_or_else(|_| DEFAULT_SANDBOX_VERSION.to_string());
+    let dir = cache_dir()?.join(version.replace('/', "-"));
+    let bin = dir.join(BINARY_NAME);
+    if !bin.exists() {
+        install(&version, &dir)?;
+    }
+    verify(&bin, &dir.join(CHECKSUM_FILE))?;
+
+    Ok(bin)
+}
+
+fn cache_dir() -> anyhow::Result<PathBuf> {
+    let mut path = dirs::cache_dir().ok_or_else(|| anyhow!("Could not get CACHE_DIR"))?;
+    path.push("near-workspaces");
+    path.push("sandbox");
+    Ok(path)
+}
+
+fn platform() -> anyhow::Result<&'static str> {
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Ok("Linux-x86_64"),
+        ("macos", "x86_64") => Ok("Darwin-x86_64"),
+        (os, arch) => Err(anyhow!(
+            "no prebuilt near-sandbox for {}-{}: build it from nearcore and set {}",
+            os,
+            arch,
+            SANDBOX_BIN_PATH_ENV
+        )),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(hash(bytes).0)
+}
+
+fn install(version: &str, dir: &Path) -> anyhow::Result<()> {
+    let url = format!(
+        "{}/{}/{}/{}.tar.gz",
+        BINARY_BASE_URL,
+        platform()?,
+        version,
+        BINARY_NAME
+    );
+    trace::info!("Downloading near-sandbox from {}", url);
+    let archive = download(url.clone())?;
+
+    if let Ok(expected) = env::var(SANDBOX_SHA256_ENV) {
+        let actual = sha256_hex(&archive);
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(anyhow!(
+                "checksum mismatch for {}: expected {}, got {}",
+                url,
+                expected.trim(),
+                actual
+            ));
+        }
+    }
+
+    let binary = extract_binary(&archive)?;
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(CHECKSUM_FILE), sha256_hex(&binary))?;
+
+    // Concurrent test processes may be installing at the same time, so the binary is only
+    // moved in place once fully written:
+    let partial = dir.join(format!(".{}-{}", BINARY_NAME, std::process::id()));
+    fs::write(&partial, &binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(&partial, dir.join(BINARY_NAME))?;
+
+    Ok(())
+}
+
+/// Fetches `url` on a separate thread, as this is called from synchronous code which might
+/// itself be running within a tokio runtime.
+fn download(url: String) -> anyhow::Result<Vec<u8>> {
+    std::thread::spawn(move || -> anyhow::Result<Vec<u8>> {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let resp = reqwest::get(&url).await?.error_for_status()?;
+            Ok(resp.bytes().await?.to_vec())
+        })
+    })
+    .join()
+    .map_err(|_| anyhow!("near-sandbox download panicked"))?
+}
+
+fn extract_binary(archive: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.file_name() == Some(BINARY_NAME.as_ref()) {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+    Err(anyhow!("no {} binary in downloaded archive", BINARY_NAME))
+}
+
+fn verify(bin: &Path, checksum_file: &Path) -> anyhow::Result<()> {
+    let expected = fs::read_to_string(checksum_file)?;
+    let actual = sha256_hex(&fs::read(bin)?);
+    if expected.trim() != actual {
+        return Err(anyhow!(
+            "cached near-sandbox at {} does not match its checksum: delete {} to download it again",
+            bin.display(),
+            bin.parent().unwrap_or(bin).display()
+        ));
+    }
+    Ok(())
+}
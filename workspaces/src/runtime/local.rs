@@ -1,92 +1,151 @@
 use anyhow::anyhow;
 use portpicker::pick_unused_port;
 
-use std::fs::{self, File};
-use std::io::prelude::*;
+use rand::Rng;
+
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Child;
+#[cfg(feature = "tracing")]
+use std::process::Stdio;
+use std::process::{Child, Command};
+use std::sync::Mutex;
 use std::{thread, time::Duration};
 
-use near_crypto::{InMemorySigner, PublicKey, Signer};
-use near_primitives::transaction::SignedTransaction;
+use near_crypto::{InMemorySigner, PublicKey};
+use near_primitives::account::AccessKey;
+use near_primitives::transaction::{
+    Action, AddKeyAction, CreateAccountAction, DeployContractAction, TransferAction,
+};
 use near_primitives::types::AccountId;
 use near_primitives::views::FinalExecutionOutcomeView;
 
 use super::context;
+use super::install;
+use super::shared::{self, SharedSandboxLease};
 use super::RuntimeFlavor;
-use crate::rpc::tool;
-use crate::{CallExecutionResult, NEAR_BASE};
+use crate::network::GenesisConfig;
+use crate::rpc::client::Client;
+use crate::trace;
+use crate::{AccountAlreadyExists, ExecutionResult, InitCall, NEAR_BASE};
 
-fn home_dir(port: u16) -> PathBuf {
-    let mut path = std::env::temp_dir();
-    path.push(format!("sandbox-{}", port));
-    path
+/// A fresh home directory for a sandbox node under the system temp directory (`TMPDIR` on
+/// unix), so concurrent sandboxes and leftovers of previous runs never share node data.
+fn unique_home_dir(rpc_port: u16) -> PathBuf {
+    let mut rng = rand::thread_rng();
+    loop {
+        let path = std::env::temp_dir().join(format!(
+            "sandbox-{}-{}-{:x}",
+            rpc_port,
+            std::process::id(),
+            rng.gen::<u64>()
+        ));
+        if !path.exists() {
+            return path;
+        }
+    }
 }
 
-fn root_account() -> InMemorySigner {
-    let rt = crate::runtime::context::current().expect(context::MISSING_RUNTIME_ERROR);
-    let port = match rt {
-        RuntimeFlavor::Sandbox(port) => port,
-        _ => panic!("expected to be in sandbox runtime while retrieving port"),
-    };
-
-    let mut path = home_dir(port);
-    path.push("validator_key.json");
+pub(crate) fn root_account(home_dir: &Path) -> InMemorySigner {
+    let path = home_dir.join("validator_key.json");
 
     InMemorySigner::from_file(&path)
 }
 
 pub(crate) async fn create_top_level_account(
+    client: &Client,
+    root_signer: &InMemorySigner,
     new_account_id: AccountId,
     new_account_pk: PublicKey,
-) -> anyhow::Result<CallExecutionResult> {
-    let root_signer = root_account();
-    crate::create_account(
-        &root_signer,
-        root_signer.account_id.clone(),
-        new_account_id,
-        new_account_pk,
-        None,
-    )
-    .await
+) -> anyhow::Result<ExecutionResult> {
+    let result = client
+        .create_account(
+            root_signer,
+            root_signer.account_id.clone(),
+            new_account_id,
+            new_account_pk,
+            None,
+        )
+        .await?;
+    if let Some(err) = AccountAlreadyExists::from_status(result.status()) {
+        return Err(err.into());
+    }
+    Ok(result)
 }
 
 pub(crate) async fn create_tla_and_deploy(
+    client: &Client,
+    root_signer: &InMemorySigner,
     new_account_id: AccountId,
     new_account_pk: PublicKey,
-    _signer: &dyn Signer,
-    code_filepath: impl AsRef<Path>,
+    code: Vec<u8>,
+    init: Option<InitCall>,
 ) -> anyhow::Result<FinalExecutionOutcomeView> {
-    let root_signer = root_account();
-    let (access_key, _, block_hash) =
-        tool::access_key(root_signer.account_id.clone(), root_signer.public_key())
-            .await
-            .map_err(|e| anyhow!(e))?;
-
-    let mut code = Vec::new();
-    File::open(code_filepath)?.read_to_end(&mut code)?;
-
     // This transaction creates the account too:
-    let signed_tx = SignedTransaction::create_contract(
-        access_key.nonce + 1,
-        root_signer.account_id.clone(),
-        new_account_id,
-        code,
-        100 * NEAR_BASE,
-        new_account_pk,
-        &root_signer,
-        block_hash,
-    );
-    dbg!(&signed_tx);
-
-    let transaction_info = tool::send_tx(signed_tx).await.map_err(|e| anyhow!(e))?;
+    let mut actions = vec![
+        Action::CreateAccount(CreateAccountAction {}),
+        Action::Transfer(TransferAction {
+            deposit: 100 * NEAR_BASE,
+        }),
+        Action::AddKey(AddKeyAction {
+            public_key: new_account_pk,
+            access_key: AccessKey::full_access(),
+        }),
+        Action::DeployContract(DeployContractAction { code }),
+    ];
+    actions.extend(init.map(InitCall::into_action));
+
+    let transaction_info = client
+        .sign_and_send(
+            root_signer,
+            root_signer.account_id.clone(),
+            new_account_id,
+            actions,
+        )
+        .await
+        .map_err(|e| anyhow!(e))?;
+    if let Some(err) = AccountAlreadyExists::from_status(&transaction_info.status) {
+        return Err(err.into());
+    }
     Ok(transaction_info)
 }
 
+/// Files making up a genesis dump, see [`crate::Worker::dump_genesis`]. Along with the
+/// genesis itself, the keys of the validator have to match the validator in the genesis.
+pub(crate) const GENESIS_FILES: &[&str] = &["genesis.json", "validator_key.json", "node_key.json"];
+
+/// Attempts at starting a sandbox on freshly picked ports. Another process can grab a
+/// picked port before the sandbox binds it, which makes the sandbox exit right away.
+const SANDBOX_START_ATTEMPTS: usize = 3;
+
+/// Picks a free RPC and network port pair. The two are distinct, as each is picked
+/// independently and could otherwise coincide.
+fn pick_port_pair() -> (u16, u16) {
+    let rpc_port = pick_unused_port().expect("no ports free");
+    loop {
+        let net_port = pick_unused_port().expect("no ports free");
+        if net_port != rpc_port {
+            return (rpc_port, net_port);
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct SandboxServer {
-    pub(self) rpc_port: u16,
+    pub(crate) rpc_port: u16,
     pub(self) net_port: u16,
-    process: Option<Child>,
+    home_dir: PathBuf,
+    /// Locked so the node can be restarted from a shared reference, see
+    /// [`SandboxServer::while_stopped`].
+    process: Mutex<Option<Child>>,
+    /// Whether the ports were picked by us, and so can be picked again if taken.
+    picked_ports: bool,
+    detached: bool,
+    /// Directory of a genesis dump the node starts from, instead of a fresh genesis.
+    genesis_dir: Option<PathBuf>,
+    /// Overrides applied to the genesis before the node starts.
+    genesis_config: Option<GenesisConfig>,
+    /// The near-sandbox binary the node was started from.
+    bin: Option<PathBuf>,
 }
 
 impl SandboxServer {
@@ -94,76 +153,307 @@ impl SandboxServer {
         Self {
             rpc_port,
             net_port,
-            process: None,
+            home_dir: unique_home_dir(rpc_port),
+            process: Mutex::new(None),
+            picked_ports: false,
+            detached: false,
+            genesis_dir: None,
+            genesis_config: None,
+            bin: None,
         }
     }
 
+    /// Directory holding the node's data and keys. Unique to this server, and removed once
+    /// the server is dropped.
+    pub fn home_dir(&self) -> &Path {
+        &self.home_dir
+    }
+
+    /// The near-sandbox binary the node was started from, once it is started.
+    pub(crate) fn bin_path(&self) -> Option<&Path> {
+        self.bin.as_deref()
+    }
+
+    /// Start the node from the genesis dump in `genesis_dir` rather than a fresh genesis.
+    pub(crate) fn with_genesis(mut self, genesis_dir: PathBuf) -> Self {
+        self.genesis_dir = Some(genesis_dir);
+        self
+    }
+
+    pub(crate) fn with_genesis_config(mut self, genesis_config: GenesisConfig) -> Self {
+        self.genesis_config = Some(genesis_config);
+        self
+    }
+
+    /// Start the sandbox node. If the ports were picked automatically and the node exits
+    /// right away, such as when one of the ports got taken by a sandbox of a concurrently
+    /// running test, the node is started again on other ports.
     pub fn start(&mut self) -> anyhow::Result<()> {
-        println!("Starting up sandbox at localhost:{}", self.rpc_port);
-        let home_dir = home_dir(self.rpc_port);
+        let mut attempt = 1;
+        loop {
+            match self.start_once() {
+                Ok(()) => return Ok(()),
+                Err(err) if self.picked_ports && attempt < SANDBOX_START_ATTEMPTS => {
+                    trace::warn!(
+                        "Sandbox at localhost:{} failed to start, retrying on other ports: {}",
+                        self.rpc_port,
+                        err
+                    );
+                    let _ = fs::remove_dir_all(&self.home_dir);
+                    let (rpc_port, net_port) = pick_port_pair();
+                    self.rpc_port = rpc_port;
+                    self.net_port = net_port;
+                    self.home_dir = unique_home_dir(rpc_port);
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
 
-        // Remove dir if it already exists:
-        let _ = fs::remove_dir_all(&home_dir);
-        near_sandbox_utils::init(&home_dir)?.wait()?;
+    fn start_once(&mut self) -> anyhow::Result<()> {
+        trace::info!(
+            "Starting up sandbox at localhost:{} with home {}",
+            self.rpc_port,
+            self.home_dir.display()
+        );
+        let bin = install::sandbox_bin()?;
+        let status = Command::new(&bin)
+            .arg("--home")
+            .arg(&self.home_dir)
+            .arg("init")
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("could not initialize sandbox home: {}", status));
+        }
+        if let Some(genesis_dir) = &self.genesis_dir {
+            for file in GENESIS_FILES {
+                fs::copy(genesis_dir.join(file), self.home_dir.join(file)).map_err(|err| {
+                    anyhow!(
+                        "could not copy {} from {}: {}",
+                        file,
+                        genesis_dir.display(),
+                        err
+                    )
+                })?;
+            }
+        }
+        if let Some(genesis_config) = &self.genesis_config {
+            genesis_config.apply(&self.home_dir)?;
+        }
+
+        let child = self.spawn(&bin)?;
+        *self.process.get_mut().unwrap() = Some(child);
+        self.bin = Some(bin);
+        Ok(())
+    }
 
-        let child = near_sandbox_utils::run(&home_dir, self.rpc_port, self.net_port)?;
-        println!("Started sandbox: pid={:?}", child.id());
-        self.process = Some(child);
+    /// Run the node out of the already initialized home directory.
+    fn spawn(&self, bin: &Path) -> anyhow::Result<Child> {
+        let mut command = Command::new(bin);
+        command
+            .arg("--home")
+            .arg(&self.home_dir)
+            .arg("run")
+            .arg("--rpc-addr")
+            .arg(format!("0.0.0.0:{}", self.rpc_port))
+            .arg("--network-addr")
+            .arg(format!("0.0.0.0:{}", self.net_port));
+        #[cfg(feature = "tracing")]
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = command.spawn()?;
+        trace::info!("Started sandbox: pid={:?}", child.id());
+        #[cfg(feature = "tracing")]
+        forward_output(&mut child, self.rpc_port);
 
         // TODO: Get rid of this sleep, and ping sandbox is alive instead:
         thread::sleep(Duration::from_secs(3));
-        Ok(())
+        if let Some(status) = child.try_wait()? {
+            return Err(anyhow!("sandbox exited while starting up: {}", status));
+        }
+        Ok(child)
+    }
+
+    /// Stop the node, run `f` on its home directory, and start the node again on the same
+    /// ports, e.g. to copy its data while nothing writes to it. The node is started again
+    /// even if `f` fails.
+    pub(crate) fn while_stopped<R>(
+        &self,
+        f: impl FnOnce(&Path) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        let bin = self
+            .bin
+            .as_deref()
+            .ok_or_else(|| anyhow!("sandbox at localhost:{} is not running", self.rpc_port))?;
+        let mut process = self.process.lock().unwrap();
+        if let Some(mut child) = process.take() {
+            child.kill()?;
+            child.wait()?;
+        }
+
+        let result = f(&self.home_dir);
+        *process = Some(self.spawn(bin)?);
+        result
+    }
+
+    /// Stop managing the sandbox process, so that it keeps running after this is dropped.
+    /// Its home directory is left in place as well.
+    pub(crate) fn detach(&mut self) -> Option<Child> {
+        self.detached = true;
+        self.process.get_mut().unwrap().take()
+    }
+}
+
+/// Forward the output of the sandbox `child` line by line as events under
+/// [`trace::SANDBOX_TARGET`], e.g. to see what the node did before a test failed with
+/// `RUST_LOG=workspaces::sandbox=debug`. The forwarding stops once the process exits.
+#[cfg(feature = "tracing")]
+fn forward_output(child: &mut Child, rpc_port: u16) {
+    use std::io::{BufRead, BufReader, Read};
+
+    fn forward(output: impl Read + Send + 'static, rpc_port: u16, stream: &'static str) {
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                match line {
+                    Ok(line) => {
+                        tracing::debug!(target: trace::SANDBOX_TARGET, rpc_port, stream, "{}", line)
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, rpc_port, "stdout");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(stderr, rpc_port, "stderr");
     }
 }
 
 impl Default for SandboxServer {
     fn default() -> Self {
-        let rpc_port = pick_unused_port().expect("no ports free");
-        let net_port = pick_unused_port().expect("no ports free");
-        Self::new(rpc_port, net_port)
+        let (rpc_port, net_port) = pick_port_pair();
+        let mut server = Self::new(rpc_port, net_port);
+        server.picked_ports = true;
+        server
     }
 }
 
 impl Drop for SandboxServer {
     fn drop(&mut self) {
-        if self.process.is_none() {
+        if self.detached {
             return;
         }
 
-        let child = self.process.as_mut().unwrap();
-
-        eprintln!(
-            "Cleaning up sandbox: port={}, pid={}",
-            self.rpc_port,
-            child.id()
-        );
+        if let Some(child) = self.process.get_mut().unwrap().as_mut() {
+            trace::info!(
+                "Cleaning up sandbox: port={}, pid={}",
+                self.rpc_port,
+                child.id()
+            );
 
-        child
-            .kill()
-            .map_err(|e| format!("Could not cleanup sandbox due to: {:?}", e))
-            .unwrap();
+            child
+                .kill()
+                .map_err(|e| format!("Could not cleanup sandbox due to: {:?}", e))
+                .unwrap();
+            let _ = child.wait();
+        }
+        let _ = fs::remove_dir_all(&self.home_dir);
     }
 }
 
+#[derive(Debug)]
+enum SandboxHandle {
+    Owned(SandboxServer),
+    Shared(SharedSandboxLease),
+    /// The shared sandbox could not be acquired, which `run` reports.
+    Unavailable(anyhow::Error),
+}
+
 pub struct SandboxRuntime {
-    server: SandboxServer,
-    _guard: context::EnterGuard,
+    server: SandboxHandle,
+    // Nothing to enter without a sandbox:
+    _guard: Option<context::EnterGuard>,
 }
 
 impl SandboxRuntime {
+    /// Use a sandbox shared with other test processes on this machine instead of spawning
+    /// one specifically for this runtime. The sandbox is started if no other process is
+    /// currently using it. The same is done for every `SandboxRuntime` if the
+    /// `NEAR_WORKSPACES_SHARED_SANDBOX` environment variable is set.
+    pub fn shared() -> anyhow::Result<Self> {
+        Self::shared_with_lease(shared::DEFAULT_LEASE_DURATION)
+    }
+
+    /// Same as [`SandboxRuntime::shared`], but with a custom lease duration. Should be
+    /// longer than this process is expected to use the sandbox for.
+    pub fn shared_with_lease(lease_duration: std::time::Duration) -> anyhow::Result<Self> {
+        let lease = SharedSandboxLease::acquire(lease_duration)?;
+        let flavor = RuntimeFlavor::Sandbox {
+            port: lease.rpc_port,
+            home_dir: lease.home_dir.clone(),
+        };
+
+        Ok(Self {
+            server: SandboxHandle::Shared(lease),
+            _guard: Some(context::enter(flavor)),
+        })
+    }
+
+    /// Home directory of the sandbox node backing this runtime. Empty if the shared sandbox
+    /// could not be acquired, see [`SandboxRuntime::run`].
+    pub fn home_dir(&self) -> &Path {
+        match &self.server {
+            SandboxHandle::Owned(server) => server.home_dir(),
+            SandboxHandle::Shared(lease) => &lease.home_dir,
+            SandboxHandle::Unavailable(_) => Path::new(""),
+        }
+    }
+
+    /// Start the sandbox node. Fails if it could not be started, or if the shared sandbox
+    /// was asked for through `NEAR_WORKSPACES_SHARED_SANDBOX` but could not be acquired.
+
     pub fn run(&mut self) -> anyhow::Result<()> {
-        self.server.start()
+        match &mut self.server {
+            SandboxHandle::Owned(server) => {
+                server.start()?;
+                // Starting might have moved the server to other ports:
+                context::update(RuntimeFlavor::Sandbox {
+                    port: server.rpc_port,
+                    home_dir: server.home_dir.clone(),
+                });
+                Ok(())
+            }
+            // Already started by whichever process first acquired a lease:
+            SandboxHandle::Shared(_) => Ok(()),
+            SandboxHandle::Unavailable(err) => {
+                Err(anyhow!("could not acquire the shared sandbox: {:?}", err))
+            }
+        }
     }
 }
 
 impl Default for SandboxRuntime {
     fn default() -> Self {
+        if std::env::var_os(shared::SHARED_SANDBOX_ENV).is_some() {
+            // Reported by `run`, since this can't fail:
+            return Self::shared().unwrap_or_else(|err| Self {
+                server: SandboxHandle::Unavailable(err),
+                _guard: None,
+            });
+        }
+
         let server = SandboxServer::default();
-        let rpc_port = server.rpc_port;
+        let flavor = RuntimeFlavor::Sandbox {
+            port: server.rpc_port,
+            home_dir: server.home_dir.clone(),
+        };
 
         Self {
-            server,
-            _guard: context::enter(RuntimeFlavor::Sandbox(rpc_port)),
+            server: SandboxHandle::Owned(server),
+            _guard: Some(context::enter(flavor)),
         }
     }
 }
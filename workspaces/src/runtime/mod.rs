@@ -1,61 +1,61 @@
 pub(crate) mod context;
+pub(crate) mod install;
 pub(crate) mod local;
 pub(crate) mod online;
+pub(crate) mod shared;
 
 pub use local::SandboxRuntime;
-pub use online::TestnetRuntime;
+pub use online::{MainnetRuntime, TestnetRuntime};
 
 use anyhow::anyhow;
+use async_trait::async_trait;
 use url::Url;
 
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use near_crypto::{PublicKey, Signer};
+use near_crypto::{InMemorySigner, PublicKey};
 use near_primitives::types::AccountId;
 use near_primitives::views::FinalExecutionOutcomeView;
 
-use crate::CallExecutionResult;
+use crate::network::{
+    AllowDevAccountCreation, AllowStateChanges, NetworkClient, NetworkInfo, TopLevelAccountCreator,
+};
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::{ExecutionResult, InitCall};
 
-const SANDBOX_CREDENTIALS_DIR: &str = ".near-credentials/sandbox/";
-const TESTNET_CREDENTIALS_DIR: &str = ".near-credentials/testnet/";
+const MAINNET_READ_ONLY_ERROR: &str =
+    "mainnet runtime is read-only: only queries such as view and view_state are allowed";
 
-// TODO: implement mainnet/testnet runtimes
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub(crate) enum RuntimeFlavor {
-    Mainnet,
+    Mainnet { archival: bool },
     Testnet,
-    Sandbox(u16),
+    Sandbox { port: u16, home_dir: PathBuf },
 }
 
 impl RuntimeFlavor {
     pub fn rpc_addr(&self) -> String {
         match self {
-            Self::Sandbox(port) => format!("http://localhost:{}", port),
+            Self::Sandbox { port, .. } => format!("http://localhost:{}", port),
             Self::Testnet => online::TestnetRuntime::RPC_URL.to_string(),
-            _ => unimplemented!(),
+            Self::Mainnet { archival: false } => online::MainnetRuntime::RPC_URL.to_string(),
+            Self::Mainnet { archival: true } => {
+                online::MainnetRuntime::ARCHIVAL_RPC_URL.to_string()
+            }
         }
     }
 
     pub(crate) fn name(&self) -> &'static str {
         match self {
-            Self::Sandbox(_) => "sandbox",
-            Self::Mainnet => "mainnet",
+            Self::Sandbox { .. } => "sandbox",
+            Self::Mainnet { .. } => "mainnet",
             Self::Testnet => "testnet",
         }
     }
 
     pub fn keystore_path(&self) -> anyhow::Result<PathBuf> {
-        let home_dir =
-            dirs::home_dir().ok_or_else(|| anyhow!("Could not get HOME_DIR".to_string()))?;
-        let mut path = PathBuf::from(&home_dir);
-        path.push(match self {
-            Self::Sandbox(_) => SANDBOX_CREDENTIALS_DIR,
-            Self::Testnet => TESTNET_CREDENTIALS_DIR,
-            _ => unimplemented!(),
-        });
-
-        Ok(path)
+        tool::credentials_dir(self.name())
     }
 
     pub fn helper_url(&self) -> Url {
@@ -65,40 +65,124 @@ impl RuntimeFlavor {
         }
     }
 
-    pub async fn create_top_level_account(
+    /// Base URL of the block explorer for this network, if there is one.
+    pub fn explorer_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Testnet => Some(online::TestnetRuntime::EXPLORER_URL),
+            Self::Mainnet { .. } => Some(online::MainnetRuntime::EXPLORER_URL),
+            Self::Sandbox { .. } => None,
+        }
+    }
+
+    /// Client for this runtime's RPC. Hooks registered within the runtime are not attached.
+    pub(crate) fn client(&self) -> Client {
+        Client::new(self.rpc_addr())
+            .with_explorer_url(self.explorer_url())
+            .read_only(matches!(self, Self::Mainnet { .. }))
+            .seeded_keys(matches!(self, Self::Sandbox { .. }))
+    }
+}
+
+/// Network of the runtime entered on the current thread, backing the free functions such
+/// as [`crate::call`] and [`crate::dev_deploy`].
+pub(crate) struct ContextNetwork {
+    flavor: RuntimeFlavor,
+    client: Client,
+}
+
+impl ContextNetwork {
+    pub(crate) fn current() -> Self {
+        Self {
+            flavor: context::current().expect(context::MISSING_RUNTIME_ERROR),
+            client: context::client(),
+        }
+    }
+}
+
+impl NetworkClient for ContextNetwork {
+    fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl NetworkInfo for ContextNetwork {
+    fn name(&self) -> &'static str {
+        self.flavor.name()
+    }
+
+    fn keystore_path(&self) -> anyhow::Result<PathBuf> {
+        self.flavor.keystore_path()
+    }
+}
+
+// Mainnet is read-only within the context too, but only checked once a transaction is sent.
+impl AllowStateChanges for ContextNetwork {}
+
+impl AllowDevAccountCreation for ContextNetwork {}
+
+#[async_trait]
+impl TopLevelAccountCreator for ContextNetwork {
+    async fn create_tla(
         &self,
         new_account_id: AccountId,
         new_account_pk: PublicKey,
-    ) -> anyhow::Result<Option<CallExecutionResult>> {
-        match self {
-            Self::Sandbox(_) => Ok(Some(
-                local::create_top_level_account(new_account_id, new_account_pk).await?,
+    ) -> anyhow::Result<Option<ExecutionResult>> {
+        match &self.flavor {
+            RuntimeFlavor::Sandbox { home_dir, .. } => Ok(Some(
+                local::create_top_level_account(
+                    &self.client,
+                    &local::root_account(home_dir),
+                    new_account_id,
+                    new_account_pk,
+                )
+                .await?,
             )),
-            Self::Testnet => {
-                online::create_top_level_account(new_account_id, new_account_pk).await?;
-                Ok(None)
+            RuntimeFlavor::Testnet => {
+                online::create_top_level_account(
+                    &self.client,
+                    &[self.flavor.helper_url()],
+                    new_account_id,
+                    new_account_pk,
+                )
+                .await
             }
-            _ => unimplemented!(),
+            RuntimeFlavor::Mainnet { .. } => Err(anyhow!(MAINNET_READ_ONLY_ERROR)),
         }
     }
 
-    pub async fn create_tla_and_deploy(
+    async fn create_tla_and_deploy(
         &self,
         new_account_id: AccountId,
         new_account_pk: PublicKey,
-        signer: &dyn Signer,
-        code_filepath: impl AsRef<Path>,
+        signer: &InMemorySigner,
+        code: Vec<u8>,
+        init: Option<InitCall>,
     ) -> anyhow::Result<FinalExecutionOutcomeView> {
-        match self {
-            Self::Sandbox(_) => {
-                local::create_tla_and_deploy(new_account_id, new_account_pk, signer, code_filepath)
-                    .await
+        match &self.flavor {
+            RuntimeFlavor::Sandbox { home_dir, .. } => {
+                local::create_tla_and_deploy(
+                    &self.client,
+                    &local::root_account(home_dir),
+                    new_account_id,
+                    new_account_pk,
+                    code,
+                    init,
+                )
+                .await
             }
-            Self::Testnet => {
-                online::create_tla_and_deploy(new_account_id, new_account_pk, signer, code_filepath)
-                    .await
+            RuntimeFlavor::Testnet => {
+                online::create_tla_and_deploy(
+                    &self.client,
+                    &[self.flavor.helper_url()],
+                    new_account_id,
+                    new_account_pk,
+                    signer,
+                    code,
+                    init,
+                )
+                .await
             }
-            _ => unimplemented!(),
+            RuntimeFlavor::Mainnet { .. } => Err(anyhow!(MAINNET_READ_ONLY_ERROR)),
         }
     }
 }
@@ -111,52 +195,72 @@ pub(crate) fn assert_within(runtimes: &[&str]) -> bool {
     )
 }
 
-/// Spawn this task within a new runtime context. Useful for when trying to
-/// run multiple runtimes (testnet, sandbox, ...) within the same thread.
+/// Spawn this task within a new runtime context, entered by `start` and exited once the task
+/// is done. Useful for when trying to run multiple runtimes (testnet, sandbox, ...) within the
+/// same thread.
 // NOTE: this could also be equivalent to tokio::spawn as well
-pub(crate) async fn scope<T>(runtime: &str, scoped_task: T) -> anyhow::Result<T::Output>
+pub(crate) async fn scope<R, T>(
+    start: impl FnOnce() -> anyhow::Result<R> + Send + 'static,
+    scoped_task: T,
+) -> anyhow::Result<T::Output>
 where
     T: core::future::Future + Send + 'static,
     T::Output: Send + 'static,
 {
-    let rt = runtime.to_string();
     let task = move || {
         // Create the relevant runtime. This is similar to how workspaces_macros
         // sets up the runtime, except we're not setting up a second runtime here.
         // Expects tokio to be used for the runtime. Might consider using
         // async_compat if we want to expose choosing the runtime to the user.
-        match &*rt {
-            "sandbox" => {
-                let mut rt = SandboxRuntime::default();
-                let _ = rt.run().unwrap();
-
-                tokio::runtime::Handle::current().block_on(scoped_task)
-            }
-            "testnet" => {
-                let mut rt = TestnetRuntime::default();
-                let _ = rt.run().unwrap();
-
-                tokio::runtime::Handle::current().block_on(scoped_task)
-            }
-            _ => unimplemented!(),
-        }
+        let _runtime = start()?;
+        Ok(tokio::runtime::Handle::current().block_on(scoped_task))
     };
 
-    tokio::task::spawn_blocking(task).await.map_err(Into::into)
+    tokio::task::spawn_blocking(task).await?
 }
 
+/// Run the task within a sandbox runtime. Prefer [`crate::sandbox`], which returns a
+/// [`Worker`](crate::Worker) without entering any runtime context.
 pub async fn with_sandbox<T>(scoped_task: T) -> anyhow::Result<T::Output>
 where
     T: core::future::Future + Send + 'static,
     T::Output: Send + 'static,
 {
-    scope("sandbox", scoped_task).await
+    let start = || {
+        let mut rt = SandboxRuntime::default();
+        rt.run()?;
+        Ok(rt)
+    };
+    scope(start, scoped_task).await
 }
 
+/// Run the task within a testnet runtime. Prefer [`crate::testnet`], which returns a
+/// [`Worker`](crate::Worker) without entering any runtime context.
 pub async fn with_testnet<T>(scoped_task: T) -> anyhow::Result<T::Output>
 where
     T: core::future::Future + Send + 'static,
     T::Output: Send + 'static,
 {
-    scope("testnet", scoped_task).await
+    let start = || {
+        let mut rt = TestnetRuntime::default();
+        rt.run()?;
+        Ok(rt)
+    };
+    scope(start, scoped_task).await
+}
+
+/// Run the task within a read-only mainnet runtime, for querying or spooning contract
+/// state from mainnet. Prefer [`crate::mainnet`], which returns a [`Worker`](crate::Worker)
+/// without entering any runtime context.
+pub async fn with_mainnet<T>(scoped_task: T) -> anyhow::Result<T::Output>
+where
+    T: core::future::Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    let start = || {
+        let mut rt = MainnetRuntime::default();
+        rt.run()?;
+        Ok(rt)
+    };
+    scope(start, scoped_task).await
 }
@@ -1,18 +1,17 @@
 use anyhow::anyhow;
-
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
+use url::Url;
 
 use near_crypto::{PublicKey, Signer};
-use near_primitives::transaction::{Action, DeployContractAction, SignedTransaction};
-use near_primitives::types::AccountId;
-use near_primitives::views::FinalExecutionOutcomeView;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{Action, DeployContractAction};
+use near_primitives::types::{AccountId, BlockHeight};
+use near_primitives::views::{AccessKeyView, FinalExecutionOutcomeView};
 
 use super::context;
 use super::RuntimeFlavor;
+use crate::rpc::client::Client;
 use crate::rpc::tool;
-use crate::runtime::context::MISSING_RUNTIME_ERROR;
+use crate::{AccountAlreadyExists, ExecutionResult, InitCall};
 
 pub struct TestnetRuntime {
     _guard: context::EnterGuard,
@@ -21,6 +20,7 @@ pub struct TestnetRuntime {
 impl TestnetRuntime {
     pub const RPC_URL: &'static str = "https://rpc.testnet.near.org";
     pub const HELPER_URL: &'static str = "https://helper.testnet.near.org";
+    pub const EXPLORER_URL: &'static str = "https://explorer.testnet.near.org";
 
     pub fn run(&mut self) -> anyhow::Result<()> {
         Ok(())
@@ -35,43 +35,130 @@ impl Default for TestnetRuntime {
     }
 }
 
+/// Read-only runtime connected to mainnet. Allows querying contracts, such as spooning
+/// their state into a sandbox, but refuses to send any transaction.
+pub struct MainnetRuntime {
+    _guard: context::EnterGuard,
+}
+
+impl MainnetRuntime {
+    pub const RPC_URL: &'static str = "https://rpc.mainnet.near.org";
+    pub const ARCHIVAL_RPC_URL: &'static str = "https://archival-rpc.mainnet.near.org";
+    pub const EXPLORER_URL: &'static str = "https://explorer.near.org";
+
+    /// Connect to a mainnet archival node instead, which is required to query state at
+    /// blocks older than a few epochs.
+    pub fn archival() -> Self {
+        Self {
+            _guard: context::enter(RuntimeFlavor::Mainnet { archival: true }),
+        }
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl Default for MainnetRuntime {
+    fn default() -> Self {
+        Self {
+            _guard: context::enter(RuntimeFlavor::Mainnet { archival: false }),
+        }
+    }
+}
+
+/// Create `new_account_id` through the first available testnet helper, and wait for the account to show up
+/// to queries, so that it can sign transactions as soon as this returns.
 pub(crate) async fn create_top_level_account(
+    client: &Client,
+    helper_urls: &[Url],
     new_account_id: AccountId,
     new_account_pk: PublicKey,
-) -> anyhow::Result<()> {
-    let rt = crate::runtime::context::current().expect(MISSING_RUNTIME_ERROR);
-    let helper_url = rt.helper_url();
-    tool::url_create_account(helper_url, new_account_id, new_account_pk).await
+) -> anyhow::Result<Option<ExecutionResult>> {
+    let outcome = tool::helpers_create_account(
+        client,
+        helper_urls,
+        new_account_id.clone(),
+        new_account_pk.clone(),
+    )
+    .await?;
+
+    // The helper answers with a successful status even if its transaction failed:
+    let result = match outcome {
+        Some(outcome) => {
+            if let Some(err) = AccountAlreadyExists::from_status(&outcome.status) {
+                return Err(err.into());
+            }
+            let result = client
+                .into_result(outcome)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            if !result.is_success() {
+                return Err(anyhow!(
+                    "helper could not create {}: {:?}",
+                    new_account_id,
+                    result.status()
+                ));
+            }
+            Some(result)
+        }
+        None => None,
+    };
+
+    wait_for_access_key(client, new_account_id, new_account_pk).await?;
+    Ok(result)
+}
+
+/// Poll the RPC, per the `query` retry policy of `client`, until `pk` is a key of
+/// `account_id`. Accounts created by the helper, or any other service, only show up once the
+/// block creating them is final.
+pub(crate) async fn wait_for_access_key(
+    client: &Client,
+    account_id: AccountId,
+    pk: PublicKey,
+) -> anyhow::Result<(AccessKeyView, BlockHeight, CryptoHash)> {
+    let policy = client.retry_policy("query");
+    let mut attempt = 1;
+    loop {
+        match client.access_key(account_id.clone(), pk.clone()).await {
+            Ok(access_key) => return Ok(access_key),
+            Err(_) if attempt < policy.attempts() => {
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                return Err(anyhow!(
+                    "{} was created, but is still not visible after {} attempts: {}",
+                    account_id,
+                    attempt,
+                    err
+                ))
+            }
+        }
+    }
 }
 
 // TODO: Vec[ExecutionOutcomeView] due to tla account doing multiple transactions?
 pub(crate) async fn create_tla_and_deploy(
+    client: &Client,
+    helper_urls: &[Url],
     new_account_id: AccountId,
     new_account_pk: PublicKey,
     signer: &dyn Signer,
-    code_filepath: impl AsRef<Path>,
+    code: Vec<u8>,
+    init: Option<InitCall>,
 ) -> anyhow::Result<FinalExecutionOutcomeView> {
-    create_top_level_account(new_account_id.clone(), new_account_pk.clone()).await?;
+    // Fails on a taken id or a failed helper transaction, rather than deploying to whatever
+    // account is there:
+    create_top_level_account(client, helper_urls, new_account_id.clone(), new_account_pk).await?;
 
-    // TODO: backoff-and-retry: two separate transactions, requires a sleep in between.
-    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+    // Deploy and initialize within the same batch, so the contract is never visible uninitialized:
+    let mut actions = vec![Action::DeployContract(DeployContractAction { code })];
+    actions.extend(init.map(InitCall::into_action));
 
-    let (access_key, _, block_hash) = tool::access_key(new_account_id.clone(), new_account_pk)
+    let transaction_info = client
+        .sign_and_send(signer, new_account_id.clone(), new_account_id, actions)
         .await
         .map_err(|e| anyhow!(e))?;
-
-    let mut code = Vec::new();
-    File::open(code_filepath)?.read_to_end(&mut code)?;
-
-    let signed_tx = SignedTransaction::from_actions(
-        access_key.nonce + 1,
-        new_account_id.clone(),
-        new_account_id.clone(),
-        signer,
-        vec![Action::DeployContract(DeployContractAction { code })],
-        block_hash,
-    );
-
-    let transaction_info = tool::send_tx(signed_tx).await.map_err(|e| anyhow!(e))?;
     Ok(transaction_info)
 }
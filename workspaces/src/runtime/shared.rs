@@ -0,0 +1,307 @@
+//! A sandbox shared between several test processes on the same machine. The first process
+//! to ask for it starts the sandbox, and every process then holds a time-bounded lease on
+//! it. Once the last lease is released (or has expired, for processes that crashed), the
+//! sandbox is shut down. This trades isolation for startup cost in large CI matrices.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+
+use super::local::SandboxServer;
+use crate::trace;
+
+/// Setting this environment variable makes every `SandboxRuntime` use the shared sandbox.
+pub(crate) const SHARED_SANDBOX_ENV: &str = "NEAR_WORKSPACES_SHARED_SANDBOX";
+
+/// How long a lease is valid for. A process holding the sandbox longer than this risks
+/// having it shut down by another process once that one releases its own lease.
+pub(crate) const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(30 * 60);
+
+const SHARED_DIR: &str = "near-workspaces-shared-sandbox";
+const SERVER_FILE: &str = "server";
+const LEASES_DIR: &str = "leases";
+const LOCK_FILE: &str = "lock";
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(60);
+
+fn shared_dir() -> PathBuf {
+    std::env::temp_dir().join(SHARED_DIR)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before unix epoch")
+        .as_secs()
+}
+
+/// Cross-process lock over the shared directory, held for as long as this is alive.
+struct DirLock(PathBuf);
+
+impl DirLock {
+    fn acquire(dir: &Path) -> io::Result<Self> {
+        let path = dir.join(LOCK_FILE);
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self(path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    // A process that died while holding the lock would block everyone else:
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .map(|modified| modified.elapsed().unwrap_or_default() > LOCK_STALE_AFTER)
+                        .unwrap_or(false);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                    } else {
+                        std::thread::sleep(Duration::from_millis(50));
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Removes expired leases, returning how many are still live.
+fn prune_expired_leases(dir: &Path) -> io::Result<usize> {
+    let now = now_secs();
+    let mut live = 0;
+    for entry in fs::read_dir(dir.join(LEASES_DIR))? {
+        let path = entry?.path();
+        let expiry = fs::read_to_string(&path)
+            .ok()
+            .and_then(|expiry| expiry.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+
+        if expiry <= now {
+            let _ = fs::remove_file(&path);
+        } else {
+            live += 1;
+        }
+    }
+
+    Ok(live)
+}
+
+/// Shared sandbox process, as recorded in the server file: `<rpc_port> <pid>` on the first
+/// line, and the node's home directory on the second.
+struct ServerInfo {
+    rpc_port: u16,
+    pid: u32,
+    home_dir: PathBuf,
+}
+
+fn read_server(dir: &Path) -> Option<ServerInfo> {
+    let server = fs::read_to_string(dir.join(SERVER_FILE)).ok()?;
+    let mut lines = server.lines();
+    let mut parts = lines.next()?.split_whitespace();
+    let rpc_port = parts.next()?.parse().ok()?;
+    let pid = parts.next()?.parse().ok()?;
+    let home_dir = PathBuf::from(lines.next()?);
+    Some(ServerInfo {
+        rpc_port,
+        pid,
+        home_dir,
+    })
+}
+
+fn kill_server(dir: &Path, server: &ServerInfo) {
+    let pid = server.pid;
+    trace::info!("Cleaning up shared sandbox: pid={}", pid);
+
+    #[cfg(unix)]
+    let _ = Command::new("kill").arg(pid.to_string()).status();
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(&["/F", "/PID", &pid.to_string()])
+        .status();
+
+    let _ = fs::remove_file(dir.join(SERVER_FILE));
+    let _ = fs::remove_dir_all(&server.home_dir);
+}
+
+/// A lease on the shared sandbox. Dropping it releases the lease, shutting down the
+/// sandbox if no other process holds one anymore.
+#[derive(Debug)]
+pub(crate) struct SharedSandboxLease {
+    pub(crate) rpc_port: u16,
+    pub(crate) home_dir: PathBuf,
+    /// Directory shared between the processes, holding the server file and the leases.
+    dir: PathBuf,
+    lease_path: PathBuf,
+}
+
+impl SharedSandboxLease {
+    pub(crate) fn acquire(lease_duration: Duration) -> anyhow::Result<Self> {
+        Self::acquire_in(shared_dir(), lease_duration)
+    }
+
+    fn acquire_in(dir: PathBuf, lease_duration: Duration) -> anyhow::Result<Self> {
+        fs::create_dir_all(dir.join(LEASES_DIR))?;
+        let _lock = DirLock::acquire(&dir)?;
+
+        let live_leases = prune_expired_leases(&dir)?;
+        let (rpc_port, home_dir) = match read_server(&dir) {
+            Some(server) if live_leases > 0 => (server.rpc_port, server.home_dir),
+            stale => {
+                if let Some(server) = stale {
+                    kill_server(&dir, &server);
+                }
+
+                let mut server = SandboxServer::default();
+                server.start()?;
+                let pid = server
+                    .detach()
+                    .expect("sandbox process should exist after starting")
+                    .id();
+                fs::write(
+                    dir.join(SERVER_FILE),
+                    format!(
+                        "{} {}\n{}",
+                        server.rpc_port,
+                        pid,
+                        server.home_dir().display()
+                    ),
+                )?;
+                (server.rpc_port, server.home_dir().to_path_buf())
+            }
+        };
+
+        let lease_path = dir.join(LEASES_DIR).join(format!(
+            "{}-{}",
+            std::process::id(),
+            rand::thread_rng().gen::<u32>()
+        ));
+        fs::write(
+            &lease_path,
+            (now_secs() + lease_duration.as_secs()).to_string(),
+        )?;
+
+        Ok(Self {
+            rpc_port,
+            home_dir,
+            dir,
+            lease_path,
+        })
+    }
+}
+
+impl Drop for SharedSandboxLease {
+    fn drop(&mut self) {
+        let dir = &self.dir;
+        let _lock = match DirLock::acquire(dir) {
+            Ok(lock) => lock,
+            Err(err) => {
+                trace::warn!("Could not release shared sandbox lease: {:?}", err);
+                return;
+            }
+        };
+
+        let _ = fs::remove_file(&self.lease_path);
+        if let Ok(0) = prune_expired_leases(dir) {
+            if let Some(server) = read_server(dir) {
+                kill_server(dir, &server);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh shared directory, laid out like `acquire` sets it up.
+    fn temp_shared_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "{}-test-{}",
+            SHARED_DIR,
+            rand::thread_rng().gen::<u64>()
+        ));
+        fs::create_dir_all(dir.join(LEASES_DIR)).unwrap();
+        dir
+    }
+
+    fn write_lease(dir: &Path, name: &str, expiry: &str) -> PathBuf {
+        let path = dir.join(LEASES_DIR).join(name);
+        fs::write(&path, expiry).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_prune_expired_leases() {
+        let dir = temp_shared_dir();
+        let live = write_lease(&dir, "live", &(now_secs() + 60).to_string());
+        let expired = write_lease(&dir, "expired", &(now_secs() - 1).to_string());
+        let garbled = write_lease(&dir, "garbled", "soon");
+
+        assert_eq!(prune_expired_leases(&dir).unwrap(), 1);
+        assert!(live.exists());
+        assert!(!expired.exists());
+        assert!(!garbled.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_server() {
+        let dir = temp_shared_dir();
+        assert!(read_server(&dir).is_none());
+
+        fs::write(dir.join(SERVER_FILE), "3030 1234\n/tmp/sandbox-home").unwrap();
+        let server = read_server(&dir).unwrap();
+        assert_eq!((server.rpc_port, server.pid), (3030, 1234));
+        assert_eq!(server.home_dir, Path::new("/tmp/sandbox-home"));
+
+        for garbled in [
+            "3030 1234",
+            "3030\n/tmp/sandbox-home",
+            "port 1234\n/tmp/sandbox-home",
+        ] {
+            fs::write(dir.join(SERVER_FILE), garbled).unwrap();
+            assert!(read_server(&dir).is_none(), "{:?}", garbled);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_last_lease_shuts_down_server() {
+        let dir = temp_shared_dir();
+        let home_dir = dir.join("home");
+        fs::create_dir_all(&home_dir).unwrap();
+        // Stands in for the sandbox process:
+        let mut server = Command::new("sleep").arg("60").spawn().unwrap();
+        fs::write(
+            dir.join(SERVER_FILE),
+            format!("3030 {}\n{}", server.id(), home_dir.display()),
+        )
+        .unwrap();
+
+        let lease = |name: &str| SharedSandboxLease {
+            rpc_port: 3030,
+            home_dir: home_dir.clone(),
+            dir: dir.clone(),
+            lease_path: write_lease(&dir, name, &(now_secs() + 60).to_string()),
+        };
+        let (first, second) = (lease("first"), lease("second"));
+
+        drop(first);
+        assert!(read_server(&dir).is_some());
+        assert!(server.try_wait().unwrap().is_none());
+
+        drop(second);
+        assert!(read_server(&dir).is_none());
+        assert!(!home_dir.exists());
+        // Killed, rather than exiting on its own a minute later:
+        assert!(!server.wait().unwrap().success());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
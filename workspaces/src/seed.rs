@@ -0,0 +1,74 @@
+//! A single seed per test run, driving the randomness of the crate so that a failing run can
+//! be reproduced: dev account ids, keys of new accounts on the sandbox and the default seed
+//! of `workspaces::fuzz`. Randomness which protects something, such as the nonces of signed
+//! messages or keys of accounts on testnet, stays truly random.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use near_crypto::{KeyType, SecretKey};
+
+use crate::trace;
+
+/// Fixes the seed of the run, to reproduce a reported failure.
+const SEED_ENV: &str = "NEAR_WORKSPACES_SEED";
+
+static INIT: Once = Once::new();
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static RNG: RefCell<Option<StdRng>> = RefCell::new(None);
+}
+
+/// Seed of this run, taken from `NEAR_WORKSPACES_SEED` if set and random otherwise. It is
+/// printed the first time it's used, along with how to re-run with it.
+///
+/// Every thread draws from its own generator, seeded from the run seed and the name of the
+/// thread. Test harnesses name threads after the test they run, so a test sees the same
+/// randomness when re-run with the same seed, no matter which other tests run in parallel.
+/// Dev account ids still embed the current time, keeping them unique across runs.
+pub fn run_seed() -> u64 {
+    INIT.call_once(|| {
+        let seed = match std::env::var(SEED_ENV) {
+            Ok(seed) => seed
+                .parse()
+                .unwrap_or_else(|err| panic!("invalid {}: {}", SEED_ENV, err)),
+            Err(_) => rand::thread_rng().gen(),
+        };
+        SEED.store(seed, Ordering::SeqCst);
+        trace::info!(
+            "workspaces run seed: {} (reproduce with {}={})",
+            seed,
+            SEED_ENV,
+            seed
+        );
+    });
+    SEED.load(Ordering::SeqCst)
+}
+
+/// Run `f` with the generator of the current thread, see [`run_seed`].
+pub(crate) fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| {
+        let mut rng = rng.borrow_mut();
+        let rng = rng.get_or_insert_with(|| {
+            let mut hasher = DefaultHasher::new();
+            run_seed().hash(&mut hasher);
+            std::thread::current().name().hash(&mut hasher);
+            StdRng::seed_from_u64(hasher.finish())
+        });
+        f(rng)
+    })
+}
+
+/// A key derived from the run seed. Only meant for networks where knowing the seed of a run
+/// must not give access to anything of value, such as the sandbox.
+pub(crate) fn secret_key() -> SecretKey {
+    let seed: [u8; 32] = with_rng(|rng| rng.gen());
+    SecretKey::from_seed(KeyType::ED25519, &hex::encode(seed))
+}
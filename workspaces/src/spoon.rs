@@ -0,0 +1,43 @@
+use std::convert::TryInto;
+
+use near_primitives::types::AccountId;
+
+/// A mainnet contract which tests commonly depend on, along with the prefixes of its state
+/// worth importing into a sandbox. Pass it to [`crate::Worker::import_known_contract`].
+///
+/// The prefixes only cover the top-level `STATE` of each contract: the full state of popular
+/// contracts is too large for an RPC node to return. Collections such as balances or pools
+/// therefore start out empty in the sandbox. Use [`crate::Worker::import_contract`] to pull
+/// in more of the state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownContract {
+    pub id: &'static str,
+    pub state_prefixes: &'static [&'static [u8]],
+}
+
+impl KnownContract {
+    pub fn account_id(&self) -> AccountId {
+        self.id
+            .to_string()
+            .try_into()
+            .expect("known contract ids are valid account ids")
+    }
+}
+
+/// Wrapped NEAR fungible token.
+pub const WRAP_NEAR: KnownContract = KnownContract {
+    id: "wrap.near",
+    state_prefixes: &[b"STATE"],
+};
+
+/// Tether USD fungible token.
+pub const USDT: KnownContract = KnownContract {
+    id: "usdt.tether-token.near",
+    state_prefixes: &[b"STATE"],
+};
+
+/// Ref Finance exchange.
+pub const REF_FINANCE: KnownContract = KnownContract {
+    id: "v2.ref-finance.near",
+    state_prefixes: &[b"STATE"],
+};
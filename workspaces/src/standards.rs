@@ -0,0 +1,358 @@
+//! Typed helpers for contracts implementing the NEAR token standards, so tests of such
+//! contracts don't have to spell out method names, argument layouts and the attached deposits
+//! the standards require. Enabled with the `standards` feature.
+//!
+//! - [`FungibleToken`] for NEP-141 fungible tokens, with NEP-148 metadata.
+//! - [`NonFungibleToken`] for NEP-171 non-fungible tokens, with NEP-177 metadata.
+//! - [`contract_source_metadata`] for the NEP-330 source metadata of any contract.
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use near_primitives::types::AccountId;
+
+use crate::account::ViewBuilder;
+use crate::rpc::client::Client;
+use crate::{Account, Contract, ExecutionResult, Gas, NearBalance, Network, Worker};
+
+/// Deposit the standards require on transfers, so that they can only be made with a full
+/// access key.
+const ONE_YOCTO: u128 = 1;
+
+/// Enough for the transfer, the receiver's `ft_on_transfer` and the resolution of the
+/// refund.
+const FT_TRANSFER_CALL_GAS: Gas = Gas::from_tgas(100);
+
+/// NEP-330 source metadata of a contract, advertising the version and source code it was
+/// built from, as returned by [`contract_source_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractSourceMetadata {
+    pub version: Option<String>,
+    /// Where to find the source code, such as a repository URL, often pinned to a commit.
+    pub link: Option<String>,
+    /// Standards the contract implements. Only part of NEP-330 since version 1.1.0.
+    #[serde(default)]
+    pub standards: Vec<StandardVersion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandardVersion {
+    /// Name of the standard, such as `nep141`.
+    pub standard: String,
+    pub version: String,
+}
+
+impl ContractSourceMetadata {
+    /// Version of `standard` the contract advertises implementing, if any.
+    pub fn standard_version(&self, standard: &str) -> Option<&str> {
+        self.standards
+            .iter()
+            .find(|s| s.standard == standard)
+            .map(|s| s.version.as_str())
+    }
+
+    /// Panics unless the contract advertises `version`.
+    pub fn assert_version(&self, version: &str) {
+        if self.version.as_deref() != Some(version) {
+            panic!(
+                "expected contract version {:?}, but the contract advertises {:?}",
+                version, self.version
+            );
+        }
+    }
+
+    /// Panics unless the source link of the contract contains `commit`, e.g. to check that the
+    /// deployed code was built from the expected commit.
+    pub fn assert_commit(&self, commit: &str) {
+        if !self.link.as_deref().unwrap_or_default().contains(commit) {
+            panic!(
+                "expected contract source at commit {}, but the contract links to {:?}",
+                commit, self.link
+            );
+        }
+    }
+}
+
+/// The NEP-330 source metadata of the contract deployed at `contract_id`, read from its
+/// `contract_source_metadata` view. Fails for contracts which don't implement NEP-330.
+pub async fn contract_source_metadata<T: Network>(
+    worker: &Worker<T>,
+    contract_id: &AccountId,
+) -> anyhow::Result<ContractSourceMetadata> {
+    ViewBuilder::new(
+        worker.client(),
+        contract_id.clone(),
+        "contract_source_metadata",
+    )
+    .fetch()
+    .await
+    .map_err(anyhow::Error::msg)?
+    .json()
+}
+
+/// NEP-148 metadata of a fungible token, as returned by `ft_metadata`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FungibleTokenMetadata {
+    /// Version of the metadata standard, such as `ft-1.0.0`.
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    /// Image of the token, usually as a data URL.
+    pub icon: Option<String>,
+    /// Link to a JSON file with more information, hashed as `reference_hash`.
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+    /// Number of decimals of the token's smallest unit, which balances are in.
+    pub decimals: u8,
+}
+
+/// A NEP-141 fungible token contract. Balances are in the token's smallest unit, as
+/// `u128`s, which the standard passes around as JSON strings.
+#[derive(Clone)]
+pub struct FungibleToken {
+    id: AccountId,
+    client: Client,
+}
+
+impl FungibleToken {
+    pub fn new(contract: &Contract) -> Self {
+        Self {
+            id: contract.id().clone(),
+            client: contract.as_account().client().clone(),
+        }
+    }
+
+    /// The token contract deployed at `id`, e.g. one imported from another network.
+    pub fn at<T: Network>(worker: &Worker<T>, id: AccountId) -> Self {
+        Self {
+            id,
+            client: worker.client().clone(),
+        }
+    }
+
+    pub fn id(&self) -> &AccountId {
+        &self.id
+    }
+
+    /// Transfer `amount` from `sender` to `receiver_id`, which has to be registered with
+    /// [`FungibleToken::storage_deposit`] beforehand.
+    pub async fn ft_transfer(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) -> anyhow::Result<ExecutionResult> {
+        sender
+            .call(&self.id, "ft_transfer")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amount": amount.to_string(),
+                "memo": memo,
+            }))
+            .deposit(NearBalance::from_yoctonear(ONE_YOCTO))
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    /// Transfer `amount` from `sender` to the contract `receiver_id`, calling its
+    /// `ft_on_transfer` with `msg`.
+    pub async fn ft_transfer_call(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+        msg: &str,
+    ) -> anyhow::Result<ExecutionResult> {
+        sender
+            .call(&self.id, "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amount": amount.to_string(),
+                "msg": msg,
+            }))
+            .deposit(NearBalance::from_yoctonear(ONE_YOCTO))
+            .gas(FT_TRANSFER_CALL_GAS)
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    pub async fn ft_balance_of(&self, account_id: &AccountId) -> anyhow::Result<u128> {
+        let balance: String = self
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()?;
+        parse_u128(&balance)
+    }
+
+    pub async fn ft_metadata(&self) -> anyhow::Result<FungibleTokenMetadata> {
+        self.view("ft_metadata")
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()
+    }
+
+    pub async fn ft_total_supply(&self) -> anyhow::Result<u128> {
+        let supply: String = self
+            .view("ft_total_supply")
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()?;
+        parse_u128(&supply)
+    }
+
+    /// Register `account_id`, or `payer` itself if `None`, with the token by paying for its
+    /// storage. The amount needed is returned by the contract's `storage_balance_bounds`;
+    /// anything above it is refunded.
+    pub async fn storage_deposit(
+        &self,
+        payer: &Account,
+        account_id: Option<&AccountId>,
+        deposit: NearBalance,
+    ) -> anyhow::Result<ExecutionResult> {
+        payer
+            .call(&self.id, "storage_deposit")
+            .args_json(json!({
+                "account_id": account_id,
+                "registration_only": true,
+            }))
+            .deposit(deposit)
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    fn view(&self, method_name: &str) -> ViewBuilder<'_> {
+        ViewBuilder::new(&self.client, self.id.clone(), method_name)
+    }
+}
+
+/// A NEP-171 non-fungible token contract.
+#[derive(Clone)]
+pub struct NonFungibleToken {
+    id: AccountId,
+    client: Client,
+}
+
+/// NEP-177 metadata of a non-fungible token contract, as returned by `nft_metadata`. The
+/// metadata of individual tokens is in [`Token::metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftContractMetadata {
+    /// Version of the metadata standard, such as `nft-1.0.0`.
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    /// Gateway to prepend to the relative URLs of the tokens' media, such as an IPFS one.
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<String>,
+}
+
+/// A token as returned by `nft_token`. Metadata is left untyped, as its fields are defined
+/// by NEP-177 and contracts often extend them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Token {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    #[serde(default)]
+    pub metadata: Option<Value>,
+}
+
+impl NonFungibleToken {
+    pub fn new(contract: &Contract) -> Self {
+        Self {
+            id: contract.id().clone(),
+            client: contract.as_account().client().clone(),
+        }
+    }
+
+    /// The token contract deployed at `id`, e.g. one imported from another network.
+    pub fn at<T: Network>(worker: &Worker<T>, id: AccountId) -> Self {
+        Self {
+            id,
+            client: worker.client().clone(),
+        }
+    }
+
+    pub fn id(&self) -> &AccountId {
+        &self.id
+    }
+
+    /// Mint `token_id` to `owner_id`, with `deposit` paying for the token's storage.
+    /// Minting isn't part of NEP-171: this follows the near-sdk example contract, which takes
+    /// `token_id`, `token_owner_id` and `token_metadata`.
+    pub async fn nft_mint(
+        &self,
+        minter: &Account,
+        token_id: &str,
+        owner_id: &AccountId,
+        metadata: Value,
+        deposit: NearBalance,
+    ) -> anyhow::Result<ExecutionResult> {
+        minter
+            .call(&self.id, "nft_mint")
+            .args_json(json!({
+                "token_id": token_id,
+                "token_owner_id": owner_id,
+                "token_metadata": metadata,
+            }))
+            .deposit(deposit)
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    /// Transfer `token_id` from `sender`, its owner or an approved account, to `receiver_id`.
+    pub async fn nft_transfer(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        token_id: &str,
+        memo: Option<&str>,
+    ) -> anyhow::Result<ExecutionResult> {
+        sender
+            .call(&self.id, "nft_transfer")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "token_id": token_id,
+                "memo": memo,
+            }))
+            .deposit(NearBalance::from_yoctonear(ONE_YOCTO))
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    pub async fn nft_metadata(&self) -> anyhow::Result<NftContractMetadata> {
+        ViewBuilder::new(&self.client, self.id.clone(), "nft_metadata")
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()
+    }
+
+    /// The token `token_id`, or `None` if it doesn't exist.
+    pub async fn nft_token(&self, token_id: &str) -> anyhow::Result<Option<Token>> {
+        ViewBuilder::new(&self.client, self.id.clone(), "nft_token")
+            .args_json(json!({ "token_id": token_id }))
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()
+    }
+}
+
+fn parse_u128(value: &str) -> anyhow::Result<u128> {
+    value
+        .parse()
+        .map_err(|err| anyhow!("invalid U128 {:?}: {}", value, err))
+}
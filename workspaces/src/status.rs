@@ -0,0 +1,216 @@
+use near_crypto::{InMemorySigner, Signer};
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{SignedTransaction, Transaction};
+use near_primitives::types::Finality;
+
+use crate::rpc::client::Client;
+use crate::trace;
+use crate::{ExecutionResult, ReplacementPolicy};
+
+/// Handle to a transaction sent without waiting for it to execute, returned by
+/// [`CallBuilder::transact_async`](crate::CallBuilder::transact_async). Several of these can
+/// be in flight at once, even from the same account, e.g. to race transactions against each
+/// other.
+#[derive(Clone)]
+pub struct TransactionStatus {
+    client: Client,
+    signer: InMemorySigner,
+    transaction: Transaction,
+    hash: CryptoHash,
+    /// Hashes of the transactions this one replaced, any of which may still be included.
+    replaced: Vec<CryptoHash>,
+}
+
+impl std::fmt::Debug for TransactionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionStatus")
+            .field("signer_id", &self.transaction.signer_id)
+            .field("hash", &self.hash)
+            .field("replaced", &self.replaced)
+            .finish()
+    }
+}
+
+impl TransactionStatus {
+    pub(crate) fn new(
+        client: Client,
+        signer: InMemorySigner,
+        transaction: Transaction,
+        hash: CryptoHash,
+    ) -> Self {
+        Self {
+            client,
+            signer,
+            transaction,
+            hash,
+            replaced: Vec::new(),
+        }
+    }
+
+    pub fn hash(&self) -> &CryptoHash {
+        &self.hash
+    }
+
+    /// The result of the transaction once it executed along with all its receipts, or
+    /// `None` while it is still executing. For a replacement, this is the result of whichever
+    /// of the replaced transactions got included. Transaction hooks only run through
+    /// [`TransactionStatus::wait`].
+    pub async fn status(&self) -> Result<Option<ExecutionResult>, String> {
+        for hash in std::iter::once(&self.hash).chain(self.replaced.iter().rev()) {
+            let signer_id = self.transaction.signer_id.clone();
+            if let Some(outcome) = self.client.tx_status(signer_id, *hash).await? {
+                return Ok(Some(self.client.into_result(outcome).await?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether the transaction is still not included more than `policy`'s number of blocks
+    /// after the block it references, e.g. because it was dropped on a congested network.
+    pub async fn is_stuck(&self, policy: &ReplacementPolicy) -> Result<bool, String> {
+        Ok(self.status().await?.is_none() && self.past_stuck_height(policy).await?)
+    }
+
+    async fn past_stuck_height(&self, policy: &ReplacementPolicy) -> Result<bool, String> {
+        let referenced = self
+            .client
+            .block_height(self.transaction.block_hash)
+            .await?;
+        let latest = self.client.latest_block_height(Finality::None).await?;
+        Ok(latest.saturating_sub(referenced) > policy.stuck_after_blocks())
+    }
+
+    /// Send the transaction again, signed over a recent block so that it isn't dropped as
+    /// expired. It keeps its nonce, so at most one of the replaced and replacement
+    /// transactions can be included and the others get rejected. NEAR has no fee market, so
+    /// a replacement can't be prioritized over other transactions.
+    pub async fn replace(&self) -> Result<TransactionStatus, String> {
+        let mut transaction = self.transaction.clone();
+        let (_, _, block_hash) = self
+            .client
+            .access_key(
+                transaction.signer_id.clone(),
+                transaction.public_key.clone(),
+            )
+            .await?;
+        transaction.block_hash = block_hash;
+        let (hash, _) = transaction.get_hash_and_size();
+        let tx = SignedTransaction::new(self.signer.sign(hash.as_ref()), transaction);
+
+        let mut replacement = self.client.send_tx_async(tx, &self.signer).await?;
+        replacement.replaced = self.replaced.clone();
+        replacement.replaced.push(self.hash);
+        Ok(replacement)
+    }
+
+    /// Wait for the transaction to be executed and final, polling as configured for `tx` in
+    /// the worker's retry policy, then run the transaction hooks like for any other
+    /// transaction.
+    pub async fn wait(self) -> Result<ExecutionResult, String> {
+        let result = self.poll().await?;
+        self.finish(result).await
+    }
+
+    /// Same as [`TransactionStatus::wait`], but replaces the transaction with
+    /// [`TransactionStatus::replace`] whenever it is stuck as defined by `policy`, up to the
+    /// number of replacements it allows. The polling of each replacement is bounded by the
+    /// retry policy, as for [`TransactionStatus::wait`].
+    pub async fn wait_with_replacement(
+        self,
+        policy: ReplacementPolicy,
+    ) -> Result<ExecutionResult, String> {
+        let mut status = self;
+        let mut replacements = 0;
+        let result = loop {
+            match status.poll_until_stuck(&policy).await? {
+                Some(result) => break result,
+                None if replacements < policy.replacements() => {
+                    trace::warn!(
+                        "transaction {} is stuck, replacing it ({}/{})",
+                        status.client.describe_tx(&status.hash),
+                        replacements + 1,
+                        policy.replacements()
+                    );
+                    status = status.replace().await?;
+                    replacements += 1;
+                }
+                None => {
+                    return Err(format!(
+                        "transaction {} still stuck after {} replacements",
+                        status.client.describe_tx(&status.hash),
+                        replacements
+                    ))
+                }
+            }
+        };
+        status.finish(result).await
+    }
+
+    async fn poll(&self) -> Result<ExecutionResult, String> {
+        let policy = self.client.retry_policy("tx");
+        let mut attempt = 1;
+        loop {
+            if let Some(result) = self.status().await? {
+                return Ok(result);
+            }
+            if attempt >= policy.attempts() {
+                return Err(format!(
+                    "transaction {} still executing after {} attempts",
+                    self.client.describe_tx(&self.hash),
+                    attempt
+                ));
+            }
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Poll like [`TransactionStatus::poll`], but return `None` as soon as the transaction
+    /// is stuck.
+    async fn poll_until_stuck(
+        &self,
+        replacement: &ReplacementPolicy,
+    ) -> Result<Option<ExecutionResult>, String> {
+        let policy = self.client.retry_policy("tx");
+        let mut attempt = 1;
+        loop {
+            if let Some(result) = self.status().await? {
+                return Ok(Some(result));
+            }
+            if self.past_stuck_height(replacement).await? {
+                return Ok(None);
+            }
+            if attempt >= policy.attempts() {
+                return Err(format!(
+                    "transaction {} still executing after {} attempts",
+                    self.client.describe_tx(&self.hash),
+                    attempt
+                ));
+            }
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Wait for the outcomes of `result` to be final and run the transaction hooks on it.
+    async fn finish(&self, result: ExecutionResult) -> Result<ExecutionResult, String> {
+        let last_height = result
+            .outcomes()
+            .map(|outcome| outcome.block_height)
+            .max()
+            .unwrap_or_default();
+        self.client.wait_until_final(last_height).await?;
+        self.client
+            .run_tx_hooks(result.clone())
+            .await
+            .map_err(|e| {
+                format!(
+                    "Hook failed after transaction {}: {:?}",
+                    self.client.describe_tx(&self.hash),
+                    e
+                )
+            })?;
+
+        Ok(result)
+    }
+}
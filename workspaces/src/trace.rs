@@ -0,0 +1,42 @@
+//! Diagnostics of the crate. With the `tracing` feature these are `tracing` events, under the
+//! target of the module emitting them, such as `workspaces::rpc::client`, so they can be
+//! filtered with `RUST_LOG`. Without it, warnings and notable steps go to stderr as before,
+//! and debug events, which may carry structured fields, are dropped.
+
+/// Target of the output of sandbox nodes, forwarded line by line.
+#[cfg(feature = "tracing")]
+pub(crate) const SANDBOX_TARGET: &str = "workspaces::sandbox";
+
+// Named apart from the builtin `#[warn]` attribute, which `warn` would be ambiguous with when
+// exported below:
+#[cfg(feature = "tracing")]
+macro_rules! warn_event {
+    ($($arg:tt)+) => { tracing::warn!($($arg)+) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! warn_event {
+    ($($arg:tt)+) => { eprintln!($($arg)+) };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! info {
+    ($($arg:tt)+) => { tracing::info!($($arg)+) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! info {
+    ($($arg:tt)+) => { eprintln!($($arg)+) };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg:tt)+) => { tracing::debug!($($arg)+) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)+) => {};
+}
+
+pub(crate) use {debug, info, warn_event as warn};
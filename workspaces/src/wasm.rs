@@ -0,0 +1,105 @@
+//! Just enough of the wasm binary format to list the methods a contract exports, so that
+//! calls to methods it doesn't have can suggest the ones it does.
+
+use anyhow::anyhow;
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const EXPORT_SECTION_ID: u8 = 7;
+const FUNCTION_EXPORT_KIND: u8 = 0;
+
+/// Names of the functions exported by the wasm module `code`, which for a contract are its
+/// methods, in the order they are exported.
+pub(crate) fn exported_functions(code: &[u8]) -> anyhow::Result<Vec<String>> {
+    if code.get(..4) != Some(WASM_MAGIC) {
+        return Err(anyhow!("not a wasm module"));
+    }
+    // Skip the magic and the version:
+    let mut reader = Reader { code, pos: 8 };
+
+    while reader.pos < code.len() {
+        let id = reader.byte()?;
+        let size = reader.leb128()? as usize;
+        if id != EXPORT_SECTION_ID {
+            reader.skip(size)?;
+            continue;
+        }
+
+        let count = reader.leb128()?;
+        let mut functions = Vec::new();
+        for _ in 0..count {
+            let len = reader.leb128()? as usize;
+            let name = String::from_utf8(reader.take(len)?.to_vec())?;
+            let kind = reader.byte()?;
+            reader.leb128()?;
+            if kind == FUNCTION_EXPORT_KIND {
+                functions.push(name);
+            }
+        }
+        return Ok(functions);
+    }
+    Ok(Vec::new())
+}
+
+/// The name among `candidates` closest to `name`, if any is close enough to likely be what
+/// was meant, such as `set_status` for `set_stauts`.
+pub(crate) fn closest_match<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    // Up to a third of the name may differ, and at least one typo is always allowed:
+    let max_distance = std::cmp::max(1, name.chars().count() / 3);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct Reader<'a> {
+    code: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .code
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| anyhow!("wasm module ends unexpectedly"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> anyhow::Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn leb128(&mut self) -> anyhow::Result<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("invalid LEB128 integer in wasm module"))
+    }
+}
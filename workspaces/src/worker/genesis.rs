@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde_json::Value;
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::{AccountId, Balance};
+
+use super::Worker;
+use crate::network::Sandbox;
+use crate::runtime::local::GENESIS_FILES;
+
+impl Worker<Sandbox> {
+    /// Dump the current state of `accounts` into `dir`, as a genesis which
+    /// [`Sandbox::from_genesis`] starts new sandboxes from, e.g. to share a fixture explored
+    /// interactively. The accounts of the original genesis, such as the root account, are
+    /// always included. Nodes can't list all their accounts, so any other account missing
+    /// from `accounts` is left out.
+    pub async fn dump_genesis(
+        &self,
+        accounts: &[AccountId],
+        dir: impl AsRef<Path>,
+    ) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        let home_dir = self.workspace.home_dir();
+        let mut genesis: Value = serde_json::from_slice(&fs::read(home_dir.join("genesis.json"))?)?;
+
+        let mut account_ids: BTreeSet<AccountId> = accounts.iter().cloned().collect();
+        let original: Vec<StateRecord> = serde_json::from_value(genesis["records"].take())?;
+        account_ids.extend(original.iter().filter_map(|record| match record {
+            StateRecord::Account { account_id, .. } => Some(account_id.clone()),
+            _ => None,
+        }));
+
+        let mut records = Vec::new();
+        let mut total_supply: Balance = 0;
+        for account_id in account_ids {
+            let view = self
+                .client()
+                .view_account(account_id.clone())
+                .await
+                .map_err(|err| anyhow!("could not dump {}: {}", account_id, err))?;
+            total_supply += view.amount + view.locked;
+            records.push(StateRecord::Account {
+                account_id: account_id.clone(),
+                account: near_primitives::account::Account::new(
+                    view.amount,
+                    view.locked,
+                    view.code_hash,
+                    view.storage_usage,
+                ),
+            });
+
+            if view.code_hash != CryptoHash::default() {
+                let code = self
+                    .client()
+                    .view_code(account_id.clone())
+                    .await
+                    .map_err(anyhow::Error::msg)?;
+                records.push(StateRecord::Contract {
+                    account_id: account_id.clone(),
+                    code,
+                });
+            }
+
+            let keys = self
+                .client()
+                .access_key_list(account_id.clone())
+                .await
+                .map_err(anyhow::Error::msg)?;
+            records.extend(keys.into_iter().map(|key| StateRecord::AccessKey {
+                account_id: account_id.clone(),
+                public_key: key.public_key,
+                access_key: key.access_key.into(),
+            }));
+
+            let state = self.client().view_state(account_id.clone(), None).await?;
+            records.extend(state.into_iter().map(|(key, value)| StateRecord::Data {
+                account_id: account_id.clone(),
+                data_key: key.into(),
+                value,
+            }));
+        }
+
+        genesis["records"] = serde_json::to_value(&records)?;
+        // The genesis is rejected unless its total supply adds up:
+        genesis["total_supply"] = Value::String(total_supply.to_string());
+
+        fs::create_dir_all(dir)?;
+        fs::write(
+            dir.join("genesis.json"),
+            serde_json::to_vec_pretty(&genesis)?,
+        )?;
+        for file in GENESIS_FILES.iter().filter(|file| **file != "genesis.json") {
+            fs::copy(home_dir.join(file), dir.join(file))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use near_crypto::{InMemorySigner, KeyType, Signer};
+use near_primitives::account::AccessKey;
+use near_primitives::hash::hash;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::AccountId;
+
+use super::Worker;
+use crate::network::{Network, Sandbox};
+use crate::{Contract, NearBalance, StatePrefix};
+
+/// Builder for copying a contract from another network into the sandbox, sent once
+/// [`ImportContractBuilder::transact`] is awaited. See [`Worker::import_contract`].
+pub struct ImportContractBuilder<'a, U> {
+    sandbox: &'a Worker<Sandbox>,
+    from: &'a Worker<U>,
+    account_id: AccountId,
+    prefixes: Vec<Vec<u8>>,
+    access_keys: bool,
+    initial_balance: Option<NearBalance>,
+}
+
+impl<'a, U> ImportContractBuilder<'a, U>
+where
+    U: Network,
+{
+    pub(super) fn new(
+        sandbox: &'a Worker<Sandbox>,
+        from: &'a Worker<U>,
+        account_id: AccountId,
+    ) -> Self {
+        Self {
+            sandbox,
+            from,
+            account_id,
+            prefixes: Vec::new(),
+            access_keys: false,
+            initial_balance: None,
+        }
+    }
+
+    /// Import the whole state of the contract. State too large to be returned at once is
+    /// fetched in pages of narrower prefixes.
+    pub fn with_data(self) -> Self {
+        self.with_data_prefixes(&[b""])
+    }
+
+    /// Import the state entries whose key starts with one of `prefixes`, such as `STATE`
+    /// for the contract's top-level struct.
+    pub fn with_data_prefixes(mut self, prefixes: &[&[u8]]) -> Self {
+        self.prefixes
+            .extend(prefixes.iter().map(|prefix| prefix.to_vec()));
+        self
+    }
+
+    /// Also copy the access keys of the contract. Their secret keys are unknown, so they
+    /// can't sign anything; this only matters to contracts inspecting their own keys.
+    pub fn with_access_keys(mut self) -> Self {
+        self.access_keys = true;
+        self
+    }
+
+    /// Balance of the imported account. Defaults to its balance on the other network.
+    pub fn initial_balance(mut self, initial_balance: NearBalance) -> Self {
+        self.initial_balance = Some(initial_balance);
+        self
+    }
+
+    /// Import the contract. The imported account gets a full access key derived from its
+    /// id, so the returned [`Contract`] can also act as the contract's owner. Everything is
+    /// fetched before anything is patched, and patched at once, so dropping the returned
+    /// future, such as on a test timeout, leaves the contract either fully imported or not
+    /// at all.
+    pub async fn transact(self) -> anyhow::Result<Contract> {
+        let account_id = self.account_id;
+        let from = self.from.client();
+        let view = from
+            .view_account(account_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let code = from
+            .view_code(account_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+
+        let signer = InMemorySigner::from_seed(
+            account_id.clone(),
+            KeyType::ED25519,
+            &account_id.to_string(),
+        );
+        let balance = self
+            .initial_balance
+            .map_or(view.amount, |balance| balance.to_yoctonear());
+        let account =
+            near_primitives::account::Account::new(balance, 0, hash(&code), view.storage_usage);
+        let mut records = vec![
+            StateRecord::Account {
+                account_id: account_id.clone(),
+                account,
+            },
+            StateRecord::Contract {
+                account_id: account_id.clone(),
+                code,
+            },
+            StateRecord::AccessKey {
+                account_id: account_id.clone(),
+                public_key: signer.public_key(),
+                access_key: AccessKey::full_access(),
+            },
+        ];
+
+        if self.access_keys {
+            let keys = from
+                .access_key_list(account_id.clone())
+                .await
+                .map_err(anyhow::Error::msg)?;
+            records.extend(keys.into_iter().map(|key| StateRecord::AccessKey {
+                account_id: account_id.clone(),
+                public_key: key.public_key,
+                access_key: key.access_key.into(),
+            }));
+        }
+
+        for prefix in self.prefixes {
+            let state = from
+                .view_state(account_id.clone(), Some(StatePrefix::raw(prefix)))
+                .await?;
+            records.extend(state.into_iter().map(|(key, value)| StateRecord::Data {
+                account_id: account_id.clone(),
+                data_key: key.into(),
+                value,
+            }));
+        }
+
+        self.sandbox
+            .client()
+            .patch_state_records(records)
+            .await
+            .map_err(anyhow::Error::msg)?;
+
+        Ok(Contract::new(
+            account_id,
+            signer,
+            self.sandbox.client().clone(),
+        ))
+    }
+}
@@ -0,0 +1,1219 @@
+// TODO: shared/global contract code deployment, next to `Worker::dev_deploy`. The pinned
+//       nearcore revision has no `DeployGlobalContract`/`UseGlobalContract` actions nor a way
+//       to query global code, so this has to wait for a nearcore bump before it can be exposed.
+
+mod genesis;
+mod import;
+mod snapshot;
+mod staking;
+mod stream;
+mod top_up;
+
+pub use self::import::ImportContractBuilder;
+pub use self::snapshot::SandboxSnapshot;
+pub use self::staking::{Delegation, RewardFeeFraction};
+use self::top_up::TopUp;
+
+use std::convert::TryInto;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use futures::FutureExt;
+use serde::de::DeserializeOwned;
+
+use near_crypto::{InMemorySigner, KeyType, PublicKey, SecretKey, Signer};
+use near_jsonrpc_client::methods::{self, sandbox_patch_state::RpcSandboxPatchStateResponse};
+use near_jsonrpc_primitives::types::chunks::ChunkReference;
+use near_primitives::borsh::BorshSerialize;
+use near_primitives::errors::{ActionError, ActionErrorKind, TxExecutionError};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::state_record::StateRecord;
+use near_primitives::transaction::{Action, DeployContractAction};
+use near_primitives::types::{AccountId, BlockId, BlockReference, Finality, FunctionArgs, ShardId};
+use near_primitives::views::{AccessKeyPermissionView, FinalExecutionStatus};
+
+use crate::fixture::FixtureCache;
+use crate::network::{
+    AllowDevAccountCreation, AllowStateChanges, Custom, Mainnet, Network, Sandbox, SandboxVersion,
+    Testnet, TopLevelAccountCreator,
+};
+use crate::rpc::client::{Client, TxHook};
+use crate::rpc::guard::AccountGuard;
+use crate::rpc::tool;
+use crate::runtime::online;
+use crate::trace;
+use crate::{
+    retry_idempotent, AccessKeyInfo, AccountInfo, Block, Chunk, ExecutionResult, InitCall,
+    NearBalance, ProtocolCosts, ReceiptCall, ReceiptNode, RetryPolicy, RpcMetrics, StatePrefix,
+    StateRecords, ViewResult, DEFAULT_CALL_FN_GAS,
+};
+use crate::{
+    run_seed, AccessKey, Account, AccountCreator, AccountCredentials, AccountPatch, Contract,
+    ContractCode, FileKeyStore, KeyStore, KnownContract,
+};
+
+const DEV_ACCOUNT_SEED: &str = "testificate";
+const DEV_SETUP_ATTEMPTS: usize = 3;
+/// Directory of the keystore mapping code hashes to dev accounts, see
+/// [`Worker::dev_deploy_cached`].
+const DEV_CACHE_DIR: &str = "dev-cache";
+const TEST_ACCOUNT_BALANCE_NEAR: u128 = 100;
+
+/// Entry point for interacting with a network. Unlike the free functions, which act on the
+/// runtime entered on the current thread, a `Worker` owns its connection to the network, so
+/// several workers can be used side by side and moved across threads. Cloning a worker is
+/// cheap and the clones share the same network.
+pub struct Worker<T> {
+    workspace: Arc<T>,
+    /// Overrides the file keystore of the network, see [`Worker::with_keystore`].
+    keystore: Option<Arc<dyn KeyStore>>,
+    /// Overrides how the network creates dev accounts, see [`Worker::with_account_creator`].
+    account_creator: Option<Arc<dyn AccountCreator>>,
+    /// Fixtures set up through [`Cached`](crate::Cached), shared with the clones.
+    fixtures: FixtureCache,
+}
+
+impl<T> Clone for Worker<T> {
+    fn clone(&self) -> Self {
+        Self {
+            workspace: self.workspace.clone(),
+            keystore: self.keystore.clone(),
+            account_creator: self.account_creator.clone(),
+            fixtures: self.fixtures.clone(),
+        }
+    }
+}
+
+/// Spin up a fresh sandbox, and return a worker for it:
+///
+/// ```ignore
+/// let worker = workspaces::sandbox().await?;
+/// let contract = worker.dev_deploy(wasm).await?;
+/// ```
+///
+/// Use [`Sandbox::builder`] to configure the sandbox, such as its genesis, before it starts.
+pub async fn sandbox() -> anyhow::Result<Worker<Sandbox>> {
+    let sandbox = tokio::task::spawn_blocking(Sandbox::new).await??;
+    Ok(Worker::new(sandbox))
+}
+
+/// Connect to testnet, and return a worker for it once its RPC answers.
+pub async fn testnet() -> anyhow::Result<Worker<Testnet>> {
+    connect(Testnet::new()?).await
+}
+
+/// Connect to mainnet, and return a worker for it once its RPC answers. The worker is
+/// read-only, see [`Mainnet`].
+pub async fn mainnet() -> anyhow::Result<Worker<Mainnet>> {
+    connect(Mainnet::new()).await
+}
+
+async fn connect<T: Network>(network: T) -> anyhow::Result<Worker<T>> {
+    let worker = Worker::new(network);
+    worker
+        .client()
+        .latest_block_height(Finality::Final)
+        .await
+        .map_err(|err| anyhow!("could not connect to {}: {}", worker.name(), err))?;
+    Ok(worker)
+}
+
+impl<T> Worker<T>
+where
+    T: Network,
+{
+    pub fn new(network: T) -> Self {
+        // Print the seed as the run starts, rather than somewhere in the middle of it:
+        run_seed();
+        Self {
+            workspace: Arc::new(network),
+            keystore: None,
+            account_creator: None,
+            fixtures: FixtureCache::default(),
+        }
+    }
+
+    /// Keep the keys of accounts created through this worker and its clones in `keystore`,
+    /// instead of JSON files in the network's keystore directory, e.g. an
+    /// [`InMemoryKeyStore`](crate::InMemoryKeyStore) so CI runs leave nothing behind in the
+    /// home directory.
+    pub fn with_keystore(mut self, keystore: impl KeyStore + 'static) -> Self {
+        self.keystore = Some(Arc::new(keystore));
+        self
+    }
+
+    /// Create the accounts of [`Worker::dev_create`] and [`Worker::dev_deploy`], for this
+    /// worker and its clones, through `creator` instead of the network's own way, e.g. an
+    /// internal faucet service, or a [`SubaccountCreator`](crate::SubaccountCreator) funding
+    /// them from a pre-funded account.
+    pub fn with_account_creator(mut self, creator: impl AccountCreator + 'static) -> Self {
+        self.account_creator = Some(Arc::new(creator));
+        self
+    }
+
+    pub(crate) fn keystore(&self) -> anyhow::Result<Arc<dyn KeyStore>> {
+        match &self.keystore {
+            Some(keystore) => Ok(keystore.clone()),
+            None => Ok(Arc::new(FileKeyStore::new(self.workspace.keystore_path()?))),
+        }
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        self.workspace.client()
+    }
+
+    pub(crate) fn fixtures(&self) -> &FixtureCache {
+        &self.fixtures
+    }
+
+    /// Name of the network this worker is connected to.
+    pub fn name(&self) -> &'static str {
+        self.workspace.name()
+    }
+
+    /// Link to the transaction `tx_hash` on the block explorer of the network, as included in
+    /// the errors of failed transactions. `None` for networks without an explorer, such as
+    /// the sandbox.
+    pub fn explorer_link(&self, tx_hash: &CryptoHash) -> Option<String> {
+        self.client()
+            .explorer_url()
+            .map(|explorer_url| tool::explorer_link(tx_hash, explorer_url))
+    }
+
+    /// Limit how many queries and transactions this worker, its clones, and the accounts and
+    /// contracts created from it keep in flight at once, with `None` meaning unlimited.
+    /// Requests above the limit wait for a slot, which keeps highly parallel tests from
+    /// overloading a local sandbox or a shared RPC endpoint. Replaces any previous limits.
+    pub fn limit_concurrency(&self, queries: Option<usize>, transactions: Option<usize>) {
+        self.client().limit_concurrency(queries, transactions);
+    }
+
+    /// Policy for retrying RPC requests of this worker and its clones, see [`RetryPolicy`].
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.client().set_retry_policy(policy);
+    }
+
+    /// Policy for the JSON-RPC `method` only, overriding the one set with
+    /// [`Worker::set_retry_policy`]: `broadcast_tx_commit` for sending transactions, `block`
+    /// for polling while waiting for transactions to become final, `tx` for polling the
+    /// status of transactions sent with `transact_async`, or `query` for polling while
+    /// waiting for accounts created by the testnet helper.
+    pub fn set_method_retry_policy(&self, method: &str, policy: RetryPolicy) {
+        self.client().set_method_retry_policy(method, policy);
+    }
+
+    /// Latency and error counters of the RPC requests sent so far through this worker and
+    /// everything created from it, such as its accounts and contracts.
+    pub fn metrics(&self) -> RpcMetrics {
+        self.client().metrics()
+    }
+
+    /// Start counting from zero again, e.g. to measure a single test of a suite sharing a
+    /// worker.
+    pub fn reset_metrics(&self) {
+        self.client().reset_metrics()
+    }
+
+    /// Function calls made by the receipts of `result`, in execution order, along with the
+    /// deposit and gas attached to each, e.g. to check that a contract forwarded exactly the
+    /// intended amount down its promise chain, rather than inferring it from final balances:
+    ///
+    /// ```ignore
+    /// let calls = worker.receipt_calls(&result).await?;
+    /// let transfer = calls.iter().find(|call| call.method_name == "ft_on_transfer").unwrap();
+    /// assert_eq!(transfer.deposit, NearBalance::from_near(1)?);
+    /// ```
+    ///
+    /// The receipts are fetched from the node, which only keeps them for a few epochs on
+    /// non-archival nodes.
+    pub async fn receipt_calls(
+        &self,
+        result: &ExecutionResult,
+    ) -> anyhow::Result<Vec<ReceiptCall>> {
+        let transaction = &result.raw().transaction;
+        let receipts = self
+            .client()
+            .tx_receipts(transaction.hash, transaction.signer_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+        Ok(result.receipt_calls(receipts))
+    }
+
+    /// Tree of receipts spawned by `result`, with the predecessor, receiver and actions of
+    /// each receipt along with its outcome, e.g. to assert on a cross-contract call failing
+    /// partway even though the transaction succeeds overall:
+    ///
+    /// ```ignore
+    /// let tree = worker.receipt_tree(&result).await?;
+    /// let failed = tree.find_failure().expect("a receipt failed");
+    /// assert_eq!(failed.method_name(), Some("ft_on_transfer"));
+    /// ```
+    ///
+    /// As with [`Worker::receipt_calls`], the receipts are fetched from the node.
+    pub async fn receipt_tree(&self, result: &ExecutionResult) -> anyhow::Result<ReceiptNode> {
+        let transaction = &result.raw().transaction;
+        let receipts = self
+            .client()
+            .tx_receipts(transaction.hash, transaction.signer_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+        result.receipt_tree(receipts)
+    }
+
+    pub async fn display_account_info(&self, account_id: AccountId) -> Result<AccountInfo, String> {
+        self.client().display_account_info(account_id).await
+    }
+
+    /// Same as [`Worker::display_account_info`] for several accounts at once, fetched in a
+    /// single round trip where the RPC endpoint supports JSON-RPC batching.
+    pub async fn display_account_infos(
+        &self,
+        account_ids: Vec<AccountId>,
+    ) -> Result<Vec<AccountInfo>, String> {
+        self.client().display_account_infos(account_ids).await
+    }
+
+    /// Query the view method `method_name` of `contract_id`. Decode what it returned with
+    /// [`ViewResult::json`] or [`ViewResult::borsh`], or use the raw bytes directly.
+    pub async fn view(
+        &self,
+        contract_id: AccountId,
+        method_name: String,
+        args: FunctionArgs,
+    ) -> Result<ViewResult, String> {
+        self.client().view_raw(contract_id, method_name, args).await
+    }
+
+    /// Calls a view method following the `from_index`/`limit` pagination convention used by
+    /// the NEAR standards (e.g. `nft_tokens`), fetching pages of `page_size` items until a
+    /// short page is returned, and aggregates all pages into a single list. `args` are passed
+    /// along with every page request. `from_index` is sent as a string, matching near-sdk's
+    /// `U128`/`U64` JSON representation.
+    pub async fn view_paginated<U: DeserializeOwned>(
+        &self,
+        contract_id: AccountId,
+        method_name: String,
+        args: serde_json::Value,
+        page_size: u64,
+    ) -> Result<Vec<U>, String> {
+        if page_size == 0 {
+            return Err("view_paginated requires a non-zero page size".to_string());
+        }
+
+        let mut args = match args {
+            serde_json::Value::Object(args) => args,
+            serde_json::Value::Null => serde_json::Map::new(),
+            _ => return Err("view_paginated expects args to be a JSON object".to_string()),
+        };
+
+        let mut items = Vec::new();
+        loop {
+            args.insert("from_index".into(), items.len().to_string().into());
+            args.insert("limit".into(), page_size.into());
+            let page_args = serde_json::to_vec(&args).map_err(|err| err.to_string())?;
+
+            let page = self
+                .view(contract_id.clone(), method_name.clone(), page_args.into())
+                .await?;
+            let page: Vec<U> = page
+                .json()
+                .map_err(|err| format!("serde_json error: {:?}", err))?;
+
+            let page_len = page.len() as u64;
+            items.extend(page);
+            if page_len < page_size {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Storage, action and receipt costs of the protocol version the network runs at, to
+    /// compute expected charges from. See also [`Block::gas_cost`].
+    pub async fn protocol_costs(&self) -> anyhow::Result<ProtocolCosts> {
+        let config = self
+            .client()
+            .protocol_config()
+            .await
+            .map_err(anyhow::Error::msg)?;
+        ProtocolCosts::from_config(&config)
+    }
+
+    /// Balance, storage usage and code hash of `account_id`. Same as
+    /// [`Worker::display_account_info`].
+    pub async fn view_account(&self, account_id: AccountId) -> Result<AccountInfo, String> {
+        self.client().display_account_info(account_id).await
+    }
+
+    /// Access keys of `account_id`, e.g. to assert on key rotation.
+    pub async fn view_access_key_list(
+        &self,
+        account_id: AccountId,
+    ) -> Result<Vec<AccessKeyInfo>, String> {
+        self.client().view_access_key_list(account_id).await
+    }
+
+    /// The block at `block_reference`, such as
+    /// `BlockReference::BlockId(BlockId::Hash(outcome.block_hash))` for the block an outcome
+    /// was included in, or `BlockReference::Finality(Finality::Final)` for the latest final
+    /// block.
+    pub async fn view_block(&self, block_reference: BlockReference) -> Result<Block, String> {
+        self.client()
+            .view_block(block_reference)
+            .await
+            .map(Block::new)
+    }
+
+    /// The chunk of shard `shard_id` in the block `block_id`.
+    pub async fn view_chunk(&self, block_id: BlockId, shard_id: ShardId) -> Result<Chunk, String> {
+        self.client()
+            .view_chunk(ChunkReference::BlockShardId { block_id, shard_id })
+            .await
+            .map(Chunk::new)
+    }
+
+    /// Wasm code deployed to `account_id`, e.g. for cloning a contract into a sandbox.
+    pub async fn view_code(&self, account_id: AccountId) -> Result<Vec<u8>, String> {
+        self.client().view_code(account_id).await
+    }
+
+    /// State of the contract, optionally restricted to keys starting with `prefix`. State
+    /// too large for a single query is fetched in pages of narrower prefixes. A key equal to
+    /// a prefix that had to be split can't be fetched this way and is left out, with a warning.
+    pub async fn view_state(
+        &self,
+        contract_id: AccountId,
+        prefix: Option<StatePrefix>,
+    ) -> anyhow::Result<StateRecords> {
+        self.client().view_state(contract_id, prefix).await
+    }
+
+    /// Run `task`, then fail if the contract code or state of `account_id` changed meanwhile,
+    /// e.g. to verify that a view-only path or a rejected transaction left the contract
+    /// untouched. Balance and keys are left to [`AccountGuard`], since an account signing
+    /// transactions pays for gas even when its state is left alone.
+    pub async fn assert_no_state_change<F>(
+        &self,
+        account_id: AccountId,
+        task: F,
+    ) -> anyhow::Result<F::Output>
+    where
+        F: Future,
+    {
+        let (code_hash, state) = self.state_snapshot(account_id.clone()).await?;
+        let output = task.await;
+        let (code_hash_after, state_after) = self.state_snapshot(account_id.clone()).await?;
+
+        if code_hash != code_hash_after {
+            return Err(anyhow!(
+                "code of {} changed unexpectedly: before={}, after={}",
+                account_id,
+                code_hash,
+                code_hash_after
+            ));
+        }
+        let changed = state.changed_keys(&state_after);
+        if !changed.is_empty() {
+            let keys: Vec<_> = changed
+                .iter()
+                .map(|key| String::from_utf8_lossy(key))
+                .collect();
+            return Err(anyhow!(
+                "state of {} changed unexpectedly under keys {:?}",
+                account_id,
+                keys
+            ));
+        }
+
+        Ok(output)
+    }
+
+    async fn state_snapshot(
+        &self,
+        account_id: AccountId,
+    ) -> anyhow::Result<(CryptoHash, StateRecords)> {
+        let account = self
+            .client()
+            .view_account(account_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let state = self.client().view_state(account_id, None).await?;
+        Ok((account.code_hash, state))
+    }
+
+    /// Snapshot the keys and balance of `account_id` on this worker's network. See
+    /// [`AccountGuard`].
+    pub async fn account_guard(&self, account_id: AccountId) -> anyhow::Result<AccountGuard> {
+        AccountGuard::snapshot_with(self.client().clone(), account_id).await
+    }
+}
+
+impl<T> Worker<T>
+where
+    T: Network + AllowStateChanges,
+{
+    /// Handle to the existing account `account_id`, signing with `secret_key`. Secret keys
+    /// can be parsed from their `ed25519:<base58>` form, as found in credential files.
+    pub fn account(&self, account_id: AccountId, secret_key: SecretKey) -> Account {
+        let signer = InMemorySigner::from_secret_key(account_id.clone(), secret_key);
+        Account::new(account_id, signer, self.client().clone())
+    }
+
+    /// Handle to an account created elsewhere, such as by another process sharing the same
+    /// sandbox.
+    pub fn account_from_credentials(&self, credentials: AccountCredentials) -> Account {
+        self.account(credentials.account_id, credentials.secret_key)
+    }
+
+    /// Same as [`Worker::account_from_credentials`], for an account with a contract deployed
+    /// to it.
+    pub fn contract_from_credentials(&self, credentials: AccountCredentials) -> Contract {
+        let signer =
+            InMemorySigner::from_secret_key(credentials.account_id.clone(), credentials.secret_key);
+        Contract::new(credentials.account_id, signer, self.client().clone())
+    }
+
+    /// Handle to `account_id` signing with its key from this worker's keystore, see
+    /// [`Worker::with_keystore`], e.g. an account whose key CI injects through an
+    /// [`EnvKeyStore`](crate::EnvKeyStore).
+    pub fn account_from_keystore(&self, account_id: AccountId) -> anyhow::Result<Account> {
+        let signer = self
+            .keystore()?
+            .load(&account_id)?
+            .ok_or_else(|| anyhow!("no key for {} in the keystore", account_id))?;
+        Ok(Account::new(account_id, signer, self.client().clone()))
+    }
+
+    /// Create an account by funding a fresh implicit account with `deposit` from `funder`,
+    /// storing its credentials in the keystore. On testnet, a configured funder does this
+    /// through `create_implicit_account`.
+    pub async fn create_implicit_account_from(
+        &self,
+        funder: &Account,
+        deposit: NearBalance,
+    ) -> anyhow::Result<Account> {
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let account_id = tool::implicit_account_id(&secret_key.public_key());
+        let signer = InMemorySigner::from_secret_key(account_id.clone(), secret_key);
+        self.keystore()?.store(&signer)?;
+
+        let outcome = funder
+            .transfer_near(&account_id, deposit)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        if !outcome.is_success() {
+            return Err(anyhow!(
+                "could not fund implicit account {}: {:?}",
+                account_id,
+                outcome.status()
+            ));
+        }
+
+        Ok(Account::new(account_id, signer, self.client().clone()))
+    }
+
+    /// Register a hook to be ran after every transaction sent through this worker or any
+    /// of its clones. See [`crate::register_tx_hook`] for the semantics of hooks.
+    pub fn register_tx_hook<F, Fut>(&self, hook: F)
+    where
+        F: Fn(ExecutionResult) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let hook: TxHook = Arc::new(move |result| hook(result).boxed());
+        self.client().register_tx_hook(hook);
+    }
+
+    fn register_top_up(&self, top_up: TopUp) {
+        let top_up = Arc::new(top_up);
+        self.register_tx_hook(move |result| {
+            let top_up = top_up.clone();
+            async move {
+                let signer_id = result.raw().transaction.signer_id.clone();
+                if let Err(err) = top_up.check(signer_id.clone()).await {
+                    trace::warn!("could not top up {}: {:?}", signer_id, err);
+                }
+                Ok(())
+            }
+        });
+    }
+
+    pub async fn transfer_near(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        receiver_id: AccountId,
+        amount: NearBalance,
+    ) -> Result<ExecutionResult, String> {
+        self.client()
+            .transfer_near(signer, signer_id, receiver_id, amount)
+            .await
+    }
+
+    pub async fn call(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        contract_id: AccountId,
+        method_name: String,
+        args: Vec<u8>,
+        deposit: Option<NearBalance>,
+    ) -> Result<ExecutionResult, String> {
+        self.client()
+            .call(
+                signer,
+                signer_id,
+                contract_id,
+                method_name,
+                args,
+                deposit,
+                DEFAULT_CALL_FN_GAS,
+            )
+            .await
+    }
+
+    pub async fn create_account(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+        deposit: Option<NearBalance>,
+    ) -> anyhow::Result<ExecutionResult> {
+        self.client()
+            .create_account(signer, signer_id, new_account_id, new_account_pk, deposit)
+            .await
+    }
+
+    /// Creates an account with exactly zero balance, relying on the creator to cover its
+    /// storage. If the protocol doesn't allow this, the error reports how much balance the
+    /// account would have needed for its storage instead of a raw execution failure.
+    pub async fn create_account_zero_balance(
+        &self,
+        signer: &dyn Signer,
+        signer_id: AccountId,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<ExecutionResult> {
+        let result = self
+            .create_account(
+                signer,
+                signer_id,
+                new_account_id,
+                new_account_pk,
+                Some(NearBalance::default()),
+            )
+            .await?;
+
+        if let FinalExecutionStatus::Failure(TxExecutionError::ActionError(ActionError {
+            kind: ActionErrorKind::LackBalanceForState { account_id, amount },
+            ..
+        })) = result.status()
+        {
+            return Err(anyhow!(
+                "protocol does not allow creating {} with zero balance: {} is required to cover its storage",
+                account_id,
+                NearBalance::from_yoctonear(*amount),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    pub async fn delete_account(
+        &self,
+        account_id: AccountId,
+        signer: &dyn Signer,
+        beneficiary_id: AccountId,
+    ) -> Result<ExecutionResult, String> {
+        self.client()
+            .delete_account(account_id, signer, beneficiary_id)
+            .await
+    }
+}
+
+impl<T> Worker<T>
+where
+    T: Network + AllowStateChanges + TopLevelAccountCreator,
+{
+    /// Creates a top level account. While in sandbox, we can grab the `ExecutionOutcomeView`,
+    /// but on testnet a helper account creator is used instead which does not provide the
+    /// `ExecutionOutcomeView`. Fails with [`AccountAlreadyExists`](crate::AccountAlreadyExists)
+    /// if `new_account_id` is taken.
+    pub async fn create_top_level_account(
+        &self,
+        new_account_id: AccountId,
+        new_account_pk: PublicKey,
+    ) -> anyhow::Result<Option<ExecutionResult>> {
+        self.workspace
+            .create_tla(new_account_id, new_account_pk)
+            .await
+    }
+}
+
+impl<T> Worker<T>
+where
+    T: Network + AllowStateChanges + TopLevelAccountCreator + AllowDevAccountCreation,
+{
+    /// Dev account setup mostly sends transactions, so it backs off like them, only with
+    /// fewer attempts since each one repeats the whole setup.
+    fn dev_setup_policy(&self) -> RetryPolicy {
+        self.client()
+            .retry_policy("broadcast_tx_commit")
+            .max_attempts(DEV_SETUP_ATTEMPTS)
+    }
+
+    fn dev_generate(
+        &self,
+        creator: Option<&dyn AccountCreator>,
+    ) -> anyhow::Result<(AccountId, InMemorySigner)> {
+        let account_id = match creator {
+            Some(creator) => creator.new_account_id(),
+            None => tool::random_account_id(),
+        };
+        let signer =
+            InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, DEV_ACCOUNT_SEED);
+        self.keystore()?.store(&signer)?;
+        Ok((account_id, signer))
+    }
+
+    /// Creator to retry with after creating a dev account failed with `err`: the network's
+    /// fallback, see [`Testnet::with_root_account`], if `err` says the helper is unavailable.
+    /// A configured account creator is never replaced.
+    fn fallback_creator(&self, err: &anyhow::Error) -> Option<Arc<dyn AccountCreator>> {
+        if self.account_creator.is_some() || !tool::is_unavailable(err) {
+            return None;
+        }
+        let creator = self.workspace.fallback_creator()?;
+        trace::warn!("{}, falling back to the root account", err);
+        Some(creator)
+    }
+
+    pub async fn dev_create(&self) -> anyhow::Result<Account> {
+        match self.dev_create_with(self.account_creator.as_deref()).await {
+            Ok(account) => Ok(account),
+            Err(err) => match self.fallback_creator(&err) {
+                Some(creator) => self.dev_create_with(Some(creator.as_ref())).await,
+                None => Err(err),
+            },
+        }
+    }
+
+    async fn dev_create_with(
+        &self,
+        creator: Option<&dyn AccountCreator>,
+    ) -> anyhow::Result<Account> {
+        let (account_id, signer) = self.dev_generate(creator)?;
+        retry_idempotent(&self.dev_setup_policy(), || async {
+            // A previous attempt might have gone through before failing on our end:
+            if self.client().view_account(account_id.clone()).await.is_ok() {
+                return Ok(None);
+            }
+            match creator {
+                Some(creator) => {
+                    create_with(self.client(), creator, &account_id, &signer).await?;
+                    Ok(None)
+                }
+                None => {
+                    self.create_top_level_account(account_id.clone(), signer.public_key())
+                        .await
+                }
+            }
+        })
+        .await?;
+        trace::debug!(account = %account_id, "created dev account");
+        Ok(Account::new(account_id, signer, self.client().clone()))
+    }
+
+    /// Deploy `code` to a new dev account. The code is either the path to a wasm file, or
+    /// the wasm bytes themselves, e.g. built from source with
+    /// [`compile_project`](crate::compile_project), see [`ContractCode`].
+    pub async fn dev_deploy(&self, code: impl ContractCode) -> anyhow::Result<Contract> {
+        self.dev_deploy_inner(code, None).await
+    }
+
+    /// Same as [`Worker::dev_deploy`], but also calls `init` in the same transaction as the
+    /// deployment.
+    pub async fn dev_deploy_and_init(
+        &self,
+        code: impl ContractCode,
+        init: InitCall,
+    ) -> anyhow::Result<Contract> {
+        self.dev_deploy_inner(code, Some(init)).await
+    }
+
+    /// Same as [`Worker::dev_deploy`], but reuses the account the same code was deployed to
+    /// by a previous run, like the `neardev` folder of near-cli. This keeps repeated runs on
+    /// testnet from burning funds on fresh dev accounts. The account is found through a
+    /// cache file in the keystore, keyed by the hash of the code, and is only reused if it
+    /// still exists with that code. Its state carries over from the previous runs. Keystores
+    /// without a directory, such as an [`InMemoryKeyStore`](crate::InMemoryKeyStore), keep
+    /// nothing across runs, so with them this is the same as [`Worker::dev_deploy`].
+    pub async fn dev_deploy_cached(&self, code: impl ContractCode) -> anyhow::Result<Contract> {
+        let code = code.into_wasm()?;
+        let code_hash = hash(&code);
+        let keystore = self.keystore()?;
+        let cache_path = match keystore.dir() {
+            Some(dir) => dir.join(DEV_CACHE_DIR).join(code_hash.to_string()),
+            None => return self.dev_deploy_inner(code, None).await,
+        };
+
+        if let Ok(account_id) = std::fs::read_to_string(&cache_path) {
+            if let Some(contract) = self.cached_dev_contract(account_id, code_hash).await? {
+                return Ok(contract);
+            }
+        }
+
+        let contract = self.dev_deploy_inner(code, None).await?;
+        if let Some(dir) = cache_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&cache_path, contract.id().to_string())?;
+        Ok(contract)
+    }
+
+    /// The contract cached as `account_id`, if it still exists with the code `code_hash`. It
+    /// might not, such as after a testnet reset.
+    async fn cached_dev_contract(
+        &self,
+        account_id: String,
+        code_hash: CryptoHash,
+    ) -> anyhow::Result<Option<Contract>> {
+        let account_id: AccountId = match account_id.trim().to_string().try_into() {
+            Ok(account_id) => account_id,
+            Err(_) => return Ok(None),
+        };
+        let signer = match self.keystore()?.load(&account_id)? {
+            Some(signer) => signer,
+            None => return Ok(None),
+        };
+
+        match self.client().view_account(account_id.clone()).await {
+            Ok(account) if account.code_hash == code_hash => Ok(Some(Contract::new(
+                account_id,
+                signer,
+                self.client().clone(),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    async fn dev_deploy_inner(
+        &self,
+        code: impl ContractCode,
+        init: Option<InitCall>,
+    ) -> anyhow::Result<Contract> {
+        let code = code.into_wasm()?;
+        let creator = self.account_creator.as_deref();
+        match self.dev_deploy_with(creator, &code, &init).await {
+            Ok(contract) => Ok(contract),
+            Err(err) => match self.fallback_creator(&err) {
+                Some(creator) => {
+                    self.dev_deploy_with(Some(creator.as_ref()), &code, &init)
+                        .await
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    async fn dev_deploy_with(
+        &self,
+        creator: Option<&dyn AccountCreator>,
+        code: &[u8],
+        init: &Option<InitCall>,
+    ) -> anyhow::Result<Contract> {
+        let (account_id, signer) = self.dev_generate(creator)?;
+        retry_idempotent(&self.dev_setup_policy(), || async {
+            // A previous attempt might have gone through before failing on our end. Code being
+            // present means both deployment and initialization happened, since they're batched.
+            if let Ok(account) = self.client().view_account(account_id.clone()).await {
+                if account.code_hash != CryptoHash::default() {
+                    return Ok(None);
+                }
+            }
+
+            let outcome = match creator {
+                Some(creator) => {
+                    create_with(self.client(), creator, &account_id, &signer).await?;
+                    let mut actions = vec![Action::DeployContract(DeployContractAction {
+                        code: code.to_vec(),
+                    })];
+                    actions.extend(init.clone().map(InitCall::into_action));
+                    self.client()
+                        .sign_and_send(&signer, account_id.clone(), account_id.clone(), actions)
+                        .await
+                        .map_err(anyhow::Error::msg)?
+                }
+                None => {
+                    self.workspace
+                        .create_tla_and_deploy(
+                            account_id.clone(),
+                            signer.public_key(),
+                            &signer,
+                            code.to_vec(),
+                            init.clone(),
+                        )
+                        .await?
+                }
+            };
+            Ok(Some(outcome))
+        })
+        .await?;
+        trace::debug!(contract = %account_id, "deployed dev contract");
+        Ok(Contract::new(account_id, signer, self.client().clone()))
+    }
+}
+
+/// Create `account_id` through `creator`, and wait for it to show up to queries.
+async fn create_with(
+    client: &Client,
+    creator: &dyn AccountCreator,
+    account_id: &AccountId,
+    signer: &InMemorySigner,
+) -> anyhow::Result<()> {
+    let public_key = signer.public_key();
+    creator.create_account(account_id, &public_key).await?;
+    online::wait_for_access_key(client, account_id.clone(), public_key).await?;
+    Ok(())
+}
+
+impl Worker<Custom> {
+    /// Connect to a network which is not built in, such as a self-hosted localnet. See
+    /// [`Custom`] for finer configuration, or [`Custom::from_env`] to configure it without
+    /// code changes.
+    pub fn custom(
+        rpc_url: impl Into<String>,
+        network_id: impl Into<String>,
+        root_account: Option<InMemorySigner>,
+        keystore: impl Into<PathBuf>,
+    ) -> Self {
+        let mut network = Custom::new(rpc_url)
+            .with_network_id(network_id)
+            .with_keystore(keystore);
+        if let Some(signer) = root_account {
+            network = network.with_root_account(signer);
+        }
+        Self::new(network)
+    }
+}
+
+impl Worker<Testnet> {
+    /// Create an account by funding a fresh implicit account with `deposit` from the
+    /// configured funder, see [`Testnet::with_funder`]. Unlike [`Worker::dev_create`], this
+    /// does not go through the testnet helper. Its credentials are stored in the keystore
+    /// like those of dev accounts.
+    pub async fn create_implicit_account(&self, deposit: NearBalance) -> anyhow::Result<Account> {
+        let funder = self.workspace.funder().ok_or_else(|| {
+            anyhow!("no funder configured for implicit accounts, see `Testnet::with_funder`")
+        })?;
+        let funder = Account::new(
+            funder.account_id.clone(),
+            funder.clone(),
+            self.client().clone(),
+        );
+        self.create_implicit_account_from(&funder, deposit).await
+    }
+
+    /// Keep long-running suites from failing halfway with `NotEnoughBalance`: after every
+    /// transaction sent through this worker, its signer is topped up by `amount` if its
+    /// balance fell below `threshold`. Funds come from the funder if one is configured, see
+    /// [`Testnet::with_funder`], and from the testnet helper otherwise, which also refills
+    /// the funder. A failed top-up doesn't fail the transaction, it is logged as a warning
+    /// instead: a `tracing` event with the `tracing` feature, and on stderr otherwise.
+    pub fn auto_top_up(&self, threshold: NearBalance, amount: NearBalance) {
+        self.register_top_up(TopUp::new(
+            self.client().clone(),
+            self.workspace.helper_urls().to_vec(),
+            self.workspace.funder().cloned(),
+            threshold,
+            amount,
+        ));
+    }
+}
+
+impl Worker<Sandbox> {
+    /// Version of the running sandbox node and the binary it was started from, e.g. to
+    /// record in test logs which node produced a result, or to skip cases depending on a
+    /// newer node with [`SandboxVersion::at_least`].
+    pub async fn sandbox_version(&self) -> anyhow::Result<SandboxVersion> {
+        let status = self
+            .client()
+            .rpc("status", methods::status::RpcStatusRequest)
+            .await
+            .map_err(|err| anyhow!("Failed to query node status: {:?}", err))?;
+        Ok(SandboxVersion {
+            version: status.version.version,
+            build: status.version.build,
+            protocol_version: status.protocol_version,
+            latest_protocol_version: status.latest_protocol_version,
+            binary: self.workspace.bin_path().map(Path::to_path_buf),
+        })
+    }
+
+    /// The sandbox's root account, which holds most of its supply and creates the top
+    /// level accounts.
+    pub fn root_account(&self) -> Account {
+        let signer = self.workspace.root_signer();
+        Account::new(signer.account_id.clone(), signer, self.client().clone())
+    }
+
+    /// Testnet's `auto_top_up` on the sandbox, funded from the root account, so that
+    /// suites relying on it run unchanged against either.
+    pub fn auto_top_up(&self, threshold: NearBalance, amount: NearBalance) {
+        self.register_top_up(TopUp::new(
+            self.client().clone(),
+            Vec::new(),
+            Some(self.workspace.root_signer()),
+            threshold,
+            amount,
+        ));
+    }
+
+    /// `alice.<root>`, see [`Worker::test_account`].
+    pub async fn alice(&self) -> anyhow::Result<Account> {
+        self.test_account("alice").await
+    }
+
+    /// `bob.<root>`, see [`Worker::test_account`].
+    pub async fn bob(&self) -> anyhow::Result<Account> {
+        self.test_account("bob").await
+    }
+
+    /// `carol.<root>`, see [`Worker::test_account`].
+    pub async fn carol(&self) -> anyhow::Result<Account> {
+        self.test_account("carol").await
+    }
+
+    /// Subaccount `<name>.<root>` of the sandbox root, funded with 100 NEAR when it is first
+    /// asked for. Its key is derived from its name, so asking for it again returns a handle
+    /// to the same account. Meant for human actors in tests, which read better as `alice`
+    /// than as a `dev-` account id.
+    pub async fn test_account(&self, name: &str) -> anyhow::Result<Account> {
+        let root = self.root_account();
+        let account_id: AccountId = format!("{}.{}", name, root.id())
+            .try_into()
+            .map_err(|err| anyhow!("invalid test account name {:?}: {:?}", name, err))?;
+        let signer = InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, name);
+
+        if self
+            .client()
+            .view_account(account_id.clone())
+            .await
+            .is_err()
+        {
+            let outcome = self
+                .create_account(
+                    root.signer(),
+                    root.id().clone(),
+                    account_id.clone(),
+                    signer.public_key(),
+                    Some(NearBalance::from_near(TEST_ACCOUNT_BALANCE_NEAR)?),
+                )
+                .await?;
+            if !matches!(outcome.status(), FinalExecutionStatus::SuccessValue(_)) {
+                return Err(anyhow!(
+                    "could not create test account {}: {:?}",
+                    account_id,
+                    outcome.status()
+                ));
+            }
+        }
+
+        Ok(Account::new(account_id, signer, self.client().clone()))
+    }
+
+    /// Advance the sandbox by `delta_height` blocks, moving block height and timestamp
+    /// forward without waiting in real time. Useful to test time-locked or epoch-dependent
+    /// contract logic. Sandboxes too old to support this fail with a `FeatureNotSupported`
+    /// error naming the version needed, as does patching state on them.
+    ///
+    /// Like patches, a fast-forward runs to completion even if the future awaiting it is
+    /// dropped, such as by a test timeout, and later patches and fast-forwards wait for it,
+    /// so the sandbox is never left in between.
+    pub async fn fast_forward(&self, delta_height: u64) -> Result<(), String> {
+        self.client().sandbox_fast_forward(delta_height).await
+    }
+
+    pub async fn patch_state<U>(
+        &self,
+        account_id: AccountId,
+        key: String,
+        value: &U,
+    ) -> Result<RpcSandboxPatchStateResponse, String>
+    where
+        U: BorshSerialize,
+    {
+        let value = U::try_to_vec(value).unwrap();
+        self.patch_state_bytes(account_id, key, value).await
+    }
+
+    /// Overwrite fields of `account_id`, creating the account if it does not exist. Along
+    /// with [`Worker::patch_access_key`], [`Worker::patch_code`] and [`Worker::patch_state`],
+    /// this allows fully cloning an account from another network into the sandbox.
+    pub async fn patch_account(
+        &self,
+        account_id: AccountId,
+        patch: AccountPatch,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        let record = self.account_record(account_id, patch).await?;
+        self.client().patch_state_records(vec![record]).await
+    }
+
+    /// Add `access_key` for `public_key` to `account_id`, or replace the existing one.
+    pub async fn patch_access_key(
+        &self,
+        account_id: AccountId,
+        public_key: PublicKey,
+        access_key: AccessKey,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        let record = StateRecord::AccessKey {
+            account_id,
+            public_key,
+            access_key,
+        };
+        self.client().patch_state_records(vec![record]).await
+    }
+
+    /// Signer for `account_id`, such as a contract imported from mainnet, to act as it in the
+    /// sandbox. Its key from the keystore is used if there is one, and a new key is generated
+    /// otherwise. Unless that key already is a full access key of the account, it is patched
+    /// onto the account as one, and stored in the keystore, so later runs against the same
+    /// sandbox find it again:
+    ///
+    /// ```ignore
+    /// worker.import_contract(&usdt_id, &mainnet).transact().await?;
+    /// let signer = worker.signer_for(&usdt_id).await?;
+    /// ```
+    pub async fn signer_for(&self, account_id: &AccountId) -> anyhow::Result<InMemorySigner> {
+        let signer = match self.keystore()?.load(account_id)? {
+            Some(signer) => signer,
+            None => InMemorySigner::from_secret_key(
+                account_id.clone(),
+                self.client().random_secret_key(),
+            ),
+        };
+        self.take_over(signer).await
+    }
+
+    /// Same as [`Worker::signer_for`], but with `secret_key` as the key of the account, e.g.
+    /// to keep the key the same across sandboxes.
+    pub async fn signer_for_key(
+        &self,
+        account_id: &AccountId,
+        secret_key: SecretKey,
+    ) -> anyhow::Result<InMemorySigner> {
+        self.take_over(InMemorySigner::from_secret_key(
+            account_id.clone(),
+            secret_key,
+        ))
+        .await
+    }
+
+    /// Make the key of `signer` a full access key of its account, if it isn't already, and
+    /// store it in the keystore.
+    async fn take_over(&self, signer: InMemorySigner) -> anyhow::Result<InMemorySigner> {
+        let account_id = signer.account_id.clone();
+        self.client()
+            .view_account(account_id.clone())
+            .await
+            .map_err(|err| anyhow!("cannot sign for {}: {}", account_id, err))?;
+
+        let has_key = matches!(
+            self.client().access_key(account_id.clone(), signer.public_key()).await,
+            Ok((key, _, _)) if key.permission == AccessKeyPermissionView::FullAccess
+        );
+        if !has_key {
+            self.patch_access_key(account_id, signer.public_key(), AccessKey::full_access())
+                .await
+                .map_err(anyhow::Error::msg)?;
+        }
+        self.keystore()?.store(&signer)?;
+        Ok(signer)
+    }
+
+    /// Replace the contract code of `account_id`, updating its code hash along with it.
+    pub async fn patch_code(
+        &self,
+        account_id: AccountId,
+        code: Vec<u8>,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        let patch = AccountPatch::default().code_hash(hash(&code));
+        let account = self.account_record(account_id.clone(), patch).await?;
+        let records = vec![StateRecord::Contract { account_id, code }, account];
+        self.client().patch_state_records(records).await
+    }
+
+    /// Start building the import of the contract at `account_id` on another network into
+    /// the sandbox under the same id. Only its code and balance are imported unless asked
+    /// for more, see [`ImportContractBuilder`].
+    pub fn import_contract<'a, U>(
+        &'a self,
+        account_id: &AccountId,
+        from: &'a Worker<U>,
+    ) -> ImportContractBuilder<'a, U>
+    where
+        U: Network,
+    {
+        ImportContractBuilder::new(self, from, account_id.clone())
+    }
+
+    /// Import one of the popular mainnet contracts, such as [`crate::WRAP_NEAR`], with its
+    /// recommended state filter. See [`Worker::import_contract`].
+    pub async fn import_known_contract<U>(
+        &self,
+        from: &Worker<U>,
+        contract: KnownContract,
+    ) -> anyhow::Result<Contract>
+    where
+        U: Network,
+    {
+        self.import_contract(&contract.account_id(), from)
+            .with_data_prefixes(contract.state_prefixes)
+            .transact()
+            .await
+    }
+
+    /// Record of `account_id` with `patch` applied. Fields left out of the patch keep their
+    /// current value, or are zero for an account which doesn't exist yet.
+    async fn account_record(
+        &self,
+        account_id: AccountId,
+        patch: AccountPatch,
+    ) -> Result<StateRecord, String> {
+        let (amount, locked, code_hash, storage_usage) = match self
+            .client()
+            .view_account_if_exists(account_id.clone())
+            .await?
+        {
+            Some(view) => (view.amount, view.locked, view.code_hash, view.storage_usage),
+            None => Default::default(),
+        };
+
+        let account = near_primitives::account::Account::new(
+            patch.balance.map_or(amount, |b| b.to_yoctonear()),
+            patch.locked.map_or(locked, |b| b.to_yoctonear()),
+            patch.code_hash.unwrap_or(code_hash),
+            patch.storage_usage.unwrap_or(storage_usage),
+        );
+        Ok(StateRecord::Account {
+            account_id,
+            account,
+        })
+    }
+
+    /// Same as [`Worker::patch_state`], but takes an already serialized value, such as the
+    /// raw bytes returned from [`Worker::view_state`]. The key can be binary as well.
+    pub async fn patch_state_bytes(
+        &self,
+        account_id: AccountId,
+        key: impl AsRef<[u8]>,
+        value: Vec<u8>,
+    ) -> Result<RpcSandboxPatchStateResponse, String> {
+        self.client()
+            .patch_state_bytes(account_id, key, value)
+            .await
+    }
+}
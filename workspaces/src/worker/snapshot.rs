@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use rand::Rng;
+
+use super::Worker;
+use crate::network::Sandbox;
+
+/// Directory of the node's chain data within its home directory. The rest of the home
+/// directory, such as the genesis and the keys, doesn't change while the node runs.
+const DATA_DIR: &str = "data";
+
+/// Chain state of a sandbox saved by [`Worker::snapshot`], to go back to with
+/// [`Worker::restore`]. The saved data is removed once this is dropped.
+#[derive(Debug)]
+pub struct SandboxSnapshot {
+    dir: PathBuf,
+    /// Home directory of the sandbox the snapshot was taken from: the data of a sandbox is
+    /// only valid along with the validator keys of its own home directory.
+    home_dir: PathBuf,
+}
+
+impl Drop for SandboxSnapshot {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl Worker<Sandbox> {
+    /// Save the entire chain state of the sandbox, so that expensive setup, such as deploying
+    /// many contracts or minting thousands of tokens, runs once and test cases start from it
+    /// again with [`Worker::restore`]:
+    ///
+    /// ```ignore
+    /// let snapshot = worker.snapshot().await?;
+    /// for case in cases {
+    ///     run_case(&worker, case).await?;
+    ///     worker.restore(&snapshot).await?;
+    /// }
+    /// ```
+    ///
+    /// The node is stopped while its data is copied, and restarted on the same ports. This
+    /// runs to completion even if the future awaiting it is dropped, so the node is never
+    /// left stopped.
+    pub async fn snapshot(&self) -> anyhow::Result<SandboxSnapshot> {
+        let sandbox = self.workspace.clone();
+        self.client()
+            .run_to_completion(tokio::task::spawn_blocking(move || {
+                sandbox.while_stopped(|home_dir| {
+                    let dir = unique_snapshot_dir();
+                    copy_dir(&home_dir.join(DATA_DIR), &dir)?;
+                    Ok(SandboxSnapshot {
+                        dir,
+                        home_dir: home_dir.to_path_buf(),
+                    })
+                })
+            }))
+            .await?
+    }
+
+    /// Bring the sandbox back to the state saved in `snapshot`, which can be restored any
+    /// number of times. Everything that happened since, such as accounts created, is gone.
+    /// Fails if the snapshot was taken from another sandbox. Like [`Worker::snapshot`], this
+    /// runs to completion even if the future awaiting it is dropped.
+    pub async fn restore(&self, snapshot: &SandboxSnapshot) -> anyhow::Result<()> {
+        if snapshot.home_dir != self.workspace.home_dir() {
+            return Err(anyhow!(
+                "snapshot was taken from the sandbox at {}, not this one",
+                snapshot.home_dir.display()
+            ));
+        }
+
+        let sandbox = self.workspace.clone();
+        let snapshot_dir = snapshot.dir.clone();
+        let client = self.client().clone();
+        self.client()
+            .run_to_completion(async move {
+                tokio::task::spawn_blocking(move || {
+                    sandbox.while_stopped(|home_dir| {
+                        let data_dir = home_dir.join(DATA_DIR);
+                        fs::remove_dir_all(&data_dir)?;
+                        copy_dir(&snapshot_dir, &data_dir)
+                    })
+                })
+                .await??;
+
+                // Transactions sent since the snapshot are gone, along with their nonces:
+                client.forget_nonces();
+                Ok::<_, anyhow::Error>(())
+            })
+            .await
+    }
+}
+
+fn unique_snapshot_dir() -> PathBuf {
+    let mut rng = rand::thread_rng();
+    loop {
+        let path = std::env::temp_dir().join(format!(
+            "sandbox-snapshot-{}-{:x}",
+            std::process::id(),
+            rng.gen::<u64>()
+        ));
+        if !path.exists() {
+            return path;
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use futures::future::try_join_all;
+use serde::Deserialize;
+use serde_json::json;
+
+use near_primitives::types::AccountId;
+
+use super::Worker;
+use crate::network::Network;
+use crate::NearBalance;
+
+/// The stake of an account in a staking pool, as reported by the pool's `get_account` view
+/// method, which all pools deployed from the reference staking pool contract have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delegation {
+    /// Stake earning rewards, including the rewards already earned: pools restake them every
+    /// epoch.
+    pub staked: NearBalance,
+    /// Balance deposited or unstaked, but not staked.
+    pub unstaked: NearBalance,
+    /// Whether the unstaked balance can be withdrawn, i.e. enough epochs passed since it was
+    /// unstaked.
+    pub can_withdraw: bool,
+}
+
+impl Delegation {
+    pub fn total(&self) -> NearBalance {
+        NearBalance::from_yoctonear(self.staked.to_yoctonear() + self.unstaked.to_yoctonear())
+    }
+
+    /// Rewards earned since `earlier`, a delegation of the same account in the same pool.
+    /// Only correct if the account didn't deposit, stake or withdraw in between.
+    pub fn rewards_since(&self, earlier: &Delegation) -> NearBalance {
+        NearBalance::from_yoctonear(
+            self.total()
+                .to_yoctonear()
+                .saturating_sub(earlier.total().to_yoctonear()),
+        )
+    }
+}
+
+/// Share of the rewards a staking pool keeps as its fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RewardFeeFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+#[derive(Deserialize)]
+struct PoolAccount {
+    staked_balance: String,
+    unstaked_balance: String,
+    can_withdraw: bool,
+}
+
+impl<T> Worker<T>
+where
+    T: Network,
+{
+    /// Stake of `account_id` in the staking pool `pool_id`. Accounts which never delegated to
+    /// the pool have a zero stake.
+    pub async fn view_delegation(
+        &self,
+        account_id: &AccountId,
+        pool_id: &AccountId,
+    ) -> anyhow::Result<Delegation> {
+        let args = json!({ "account_id": account_id }).to_string();
+        let account = self
+            .view(
+                pool_id.clone(),
+                "get_account".to_string(),
+                args.into_bytes().into(),
+            )
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let account: PoolAccount = account.json()?;
+
+        Ok(Delegation {
+            staked: NearBalance::from_yoctonear(account.staked_balance.parse()?),
+            unstaked: NearBalance::from_yoctonear(account.unstaked_balance.parse()?),
+            can_withdraw: account.can_withdraw,
+        })
+    }
+
+    /// Stakes of `account_id` in each of `pool_ids`, in the same order, e.g. to check what a
+    /// staking dashboard shows for an account spread over several validators. The pools are
+    /// queried concurrently.
+    pub async fn view_delegations(
+        &self,
+        account_id: &AccountId,
+        pool_ids: &[AccountId],
+    ) -> anyhow::Result<Vec<(AccountId, Delegation)>> {
+        try_join_all(pool_ids.iter().map(|pool_id| async move {
+            let delegation = self.view_delegation(account_id, pool_id).await?;
+            Ok::<_, anyhow::Error>((pool_id.clone(), delegation))
+        }))
+        .await
+    }
+
+    pub async fn view_reward_fee_fraction(
+        &self,
+        pool_id: &AccountId,
+    ) -> anyhow::Result<RewardFeeFraction> {
+        let fraction = self
+            .view(
+                pool_id.clone(),
+                "get_reward_fee_fraction".to_string(),
+                Vec::new().into(),
+            )
+            .await
+            .map_err(anyhow::Error::msg)?;
+        fraction.json()
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use anyhow::anyhow;
+use futures::stream::{self, BoxStream, StreamExt};
+
+use near_jsonrpc_primitives::types::chunks::ChunkReference;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight, BlockId, BlockReference, Finality};
+
+use super::Worker;
+use crate::network::Network;
+use crate::rpc::client::Client;
+use crate::ExecutionOutcome;
+
+impl<T> Worker<T>
+where
+    T: Network,
+{
+    /// Outcomes executed by `account_id`, with their logs, as the network reaches them.
+    /// Only transactions signed by or sent to `account_id` and included in blocks that
+    /// become final after this returns are followed, so receipts reaching it through calls
+    /// of other contracts are not. Outcomes come once their whole transaction has executed,
+    /// in the order of the blocks they executed in among those of transactions finishing at
+    /// the same time. A transaction finishing later can still yield outcomes of earlier
+    /// blocks. The stream doesn't end by itself, but it does after yielding an error, so
+    /// bound it with `take` or a timeout:
+    ///
+    /// ```ignore
+    /// let mut outcomes = worker.stream_outcomes(contract.id().clone()).await?;
+    /// contract.call("nft_mint").args_json(args).transact().await?;
+    /// let outcome = outcomes.next().await.unwrap()?;
+    /// assert!(outcome.logs[0].starts_with("EVENT_JSON:"));
+    /// ```
+    pub async fn stream_outcomes(
+        &self,
+        account_id: AccountId,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<ExecutionOutcome>>> {
+        let poller = OutcomePoller::new(self.client().clone(), account_id).await?;
+        Ok(stream::unfold(Some(poller), |poller| async move {
+            let mut poller = poller?;
+            match poller.next().await {
+                Ok(outcome) => Some((Ok(outcome), Some(poller))),
+                Err(err) => Some((Err(err), None)),
+            }
+        })
+        .boxed())
+    }
+}
+
+/// Follows the final blocks of the network for the transactions they include, and the
+/// outcomes of those transactions executed by one account.
+struct OutcomePoller {
+    client: Client,
+    account_id: AccountId,
+    next_height: BlockHeight,
+    /// Transactions of the account seen in a block, with their signer, which haven't
+    /// finished executing.
+    pending: Vec<(CryptoHash, AccountId)>,
+    ready: VecDeque<ExecutionOutcome>,
+}
+
+impl OutcomePoller {
+    async fn new(client: Client, account_id: AccountId) -> anyhow::Result<Self> {
+        let final_height = client
+            .latest_block_height(Finality::Final)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        Ok(Self {
+            client,
+            account_id,
+            next_height: final_height + 1,
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+        })
+    }
+
+    async fn next(&mut self) -> anyhow::Result<ExecutionOutcome> {
+        loop {
+            if let Some(outcome) = self.ready.pop_front() {
+                return Ok(outcome);
+            }
+            self.poll().await?;
+        }
+    }
+
+    /// Pick up the transactions of the blocks which became final since the last poll, then
+    /// the outcomes of the transactions done executing. Waits a block's time if that
+    /// turned up nothing, so that following an idle network doesn't flood the RPC.
+    async fn poll(&mut self) -> anyhow::Result<()> {
+        let final_height = self
+            .client
+            .latest_block_height(Finality::Final)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        while self.next_height <= final_height {
+            self.scan_block(self.next_height).await?;
+            self.next_height += 1;
+        }
+
+        let mut pending = Vec::new();
+        let mut outcomes = Vec::new();
+        for (hash, signer_id) in std::mem::take(&mut self.pending) {
+            let outcome = self
+                .client
+                .tx_status(signer_id.clone(), hash)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            let outcome = match outcome {
+                Some(outcome) => outcome,
+                None => {
+                    pending.push((hash, signer_id));
+                    continue;
+                }
+            };
+            let result = self
+                .client
+                .into_result(outcome)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            outcomes.extend(result.outcomes_for(&self.account_id).into_iter().cloned());
+        }
+        self.pending = pending;
+        // Stable, so receipts of the same block keep their order:
+        outcomes.sort_by_key(|o: &ExecutionOutcome| o.block_height);
+        self.ready.extend(outcomes);
+
+        if self.ready.is_empty() {
+            tokio::time::sleep(self.client.retry_policy("block").delay(1)).await;
+        }
+        Ok(())
+    }
+
+    /// Queue the transactions of the account included in the block at `height`. The others
+    /// are skipped without querying their status, which would flood the RPC of a busy
+    /// network.
+    async fn scan_block(&mut self, height: BlockHeight) -> anyhow::Result<()> {
+        let block = match self
+            .client
+            .view_block(BlockReference::BlockId(BlockId::Height(height)))
+            .await
+        {
+            Ok(block) => block,
+            // Heights can be skipped, when a block producer misses its turn:
+            Err(err) if err.contains("UnknownBlock") => return Ok(()),
+            Err(err) => return Err(anyhow!(err)),
+        };
+
+        // A shard without a new chunk repeats its previous one, whose transactions were
+        // already seen:
+        for header in block.chunks.iter().filter(|c| c.height_included == height) {
+            let chunk = self
+                .client
+                .view_chunk(ChunkReference::ChunkHash {
+                    chunk_id: header.chunk_hash,
+                })
+                .await
+                .map_err(anyhow::Error::msg)?;
+            let account_id = &self.account_id;
+            self.pending.extend(
+                chunk
+                    .transactions
+                    .into_iter()
+                    .filter(|tx| &tx.signer_id == account_id || &tx.receiver_id == account_id)
+                    .map(|tx| (tx.hash, tx.signer_id)),
+            );
+        }
+        Ok(())
+    }
+}
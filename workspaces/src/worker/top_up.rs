@@ -0,0 +1,127 @@
+use anyhow::anyhow;
+use tokio::sync::Mutex;
+use url::Url;
+
+use near_crypto::{InMemorySigner, KeyType, SecretKey};
+use near_primitives::types::{AccountId, Balance};
+
+use crate::rpc::client::Client;
+use crate::rpc::tool;
+use crate::runtime::online;
+use crate::NearBalance;
+
+/// Maximum number of helper accounts drained into a single account per top-up.
+const FAUCET_DRIPS: usize = 5;
+
+/// Keeps the accounts signing transactions funded, on testnet or the sandbox, see
+/// [`Worker::auto_top_up`](crate::Worker::auto_top_up).
+pub(super) struct TopUp {
+    client: Client,
+    helper_urls: Vec<Url>,
+    reserve: Option<InMemorySigner>,
+    threshold: NearBalance,
+    amount: NearBalance,
+    // Parallel transactions from the same account would otherwise all top it up at once:
+    lock: Mutex<()>,
+}
+
+impl TopUp {
+    pub(super) fn new(
+        client: Client,
+        helper_urls: Vec<Url>,
+        reserve: Option<InMemorySigner>,
+        threshold: NearBalance,
+        amount: NearBalance,
+    ) -> Self {
+        Self {
+            client,
+            helper_urls,
+            reserve,
+            threshold,
+            amount,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Top up `account_id` by the configured amount if its balance fell below the threshold,
+    /// from the reserve if there is one, and from the testnet helper otherwise. The reserve
+    /// itself is refilled from the helper.
+    pub(super) async fn check(&self, account_id: AccountId) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().await;
+        let balance = self.balance(&account_id).await?;
+        if balance >= self.threshold.to_yoctonear() {
+            return Ok(());
+        }
+
+        let reserve = match &self.reserve {
+            Some(reserve) if reserve.account_id != account_id => reserve,
+            _ => return self.drip(&account_id, balance).await,
+        };
+
+        let outcome = self
+            .client
+            .transfer_near(
+                reserve,
+                reserve.account_id.clone(),
+                account_id.clone(),
+                self.amount,
+            )
+            .await
+            .map_err(anyhow::Error::msg)?;
+        if !outcome.is_success() {
+            return Err(anyhow!(
+                "could not top up {} from reserve {}: {:?}",
+                account_id,
+                reserve.account_id,
+                outcome.status()
+            ));
+        }
+
+        let reserve_balance = self.balance(&reserve.account_id).await?;
+        if reserve_balance < self.threshold.to_yoctonear() {
+            self.drip(&reserve.account_id, reserve_balance).await?;
+        }
+        Ok(())
+    }
+
+    /// Fund `account_id` from the testnet helper, which has no faucet for existing accounts:
+    /// accounts it creates are deleted right away, with `account_id` as the beneficiary.
+    async fn drip(&self, account_id: &AccountId, balance: Balance) -> anyhow::Result<()> {
+        let target = balance + self.amount.to_yoctonear();
+        for _ in 0..FAUCET_DRIPS {
+            let secret_key = SecretKey::from_random(KeyType::ED25519);
+            let drip_id = tool::random_account_id();
+            let signer = InMemorySigner::from_secret_key(drip_id.clone(), secret_key);
+            online::create_top_level_account(
+                &self.client,
+                &self.helper_urls,
+                drip_id.clone(),
+                signer.public_key.clone(),
+            )
+            .await?;
+            self.client
+                .delete_account(drip_id, &signer, account_id.clone())
+                .await
+                .map_err(anyhow::Error::msg)?;
+
+            if self.balance(account_id).await? >= target {
+                return Ok(());
+            }
+        }
+        Err(anyhow!(
+            "{} is still below {} after {} helper top-ups",
+            account_id,
+            NearBalance::from_yoctonear(target),
+            FAUCET_DRIPS
+        ))
+    }
+
+    async fn balance(&self, account_id: &AccountId) -> anyhow::Result<Balance> {
+        let view = self
+            .client
+            .view_account(account_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?;
+        Ok(view.amount)
+    }
+}
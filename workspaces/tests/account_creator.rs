@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+/// Stands in for an external service, recording the accounts it was asked for.
+struct RecordingCreator {
+    worker: Worker<Sandbox>,
+    created: Arc<Mutex<Vec<AccountId>>>,
+}
+
+#[async_trait]
+impl AccountCreator for RecordingCreator {
+    async fn create_account(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> anyhow::Result<()> {
+        self.worker
+            .create_top_level_account(account_id.clone(), public_key.clone())
+            .await?;
+        self.created.lock().unwrap().push(account_id.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_account_creator() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let created = Arc::new(Mutex::new(Vec::new()));
+    let worker = worker.clone().with_account_creator(RecordingCreator {
+        worker,
+        created: created.clone(),
+    });
+
+    let account = worker.dev_create().await?;
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    assert_eq!(
+        *created.lock().unwrap(),
+        vec![account.id().clone(), contract.id().clone()]
+    );
+
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subaccount_creator() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let creator = worker.dev_create().await?;
+    let worker = worker.with_account_creator(
+        SubaccountCreator::new(creator.clone()).initial_balance(NearBalance::from_near(5)?),
+    );
+
+    let contract = worker
+        .dev_deploy_and_init(
+            STATUS_MSG_WASM_FILEPATH,
+            InitCall::new("set_status", br#"{"message": "initialized"}"#.to_vec()),
+        )
+        .await?;
+    assert!(contract
+        .id()
+        .as_str()
+        .ends_with(&format!(".{}", creator.id())));
+
+    let status: String = contract
+        .view("get_status")
+        .args_json(json!({ "account_id": contract.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "initialized");
+
+    let balance = contract
+        .as_account()
+        .view_balance()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(balance <= NearBalance::from_near(5)?);
+    Ok(())
+}
+
+#[test]
+fn test_helper_unavailable() {
+    let account_id: AccountId = "dev-1.testnet".parse().unwrap();
+    assert!(HelperError::RateLimited { retry_after: None }.is_unavailable());
+    assert!(HelperError::Unreachable {
+        url: "https://helper.testnet.near.org/".to_string(),
+        message: "connection refused".to_string(),
+    }
+    .is_unavailable());
+    assert!(HelperError::Failed {
+        account_id: account_id.clone(),
+        status: 503,
+        message: String::new(),
+    }
+    .is_unavailable());
+    assert!(!HelperError::Failed {
+        account_id: account_id.clone(),
+        status: 403,
+        message: String::new(),
+    }
+    .is_unavailable());
+    assert!(!HelperError::InvalidAccountId {
+        account_id,
+        message: "invalid account id".to_string(),
+    }
+    .is_unavailable());
+}
@@ -0,0 +1,37 @@
+use workspaces::{Gas, NearBalance};
+
+#[test]
+fn test_balance_units() {
+    assert_eq!(
+        NearBalance::from_near(1).unwrap(),
+        NearBalance::from_yoctonear(10u128.pow(24))
+    );
+    assert_eq!(
+        NearBalance::from_millinear(1500).unwrap().to_string(),
+        "1.500 NEAR"
+    );
+}
+
+#[test]
+fn test_balance_rejects_mistyped_exponents() {
+    // Overflows a u128 of yoctoNEAR:
+    assert!(NearBalance::from_near(u128::MAX).is_err());
+    // More than the total supply of NEAR:
+    assert!(NearBalance::from_near(10u128.pow(24)).is_err());
+    assert!(NearBalance::from_yoctonear(10u128.pow(36))
+        .check_sane()
+        .is_err());
+    assert!(NearBalance::from_near(1_000_000)
+        .unwrap()
+        .check_sane()
+        .is_ok());
+}
+
+#[test]
+fn test_gas_units() {
+    assert_eq!(Gas::from_tgas(1), Gas::from_ggas(1_000));
+    assert_eq!(Gas::from_ggas(1).as_gas(), 10u64.pow(9));
+    assert_eq!(Gas::MAX, Gas::from_tgas(300));
+    assert_eq!(Gas::from(5_000_000_000_000u64), Gas::from_tgas(5));
+    assert_eq!(Gas::from_ggas(2_500).to_string(), "2.500 Tgas");
+}
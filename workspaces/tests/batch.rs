@@ -0,0 +1,85 @@
+mod common;
+
+use futures::future::try_join_all;
+
+use common::Proxy;
+use workspaces::*;
+
+/// Accounts created on `sandbox`, without going through a proxy.
+async fn dev_accounts(sandbox: &Sandbox, count: usize) -> anyhow::Result<Vec<Account>> {
+    let root = InMemorySigner::from_file(&sandbox.home_dir().join("validator_key.json"));
+    let keystore = sandbox.home_dir().join("batch-credentials");
+    let worker = Worker::custom(sandbox.rpc_addr(), "localnet", Some(root), &keystore);
+    let mut accounts = Vec::new();
+    for _ in 0..count {
+        accounts.push(worker.dev_create().await?);
+    }
+    Ok(accounts)
+}
+
+#[tokio::test]
+async fn test_batched_queries() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let accounts = dev_accounts(&sandbox, 3).await?;
+    let proxy = Proxy::start(sandbox.rpc_addr(), true).await?;
+    let worker = Worker::new(Custom::new(proxy.url()));
+
+    let account_ids: Vec<AccountId> = accounts.iter().map(|a| a.id().clone()).collect();
+    let infos = worker.display_account_infos(account_ids.clone()).await?;
+    let info_ids: Vec<AccountId> = infos.into_iter().map(|info| info.account_id).collect();
+    assert_eq!(info_ids, account_ids);
+    assert_eq!((proxy.batches(), proxy.requests()), (1, 0));
+
+    // The access keys of concurrent transactions are fetched in a single batch too:
+    proxy.reset();
+    let senders: Vec<Account> = accounts
+        .iter()
+        .map(|a| worker.account(a.id().clone(), a.secret_key().clone()))
+        .collect();
+    let receiver = &account_ids[0];
+    let amount = NearBalance::from_near(1)?;
+    let results = try_join_all(
+        senders
+            .iter()
+            .map(|sender| sender.transfer_near(receiver, amount)),
+    )
+    .await
+    .map_err(anyhow::Error::msg)?;
+    assert!(results.iter().all(ExecutionResult::is_success));
+    assert_eq!(proxy.batches(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batched_queries_fallback() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let accounts = dev_accounts(&sandbox, 3).await?;
+    let proxy = Proxy::start(sandbox.rpc_addr(), false).await?;
+    let worker = Worker::new(Custom::new(proxy.url()));
+
+    let account_ids: Vec<AccountId> = accounts.iter().map(|a| a.id().clone()).collect();
+    let infos = worker.display_account_infos(account_ids.clone()).await?;
+    let info_ids: Vec<AccountId> = infos.into_iter().map(|info| info.account_id).collect();
+    assert_eq!(info_ids, account_ids);
+    assert_eq!((proxy.batches(), proxy.requests()), (1, 3));
+
+    proxy.reset();
+    let senders: Vec<Account> = accounts
+        .iter()
+        .map(|a| worker.account(a.id().clone(), a.secret_key().clone()))
+        .collect();
+    let receiver = &account_ids[0];
+    let amount = NearBalance::from_near(1)?;
+    let results = try_join_all(
+        senders
+            .iter()
+            .map(|sender| sender.transfer_near(receiver, amount)),
+    )
+    .await
+    .map_err(anyhow::Error::msg)?;
+    assert!(results.iter().all(ExecutionResult::is_success));
+    assert_eq!(proxy.batches(), 1);
+
+    Ok(())
+}
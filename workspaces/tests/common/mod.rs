@@ -0,0 +1,151 @@
+//! Helpers shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures::future::try_join_all;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// RPC endpoint forwarding to a sandbox, which either answers JSON-RPC batches itself or
+/// rejects them like an endpoint without batching support does. It can also drop requests,
+/// the way a flaky connection does.
+#[derive(Clone)]
+pub struct Proxy {
+    url: String,
+    target: String,
+    batching: bool,
+    batches: Arc<AtomicUsize>,
+    requests: Arc<AtomicUsize>,
+    /// Number of requests still to be dropped, per RPC method.
+    drops: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Proxy {
+    pub async fn start(target: &str, batching: bool) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy = Self {
+            url: format!("http://{}", listener.local_addr()?),
+            target: target.to_string(),
+            batching,
+            batches: Arc::default(),
+            requests: Arc::default(),
+            drops: Arc::default(),
+        };
+        let server = proxy.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let server = server.clone();
+                tokio::spawn(async move { server.serve(stream).await });
+            }
+        });
+        Ok(proxy)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Batches received, whether they were answered or rejected.
+    pub fn batches(&self) -> usize {
+        self.batches.load(Ordering::SeqCst)
+    }
+
+    /// Requests received outside of batches, dropped ones included.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.batches.store(0, Ordering::SeqCst);
+        self.requests.store(0, Ordering::SeqCst);
+    }
+
+    /// Close the connection of the next `count` requests for `method` without answering.
+    pub fn drop_requests(&self, method: &str, count: usize) {
+        self.drops.lock().unwrap().insert(method.to_string(), count);
+    }
+
+    fn should_drop(&self, request: &Value) -> bool {
+        let method = request["method"].as_str().unwrap_or_default();
+        match self.drops.lock().unwrap().get_mut(method) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn serve(&self, mut stream: TcpStream) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        let body_start = loop {
+            let mut chunk = [0; 4096];
+            let read = stream.read(&mut chunk).await?;
+            anyhow::ensure!(read > 0, "connection closed before the headers ended");
+            buf.extend_from_slice(&chunk[..read]);
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&buf[..body_start]).to_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map_or(Ok(0), |len| len.trim().parse())?;
+        while buf.len() < body_start + content_length {
+            let mut chunk = [0; 4096];
+            let read = stream.read(&mut chunk).await?;
+            anyhow::ensure!(read > 0, "connection closed before the body ended");
+            buf.extend_from_slice(&chunk[..read]);
+        }
+
+        let request: Value = serde_json::from_slice(&buf[body_start..])?;
+        let response = match request {
+            Value::Array(requests) if self.batching => {
+                self.batches.fetch_add(1, Ordering::SeqCst);
+                let mut responses = try_join_all(requests.iter().map(|r| self.forward(r))).await?;
+                // Clients have to match responses by id:
+                responses.reverse();
+                Value::Array(responses)
+            }
+            Value::Array(_) => {
+                self.batches.fetch_add(1, Ordering::SeqCst);
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": -32600, "message": "Invalid Request" },
+                })
+            }
+            request => {
+                self.requests.fetch_add(1, Ordering::SeqCst);
+                if self.should_drop(&request) {
+                    return Ok(());
+                }
+                self.forward(&request).await?
+            }
+        };
+
+        let body = serde_json::to_vec(&response)?;
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&body).await?;
+        Ok(())
+    }
+
+    async fn forward(&self, request: &Value) -> anyhow::Result<Value> {
+        Ok(reqwest::Client::new()
+            .post(&self.target)
+            .json(request)
+            .send()
+            .await?
+            .json()
+            .await?)
+    }
+}
@@ -0,0 +1,35 @@
+use workspaces::{compile_project, Compile};
+
+const FEATURES_PROJECT: &str = "./tests/res/compile-features";
+
+fn contains(wasm: &[u8], needle: &str) -> bool {
+    wasm.windows(needle.len())
+        .any(|window| window == needle.as_bytes())
+}
+
+#[tokio::test]
+async fn test_compile_missing_project() {
+    let err = compile_project("./does-not-exist").await.unwrap_err();
+    assert!(err.to_string().contains("no Cargo.toml"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_compile_with_features_and_env() -> anyhow::Result<()> {
+    let wasm = Compile::new(FEATURES_PROJECT)
+        .features(&["integration-test"])
+        .env("BUILD_TAG", "built-for-tests")
+        .build()
+        .await?;
+    assert!(contains(&wasm, "integration_test_hook"));
+    assert!(contains(&wasm, "built-for-tests"));
+
+    // Test-only exports stay out of a build without the feature:
+    let wasm = Compile::new(FEATURES_PROJECT)
+        .env("BUILD_TAG", "built-for-release")
+        .build()
+        .await?;
+    assert!(!contains(&wasm, "integration_test_hook"));
+    assert!(contains(&wasm, "built-for-release"));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+contract_methods! {
+    /// The status message example contract, declared by hand.
+    struct StatusMessage {
+        /// Set the status of the calling account.
+        call fn set_status(message: String);
+        view fn get_status(account_id: AccountId) -> Option<String>;
+    }
+}
+
+contract_methods! {
+    #[abi = "../examples/res/status_message_abi.json"]
+    struct StatusMessageAbi;
+}
+
+#[tokio::test]
+async fn test_declared_methods() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let status = StatusMessage::new(&contract);
+
+    assert_eq!(status.get_status(contract.id().clone()).await?, None);
+    status
+        .set_status("hello")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert_eq!(
+        status.get_status(contract.id().clone()).await?,
+        Some("hello".to_string())
+    );
+
+    let alice = worker.dev_create().await?;
+    status
+        .called_by(&alice)
+        .set_status("hi")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert_eq!(
+        status.get_status(alice.id().clone()).await?,
+        Some("hi".to_string())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_methods_from_abi() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    contract
+        .methods::<StatusMessageAbi>()
+        .set_status("hello")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let status: Option<String> = contract
+        .methods::<StatusMessageAbi>()
+        .get_status(contract.id().to_string())
+        .await?;
+    assert_eq!(status, Some("hello".to_string()));
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::convert::TryInto;
+use workspaces::*;
+
+#[tokio::test]
+async fn test_custom_network_creates_dev_accounts() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let root = InMemorySigner::from_file(&sandbox.home_dir().join("validator_key.json"));
+    let keystore = sandbox.home_dir().join("custom-credentials");
+
+    let worker = Worker::custom(sandbox.rpc_addr(), "localnet", Some(root), &keystore);
+    assert_eq!(worker.name(), "custom");
+
+    let account = worker.dev_create().await?;
+    assert!(account.view_account().await.is_ok());
+    assert!(keystore.exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_network_without_root_account() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let worker = Worker::new(Custom::new(sandbox.rpc_addr()).with_network_id("localnet"));
+
+    assert!(worker.dev_create().await.is_err());
+    assert!(worker
+        .display_account_info("test.near".to_string().try_into()?)
+        .await
+        .is_ok());
+
+    Ok(())
+}
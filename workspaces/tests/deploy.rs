@@ -52,6 +52,68 @@ async fn test_dev_deploy() {
     .await
     .unwrap();
 
-    let actual: NftMetadata = serde_json::from_value(call_result).unwrap();
+    let actual: NftMetadata = call_result.json().unwrap();
     assert_eq!(actual, expected());
 }
+
+#[workspaces::test(sandbox)]
+async fn test_dev_deploy_and_init() {
+    // The owner is only known after deployment, so initialize with a fixed owner instead:
+    let init = InitCall::new(
+        "new_default_meta",
+        br#"{"owner_id": "alice.near"}"#.to_vec(),
+    );
+    let (contract_id, _) = dev_deploy_and_init(NFT_WASM_FILEPATH, init)
+        .await
+        .expect("could not dev-deploy NFT contract");
+
+    let call_result = view(contract_id, "nft_metadata".to_string(), Vec::new().into())
+        .await
+        .unwrap();
+
+    let actual: NftMetadata = call_result.json().unwrap();
+    assert_eq!(actual, expected());
+}
+
+#[tokio::test]
+async fn test_dev_deploy_bytes() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker
+        .dev_deploy(include_bytes!("../../examples/res/non_fungible_token.wasm"))
+        .await?;
+
+    contract
+        .call("new_default_meta")
+        .args_json(serde_json::json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let metadata: NftMetadata = contract
+        .view("nft_metadata")
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(metadata, expected());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dev_deploy_cached() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let wasm = std::fs::read(NFT_WASM_FILEPATH)?;
+
+    let first = worker.dev_deploy_cached(wasm.as_slice()).await?;
+    let second = worker.dev_deploy_cached(wasm.as_slice()).await?;
+    assert_eq!(first.id(), second.id());
+
+    // The keystore is shared between sandboxes, but the cached account doesn't exist on a
+    // fresh one, so a new account gets created:
+    let other = Worker::new(Sandbox::new()?);
+    let third = other.dev_deploy_cached(wasm.as_slice()).await?;
+    assert_ne!(first.id(), third.id());
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use workspaces::*;
+
+#[tokio::test]
+async fn test_transfer_costs_from_protocol_config() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+    let costs = worker.protocol_costs().await?;
+
+    let result = alice
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+
+    // Converting the transaction into a receipt burns the send costs of the receipt and of
+    // its single transfer action:
+    let outcome = result.transaction_outcome();
+    let expected = costs.receipt_creation_cost().send_not_sir.as_gas()
+        + costs
+            .action_cost(ActionKind::Transfer)
+            .send_not_sir
+            .as_gas();
+    assert_eq!(outcome.gas_burnt, expected);
+
+    let block = worker
+        .view_block(BlockReference::BlockId(BlockId::Hash(outcome.block_hash)))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(
+        block.gas_cost(Gas::from_gas(outcome.gas_burnt)),
+        outcome.tokens_burnt
+    );
+
+    assert!(costs.storage_cost(1_000) > NearBalance::default());
+    assert_eq!(
+        costs.storage_cost(2_000).to_yoctonear(),
+        2 * costs.storage_cost(1_000).to_yoctonear()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+const EVENTS_PROJECT: &str = "./tests/res/events";
+
+async fn set_status() -> anyhow::Result<(Contract, ExecutionResult)> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let result = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    Ok((contract, result))
+}
+
+async fn deploy_events() -> anyhow::Result<Contract> {
+    let worker = Worker::new(Sandbox::new()?);
+    worker
+        .dev_deploy(compile_project(EVENTS_PROJECT).await?)
+        .await
+}
+
+/// Events `first` and `second`, emitted by two receipts executing in different blocks.
+async fn emit_events() -> anyhow::Result<(Contract, ExecutionResult)> {
+    let contract = deploy_events().await?;
+    let result = contract
+        .call("emit")
+        .max_gas()
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+    Ok((contract, result))
+}
+
+#[test]
+fn test_expected_event_from_str() {
+    assert_eq!(
+        ExpectedEvent::from("nep171:nft_transfer"),
+        ExpectedEvent::new("nft_transfer").standard("nep171")
+    );
+    assert_eq!(
+        ExpectedEvent::from("nft_transfer"),
+        ExpectedEvent::new("nft_transfer")
+    );
+
+    let emitter: AccountId = "nft.test.near".parse().unwrap();
+    assert_eq!(
+        ExpectedEvent::from("nep171:nft_mint")
+            .emitted_by(&emitter)
+            .to_string(),
+        "nep171:nft_mint by nft.test.near"
+    );
+}
+
+#[test]
+fn test_event_from_log() -> anyhow::Result<()> {
+    let emitter: AccountId = "nft.test.near".parse()?;
+    let event = Event::from_log(
+        emitter.clone(),
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice.test.near","token_ids":["1","2"]}]}"#,
+    )
+    .expect("valid event");
+    assert_eq!(event.emitter, emitter);
+    assert_eq!(event.standard, "nep171");
+    assert_eq!(event.version, "1.0.0");
+    assert_eq!(event.event, "nft_mint");
+    assert_eq!(event.to_string(), "nep171:nft_mint by nft.test.near");
+
+    #[derive(serde::Deserialize)]
+    struct NftMint {
+        owner_id: AccountId,
+        token_ids: Vec<String>,
+    }
+    let mints: Vec<NftMint> = event.data_as()?;
+    assert_eq!(mints.len(), 1);
+    assert_eq!(mints[0].owner_id.as_str(), "alice.test.near");
+    assert_eq!(mints[0].token_ids, ["1", "2"]);
+
+    assert_eq!(Event::from_log(emitter.clone(), "minted 2 tokens"), None);
+    assert_eq!(Event::from_log(emitter, "EVENT_JSON:{\"event\""), None);
+    Ok(())
+}
+
+#[test]
+fn test_event_serde() -> anyhow::Result<()> {
+    let emitter: AccountId = "ft.test.near".parse()?;
+    let event = Event::from_log(
+        emitter,
+        r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"alice.test.near","amount":"100"}]}"#,
+    )
+    .expect("valid event");
+
+    let value = serde_json::to_value(&event)?;
+    assert_eq!(
+        value,
+        json!({
+            "emitter": "ft.test.near",
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_burn",
+            "data": [{ "owner_id": "alice.test.near", "amount": "100" }],
+        })
+    );
+    assert_eq!(serde_json::from_value::<Event>(value)?, event);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nft_mint_event() -> anyhow::Result<()> {
+    let contract = deploy_events().await?;
+    let result = contract
+        .call("nft_mint")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+
+    let events = result.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].emitter, *contract.id());
+    assert_eq!(
+        events[0].to_string(),
+        format!("nep171:nft_mint by {}", contract.id())
+    );
+
+    #[derive(serde::Deserialize)]
+    struct NftMint {
+        owner_id: AccountId,
+        token_ids: Vec<String>,
+    }
+    let mints: Vec<NftMint> = events[0].data_as()?;
+    assert_eq!(mints.len(), 1);
+    assert_eq!(mints[0].owner_id.as_str(), "alice.test.near");
+    assert_eq!(mints[0].token_ids, ["0", "1"]);
+
+    // Round-trips through serde, like the events of a dumped result:
+    let json = serde_json::to_string(&events)?;
+    assert_eq!(serde_json::from_str::<Vec<Event>>(&json)?, events);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_events() -> anyhow::Result<()> {
+    let (_, result) = set_status().await?;
+    assert_eq!(result.events(), Vec::new());
+    assert!(result.outcomes().all(|o| o.events().is_empty()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_events_expected() -> anyhow::Result<()> {
+    let (_, result) = set_status().await?;
+    result.assert_events_in_order(Vec::<ExpectedEvent>::new());
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "did not emit nep171:nft_mint by")]
+async fn test_missing_event() {
+    let (contract, result) = set_status().await.unwrap();
+    // The status message contract emits no events:
+    result
+        .assert_events_in_order([ExpectedEvent::from("nep171:nft_mint").emitted_by(contract.id())]);
+}
+
+#[tokio::test]
+async fn test_events_in_order_across_receipts() -> anyhow::Result<()> {
+    let (contract, result) = emit_events().await?;
+    let events = result.events();
+    let names: Vec<&str> = events.iter().map(|event| event.event.as_str()).collect();
+    assert_eq!(names, ["first", "second"]);
+    // Each from its own receipt:
+    let heights: Vec<_> = result
+        .outcomes()
+        .filter(|o| !o.logs.is_empty())
+        .map(|o| o.block_height)
+        .collect();
+    assert_eq!(heights.len(), 2);
+    assert_ne!(heights[0], heights[1]);
+
+    result.assert_events_in_order([
+        ExpectedEvent::from("test:first").emitted_by(contract.id()),
+        ExpectedEvent::from("test:second").emitted_by(contract.id()),
+    ]);
+    Ok(())
+}
+
+#[tokio::test]
+#[should_panic(expected = "did not emit test:first after the 1 events expected before it")]
+async fn test_events_out_of_order() {
+    let (_, result) = emit_events().await.unwrap();
+    result.assert_events_in_order(["test:second", "test:first"]);
+}
+
+#[tokio::test]
+async fn test_stream_outcomes() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let mut outcomes = worker.stream_outcomes(contract.id().clone()).await?;
+
+    let result = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+
+    // Transactions deploying the contract may still be in blocks that weren't final yet:
+    let expected = result.outcomes_for(contract.id())[0].clone();
+    let outcome = tokio::time::timeout(Duration::from_secs(30), async {
+        while let Some(outcome) = outcomes.next().await {
+            let outcome = outcome?;
+            if outcome.id == expected.id {
+                return Ok(outcome);
+            }
+        }
+        Err(anyhow::anyhow!("stream of outcomes ended"))
+    })
+    .await??;
+    assert_eq!(outcome, expected);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::panic::AssertUnwindSafe;
+
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_explorer_links() -> anyhow::Result<()> {
+    let tx_hash = CryptoHash::default();
+    assert_eq!(
+        Worker::new(Testnet::new()?).explorer_link(&tx_hash),
+        Some(format!(
+            "https://explorer.testnet.near.org/transactions/{}",
+            tx_hash
+        ))
+    );
+    let mainnet_link = Some(format!(
+        "https://explorer.near.org/transactions/{}",
+        tx_hash
+    ));
+    assert_eq!(
+        Worker::new(Mainnet::new()).explorer_link(&tx_hash),
+        mainnet_link
+    );
+    assert_eq!(
+        Worker::new(Mainnet::archival()).explorer_link(&tx_hash),
+        mainnet_link
+    );
+    assert_eq!(Worker::new(Sandbox::new()?).explorer_link(&tx_hash), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assert_success_message() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let result = contract
+        .call("set_stauts")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    // Sandbox transactions have no explorer link:
+    assert_eq!(result.explorer_link(), None);
+
+    let panic = std::panic::catch_unwind(AssertUnwindSafe(|| result.assert_success()))
+        .expect_err("the transaction failed");
+    let message = panic.downcast_ref::<String>().expect("formatted message");
+    let expected = format!("transaction {} failed: ", result.transaction_hash());
+    assert!(message.starts_with(&expected), "{}", message);
+    assert!(message.contains("MethodNotFound"), "{}", message);
+    assert!(
+        message.contains("did you mean `set_status`?"),
+        "{}",
+        message
+    );
+    assert!(!message.contains("/transactions/"), "{}", message);
+    Ok(())
+}
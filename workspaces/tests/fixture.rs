@@ -0,0 +1,84 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::FutureExt;
+
+use workspaces::*;
+
+struct DevAccount {
+    account_id: AccountId,
+}
+
+#[async_trait::async_trait]
+impl Fixture<Sandbox> for DevAccount {
+    async fn setup(worker: &Worker<Sandbox>) -> anyhow::Result<Self> {
+        let account = worker.dev_create().await?;
+        Ok(Self {
+            account_id: account.id().clone(),
+        })
+    }
+}
+
+static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails its teardown, and counts how often it ran.
+struct BrokenTeardown;
+
+#[async_trait::async_trait]
+impl Fixture<Sandbox> for BrokenTeardown {
+    async fn setup(_: &Worker<Sandbox>) -> anyhow::Result<Self> {
+        Ok(BrokenTeardown)
+    }
+
+    async fn teardown(&self, _: &Worker<Sandbox>) -> anyhow::Result<()> {
+        TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+        Err(anyhow::anyhow!("teardown failed"))
+    }
+}
+
+#[tokio::test]
+async fn test_composed_fixtures() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    with_fixture(&worker, |users: Arc<(DevAccount, DevAccount)>| async move {
+        assert_ne!(users.0.account_id, users.1.account_id);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_cached_fixture_is_shared_per_worker() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let first = Cached::<DevAccount>::setup(&worker).await?;
+    let second = Cached::<DevAccount>::setup(&worker.clone()).await?;
+    assert_eq!(first.account_id, second.account_id);
+
+    let other = Worker::new(Sandbox::new()?);
+    let third = Cached::<DevAccount>::setup(&other).await?;
+    assert_ne!(first.account_id, third.account_id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_teardown_error() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let result = with_fixture(&worker, |_: Arc<BrokenTeardown>| async {}).await;
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_body_panic_is_kept_over_teardown_error() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let before = TEARDOWNS.load(Ordering::SeqCst);
+    let panic = AssertUnwindSafe(with_fixture(&worker, |_: Arc<BrokenTeardown>| async {
+        panic!("body panicked");
+    }))
+    .catch_unwind()
+    .await
+    .expect_err("the panic of the body is propagated");
+
+    assert_eq!(panic.downcast_ref::<&str>(), Some(&"body panicked"));
+    assert!(TEARDOWNS.load(Ordering::SeqCst) > before);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "arbitrary")]
+
+use serde_json::json;
+use workspaces::fuzz::Fuzz;
+use workspaces::{Sandbox, Worker};
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_fuzz_status_message() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+
+    Fuzz::new(&worker, STATUS_MSG_WASM_FILEPATH)?
+        .cases(3)
+        .input_len(32)
+        .run(|contract, message: String| async move {
+            contract
+                .call("set_status")
+                .args_json(json!({ "message": message }))
+                .transact()
+                .await
+                .map_err(anyhow::Error::msg)?
+                .assert_success();
+
+            let status: String = contract
+                .view("get_status")
+                .args_json(json!({ "account_id": contract.id() }))
+                .fetch()
+                .await
+                .map_err(anyhow::Error::msg)?
+                .json()?;
+            anyhow::ensure!(status == message, "status {:?} != {:?}", status, message);
+            Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_fuzz_reports_shrunk_failure() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+
+    let err = Fuzz::new(&worker, STATUS_MSG_WASM_FILEPATH)?
+        .cases(1)
+        .input_len(8)
+        .shrink_attempts(4)
+        .seed(0)
+        .run(|_, bytes: Vec<u8>| async move {
+            anyhow::ensure!(bytes.is_empty(), "got {} bytes", bytes.len());
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("reproduce with NEAR_WORKSPACES_FUZZ_SEED=0"));
+
+    Ok(())
+}
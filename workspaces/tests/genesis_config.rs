@@ -0,0 +1,49 @@
+use std::convert::TryInto;
+
+use workspaces::*;
+
+#[tokio::test]
+async fn test_genesis_overrides() -> anyhow::Result<()> {
+    let funded_id: AccountId = "funded.test.near".to_string().try_into().unwrap();
+    let funded_key = SecretKey::from_seed(KeyType::ED25519, "funded");
+    let stake = NearBalance::from_near(60_000)?;
+    let gas_price = 500_000_000;
+    let balance = NearBalance::from_near(1_000)?;
+
+    let sandbox = Sandbox::builder()
+        .genesis(|genesis| {
+            genesis
+                .epoch_length(10)
+                .gas_price(gas_price)
+                .validator_stake(stake)
+                .add_account(funded_id.clone(), funded_key.public_key(), balance)
+        })
+        .build()?;
+    let worker = Worker::new(sandbox);
+
+    let funded = worker.account(funded_id, funded_key);
+    assert_eq!(
+        funded.view_balance().await.map_err(anyhow::Error::msg)?,
+        balance
+    );
+    let root = worker
+        .view_account(worker.root_account().id().clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(root.stake, stake);
+
+    let first = worker
+        .view_block(BlockReference::Finality(Finality::Final))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(first.raw().header.gas_price >= gas_price);
+
+    worker.fast_forward(20).await.map_err(anyhow::Error::msg)?;
+    let later = worker
+        .view_block(BlockReference::Finality(Finality::Final))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_ne!(later.epoch_id(), first.epoch_id());
+
+    Ok(())
+}
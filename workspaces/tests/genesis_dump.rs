@@ -0,0 +1,42 @@
+use serde_json::json;
+use workspaces::{Sandbox, Worker};
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_dump_genesis() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "dumped" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let dir = std::env::temp_dir().join(format!("genesis-dump-{}", std::process::id()));
+    worker.dump_genesis(&[contract.id().clone()], &dir).await?;
+
+    let seeded = Worker::new(Sandbox::from_genesis(&dir)?);
+    let status: String = seeded
+        .view(
+            contract.id().clone(),
+            "get_status".into(),
+            json!({ "account_id": contract.id() })
+                .to_string()
+                .into_bytes()
+                .into(),
+        )
+        .await
+        .map_err(anyhow::Error::msg)
+        .and_then(|status| status.json())?;
+    assert_eq!(status, "dumped");
+
+    // The root account of the dump keeps working, as its keys come along:
+    let account = seeded.dev_create().await?;
+    assert!(account.view_account().await.is_ok());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use workspaces::*;
+
+#[workspaces::test(sandbox)]
+async fn test_guard_detects_balance_change() {
+    let (alice, signer) = dev_create().await.unwrap();
+    let (bob, _) = dev_create().await.unwrap();
+
+    let guard = AccountGuard::snapshot(bob.clone()).await.unwrap();
+    guard.check().await.unwrap();
+
+    transfer_near(&signer, alice, bob, NearBalance::from_millinear(1).unwrap())
+        .await
+        .unwrap();
+    assert!(guard.check().await.is_err());
+}
+
+#[tokio::test]
+async fn test_guard_detects_key_addition() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let account = worker.dev_create().await?;
+
+    let guard = worker.account_guard(account.id().clone()).await?;
+    account
+        .add_key(
+            SecretKey::from_random(KeyType::ED25519).public_key(),
+            AccessKeyPermission::FullAccess,
+        )
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let err = guard.check().await.unwrap_err();
+    assert!(err.to_string().starts_with("access keys of"), "{:?}", err);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_guard_balance_tolerance() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.dev_create().await?;
+    let bob = worker.dev_create().await?;
+
+    let guard = worker.account_guard(alice.id().clone()).await?;
+    alice
+        .transfer_near(bob.id(), NearBalance::from_millinear(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    // The transfer and its gas stay within the tolerance, but not within none at all:
+    guard
+        .clone()
+        .balance_tolerance(NearBalance::from_millinear(10)?)
+        .check()
+        .await?;
+    assert!(guard.check().await.is_err());
+    // Nor is an increase ever tolerated:
+    let guard = worker
+        .account_guard(bob.id().clone())
+        .await?
+        .balance_tolerance(NearBalance::from_near(1)?);
+    alice
+        .transfer_near(bob.id(), NearBalance::from_millinear(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert!(guard.check().await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_guard_scope() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.dev_create().await?;
+    let bob = worker.dev_create().await?;
+    let carol = worker.dev_create().await?;
+
+    let guard = worker.account_guard(bob.id().clone()).await?;
+    // Work between other accounts leaves the guarded one alone, and its output is passed on:
+    let result = guard
+        .scope(alice.transfer_near(carol.id(), NearBalance::from_millinear(1)?))
+        .await?
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+
+    let err = guard
+        .scope(alice.transfer_near(bob.id(), NearBalance::from_millinear(1)?))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().starts_with("balance of"), "{:?}", err);
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+async fn set_status(contract: &Contract, message: &str) -> Result<ExecutionResult, String> {
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": message }))
+        .transact()
+        .await
+}
+
+#[workspaces::test(sandbox)]
+async fn test_runtime_tx_hook() -> anyhow::Result<()> {
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = seen.clone();
+    register_tx_hook(move |result| {
+        let counter = counter.clone();
+        async move {
+            assert!(result.is_success());
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+
+    let (contract_id, signer) = dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let before = seen.load(Ordering::SeqCst);
+    call(
+        &signer,
+        contract_id.clone(),
+        contract_id,
+        "set_status".to_string(),
+        br#"{"message": "hello"}"#.to_vec(),
+        None,
+    )
+    .await
+    .map_err(anyhow::Error::msg)?;
+    assert_eq!(seen.load(Ordering::SeqCst), before + 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_tx_hook() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = seen.clone();
+    let hook_contract = contract.clone();
+    worker.register_tx_hook(move |_| {
+        let counter = counter.clone();
+        let contract = hook_contract.clone();
+        async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            // Doesn't trigger the hook again:
+            set_status(&contract, "from hook")
+                .await
+                .map_err(anyhow::Error::msg)?
+                .assert_success();
+            Ok(())
+        }
+    });
+
+    set_status(&contract, "hello")
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failing_tx_hook() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    worker.register_tx_hook(|_| async { Err(anyhow::anyhow!("invariant broken")) });
+
+    let err = set_status(&contract, "hello").await.unwrap_err();
+    assert!(err.contains("Hook failed after transaction"), "{}", err);
+    assert!(err.contains("invariant broken"), "{}", err);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use workspaces::*;
+
+/// Sends a transaction signed by the fresh implicit `account`, which only works if the
+/// transfer funding it also created it along with its key.
+async fn assert_can_sign(account: &Account, receiver_id: &AccountId) -> anyhow::Result<()> {
+    assert_eq!(account.id().as_str().len(), 64);
+    account
+        .transfer_near(receiver_id, NearBalance::from_millinear(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_implicit_account() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let funder = worker.dev_create().await?;
+
+    let account = worker
+        .create_implicit_account_from(&funder, NearBalance::from_near(1)?)
+        .await?;
+    assert_can_sign(&account, funder.id()).await?;
+    assert!(account.view_balance().await.map_err(anyhow::Error::msg)? < NearBalance::from_near(1)?);
+
+    Ok(())
+}
+
+// Needs `NEAR_TESTNET_FUNDER_KEY_FILE` to point at the key file of a funded testnet account.
+#[tokio::test]
+#[ignore]
+async fn test_create_implicit_account_on_testnet() -> anyhow::Result<()> {
+    let worker = Worker::new(Testnet::new()?);
+    let account = worker
+        .create_implicit_account(NearBalance::from_millinear(100)?)
+        .await?;
+    assert_can_sign(&account, account.id()).await?;
+    Ok(())
+}
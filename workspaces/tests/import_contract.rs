@@ -0,0 +1,55 @@
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_import_contract_with_data() -> anyhow::Result<()> {
+    let source = Worker::new(Sandbox::new()?);
+    let contract = source.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello from source" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let sandbox = Worker::new(Sandbox::new()?);
+    let balance = NearBalance::from_near(42)?;
+    let imported = sandbox
+        .import_contract(contract.id(), &source)
+        .with_data()
+        .with_access_keys()
+        .initial_balance(balance)
+        .transact()
+        .await?;
+    assert_eq!(imported.id(), contract.id());
+
+    let status: String = imported
+        .view("get_status")
+        .args_json(json!({ "account_id": contract.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "hello from source");
+
+    let info = imported
+        .as_account()
+        .view_account()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(info.balance, balance);
+
+    // The imported contract can be driven with its derived key:
+    imported
+        .call("set_status")
+        .args_json(json!({ "message": "hello from sandbox" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "near-sdk")]
+
+use std::convert::TryInto;
+
+use workspaces::interop::{from_sdk_account_id, AccountIdExt};
+use workspaces::{AccountId, NearBalance};
+
+#[test]
+fn test_account_id_round_trip() {
+    let account_id: AccountId = "alice.test.near".to_string().try_into().unwrap();
+    let sdk_account_id = account_id.to_sdk();
+    assert_eq!(sdk_account_id.as_str(), "alice.test.near");
+    assert_eq!(from_sdk_account_id(&sdk_account_id), account_id);
+}
+
+#[test]
+fn test_balance_round_trip() {
+    let balance = NearBalance::from_near(5).unwrap();
+    let sdk_balance: near_sdk::json_types::U128 = balance.into();
+    assert_eq!(sdk_balance.0, balance.to_yoctonear());
+    assert_eq!(NearBalance::from(sdk_balance), balance);
+}
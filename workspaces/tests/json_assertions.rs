@@ -0,0 +1,36 @@
+use serde_json::json;
+use workspaces::ViewResult;
+
+fn view_result(value: serde_json::Value) -> ViewResult {
+    ViewResult {
+        result: serde_json::to_vec(&value).unwrap(),
+        logs: vec![],
+    }
+}
+
+#[test]
+fn test_assert_json_at() {
+    let result = view_result(json!({
+        "tokens": [{ "token_id": "0", "owner_id": "alice.test.near" }],
+    }));
+
+    result.assert_json_at("/tokens/0/owner_id", "alice.test.near");
+    result.assert_json_at(
+        "/tokens/0",
+        json!({ "token_id": "0", "owner_id": "alice.test.near" }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "mismatch at /tokens/0/owner_id")]
+fn test_assert_json_at_mismatch() {
+    let result = view_result(json!({ "tokens": [{ "owner_id": "alice.test.near" }] }));
+    result.assert_json_at("/tokens/0/owner_id", "bob.test.near");
+}
+
+#[test]
+#[should_panic(expected = "deepest existing path is \"/tokens\"")]
+fn test_assert_json_at_missing() {
+    let result = view_result(json!({ "tokens": [] }));
+    result.assert_json_at("/tokens/0/owner_id", "alice.test.near");
+}
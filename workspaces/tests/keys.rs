@@ -0,0 +1,280 @@
+use workspaces::*;
+
+#[test]
+fn test_key_round_trip() -> anyhow::Result<()> {
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let parsed: SecretKey = secret_key.to_string().parse()?;
+    assert_eq!(parsed, secret_key);
+
+    let public_key: PublicKey = secret_key.public_key().to_string().parse()?;
+    assert_eq!(public_key, secret_key.public_key());
+
+    assert_eq!(
+        SecretKey::from_seed(KeyType::ED25519, "seed"),
+        SecretKey::from_seed(KeyType::ED25519, "seed")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_account_from_secret_key() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+
+    let secret_key: SecretKey = alice.secret_key().to_string().parse()?;
+    let alice_again = worker.account(alice.id().clone(), secret_key);
+    assert_eq!(alice_again.public_key(), alice.public_key());
+
+    alice_again
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_and_delete_function_call_key() -> anyhow::Result<()> {
+    const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker
+        .dev_deploy(std::fs::read(STATUS_MSG_WASM_FILEPATH)?.as_slice())
+        .await?;
+    let alice = worker.alice().await?;
+
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+        allowance: Some(NearBalance::from_near(1)?.to_yoctonear()),
+        receiver_id: contract.id().to_string(),
+        method_names: vec!["set_status".to_string()],
+    });
+    alice
+        .add_key(secret_key.public_key(), permission.clone())
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let keys = alice
+        .view_access_key_list()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let key = keys
+        .iter()
+        .find(|key| key.public_key == secret_key.public_key())
+        .expect("key was added");
+    assert_eq!(key.access_key.permission, permission);
+
+    let restricted = alice.with_signer(InMemorySigner::from_secret_key(
+        alice.id().clone(),
+        secret_key.clone(),
+    ));
+    restricted
+        .call(contract.id(), "set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    // Function call keys can't sign transfers:
+    assert!(restricted
+        .transfer_near(contract.id(), NearBalance::from_near(1)?)
+        .await
+        .is_err());
+
+    alice
+        .delete_key(&secret_key.public_key())
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let keys = alice
+        .view_access_key_list()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(keys
+        .iter()
+        .all(|key| key.public_key != secret_key.public_key()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_with_temporary_key() -> anyhow::Result<()> {
+    const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker
+        .dev_deploy(std::fs::read(STATUS_MSG_WASM_FILEPATH)?.as_slice())
+        .await?;
+    let alice = worker.alice().await?;
+    let keys_before = alice
+        .view_access_key_list()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .len();
+
+    let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+        allowance: Some(NearBalance::from_near(1)?.to_yoctonear()),
+        receiver_id: contract.id().to_string(),
+        method_names: Vec::new(),
+    });
+    let leased_key = alice
+        .with_temporary_key(permission.clone(), |leased| async move {
+            leased
+                .call(contract.id(), "set_status")
+                .args_json(serde_json::json!({ "message": "leased" }))
+                .transact()
+                .await
+                .map_err(anyhow::Error::msg)?
+                .assert_success();
+            Ok::<_, anyhow::Error>(leased.public_key())
+        })
+        .await??;
+    assert_ne!(leased_key, alice.public_key());
+
+    // The key is gone afterwards, also when the body revoked it already. Function call keys
+    // can't delete keys, so the account's full access key revokes it:
+    let owner = &alice;
+    alice
+        .with_temporary_key(permission, |leased| async move {
+            owner.delete_key(&leased.public_key()).await
+        })
+        .await?
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let keys_after = alice
+        .view_access_key_list()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .len();
+    assert_eq!(keys_after, keys_before);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lock_contract() -> anyhow::Result<()> {
+    const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let alice = worker.alice().await?;
+
+    // A second key, to check that all of them are deleted:
+    contract
+        .as_account()
+        .add_key(
+            SecretKey::from_random(KeyType::ED25519).public_key(),
+            AccessKeyPermission::FullAccess,
+        )
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert!(!contract.is_locked().await.map_err(anyhow::Error::msg)?);
+
+    contract
+        .lock()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert!(contract.is_locked().await.map_err(anyhow::Error::msg)?);
+
+    let err = contract
+        .call("set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .unwrap_err();
+    let locked = AccountLocked {
+        account_id: contract.id().clone(),
+    };
+    assert_eq!(err, locked.to_string());
+
+    // Others can still call the contract:
+    alice
+        .call(contract.id(), "set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let status: String = contract
+        .view("get_status")
+        .args_json(serde_json::json!({ "account_id": alice.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_function_call_key_rejections() -> anyhow::Result<()> {
+    const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let alice = worker.alice().await?;
+
+    // Far too little allowance for the gas of any call:
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+        allowance: Some(1),
+        receiver_id: contract.id().to_string(),
+        method_names: vec!["set_status".to_string()],
+    });
+    alice
+        .add_key(secret_key.public_key(), permission)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let limited = alice.with_signer(InMemorySigner::from_secret_key(
+        alice.id().clone(),
+        secret_key.clone(),
+    ));
+
+    let err = limited
+        .call(contract.id(), "set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .unwrap_err();
+    let rejected = AccessKeyRejected {
+        account_id: alice.id().clone(),
+        public_key: secret_key.public_key(),
+        reason: KeyRejection::NotEnoughAllowance,
+    };
+    assert_eq!(err, rejected.to_string());
+    assert_eq!(
+        KeyRejection::from_error(&err),
+        Some(KeyRejection::NotEnoughAllowance)
+    );
+
+    let err = limited
+        .call(contract.id(), "get_status")
+        .args_json(serde_json::json!({ "account_id": alice.id() }))
+        .transact()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        KeyRejection::from_error(&err),
+        Some(KeyRejection::MethodNameMismatch)
+    );
+
+    // Signed with the full access key instead, as a wallet would:
+    limited
+        .call(contract.id(), "set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .fallback_signer(alice.signer())
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
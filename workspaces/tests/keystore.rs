@@ -0,0 +1,53 @@
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_in_memory_keystore() -> anyhow::Result<()> {
+    let keystore = InMemoryKeyStore::new();
+    let worker = Worker::new(Sandbox::new()?).with_keystore(keystore.clone());
+
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let signer = keystore
+        .load(contract.id())?
+        .expect("key of the dev account");
+    assert_eq!(signer.secret_key, *contract.secret_key());
+
+    // Nothing written to the default keystore directory:
+    let credentials = dirs::home_dir()
+        .unwrap()
+        .join(".near-credentials/sandbox")
+        .join(format!("{}.json", contract.id()));
+    assert!(!credentials.exists());
+
+    let account = worker.account_from_keystore(contract.id().clone())?;
+    assert_eq!(account.id(), contract.id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_env_keystore() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+
+    let keystore = EnvKeyStore::new("WORKSPACES_TEST_KEY_");
+    let name = keystore.var_name(alice.id());
+    assert!(name.starts_with("WORKSPACES_TEST_KEY_ALICE_"));
+    assert!(keystore.load(alice.id())?.is_none());
+
+    std::env::set_var(&name, alice.secret_key().to_string());
+    let worker = worker.with_keystore(keystore);
+    let account = worker.account_from_keystore(alice.id().clone())?;
+    account
+        .transfer_near(worker.bob().await?.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    std::env::set_var(&name, "not a key");
+    assert!(worker.account_from_keystore(alice.id().clone()).is_err());
+    std::env::remove_var(&name);
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use workspaces::{REF_FINANCE, USDT, WRAP_NEAR};
+
+#[test]
+fn test_known_contract_ids_are_valid() {
+    for contract in &[WRAP_NEAR, USDT, REF_FINANCE] {
+        assert_eq!(contract.account_id().to_string(), contract.id);
+        assert!(contract.state_prefixes.contains(&&b"STATE"[..]));
+    }
+}
@@ -0,0 +1,35 @@
+use std::convert::TryInto;
+
+use workspaces::{sign_message, verify_message, InMemorySigner, MessagePayload, Signer};
+
+fn signer() -> InMemorySigner {
+    InMemorySigner::from_seed(
+        "alice.near".to_string().try_into().unwrap(),
+        near_crypto::KeyType::ED25519,
+        "alice.near",
+    )
+}
+
+#[test]
+fn test_sign_and_verify_message() {
+    let signer = signer();
+    let payload = MessagePayload::new("hello", "myapp.com");
+    let signature = sign_message(&signer, &payload);
+
+    assert!(verify_message(&signer.public_key(), &payload, &signature));
+}
+
+#[test]
+fn test_verify_rejects_tampered_message() {
+    let signer = signer();
+    let payload = MessagePayload::new("hello", "myapp.com");
+    let signature = sign_message(&signer, &payload);
+
+    let mut tampered = payload.clone();
+    tampered.recipient = "evil.com".to_string();
+    assert!(!verify_message(&signer.public_key(), &tampered, &signature));
+
+    let mut replayed = payload;
+    replayed.nonce = [0; 32];
+    assert!(!verify_message(&signer.public_key(), &replayed, &signature));
+}
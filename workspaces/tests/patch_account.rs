@@ -0,0 +1,125 @@
+use std::convert::TryInto;
+
+use workspaces::{
+    AccessKey, AccountId, AccountPatch, InMemorySigner, NearBalance, Sandbox, Worker,
+};
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_patch_account_balance() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+
+    let balance = NearBalance::from_near(12345)?;
+    worker
+        .patch_account(alice.id().clone(), AccountPatch::default().balance(balance))
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let info = alice.view_account().await.map_err(anyhow::Error::msg)?;
+    assert_eq!(info.balance, balance);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_clone_account_with_code_and_keys() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let code = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+
+    // An account which never existed in this sandbox, as if cloned from another network:
+    let account_id: AccountId = "cloned.test.near".to_string().try_into().unwrap();
+    let signer =
+        InMemorySigner::from_seed(account_id.clone(), workspaces::KeyType::ED25519, "cloned");
+    worker
+        .patch_account(
+            account_id.clone(),
+            AccountPatch::default().balance(NearBalance::from_near(100)?),
+        )
+        .await
+        .map_err(anyhow::Error::msg)?;
+    worker
+        .patch_access_key(
+            account_id.clone(),
+            signer.public_key.clone(),
+            AccessKey::full_access(),
+        )
+        .await
+        .map_err(anyhow::Error::msg)?;
+    worker
+        .patch_code(account_id.clone(), code.clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    assert_eq!(
+        worker
+            .view_code(account_id.clone())
+            .await
+            .map_err(anyhow::Error::msg)?,
+        code
+    );
+
+    // The patched key can sign for the account:
+    worker
+        .call(
+            &signer,
+            account_id.clone(),
+            account_id,
+            "set_status".to_string(),
+            br#"{"message": "cloned"}"#.to_vec(),
+            None,
+        )
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signer_for_patched_account() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let code = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+
+    // As if imported from another network, with none of its keys known here:
+    let account_id: AccountId = "taken-over.test.near".to_string().try_into().unwrap();
+    worker
+        .patch_account(
+            account_id.clone(),
+            AccountPatch::default().balance(NearBalance::from_near(100)?),
+        )
+        .await
+        .map_err(anyhow::Error::msg)?;
+    worker
+        .patch_code(account_id.clone(), code)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let signer = worker.signer_for(&account_id).await?;
+    worker
+        .call(
+            &signer,
+            account_id.clone(),
+            account_id.clone(),
+            "set_status".to_string(),
+            br#"{"message": "taken over"}"#.to_vec(),
+            None,
+        )
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // The key is found again in the keystore, rather than patching another one:
+    let again = worker.signer_for(&account_id).await?;
+    assert_eq!(again.public_key, signer.public_key);
+    let keys = worker
+        .view_access_key_list(account_id.clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(keys.len(), 1);
+
+    let missing: AccountId = "missing.test.near".to_string().try_into().unwrap();
+    assert!(worker.signer_for(&missing).await.is_err());
+    Ok(())
+}
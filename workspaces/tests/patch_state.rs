@@ -1,7 +1,7 @@
 use serde_json::json;
 
 use workspaces::borsh::{self, BorshDeserialize, BorshSerialize};
-use workspaces::AccountId;
+use workspaces::{AccountId, StatePrefix};
 
 const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
 
@@ -85,6 +85,52 @@ async fn test_patch_state() {
     .await
     .unwrap();
 
-    let status: String = serde_json::from_value(result).unwrap();
+    let status: String = result.json().unwrap();
     assert_eq!(status, "hello world".to_string());
 }
+
+#[workspaces::test(sandbox)]
+async fn test_patch_state_bytes() {
+    let (contract_id, mut status_msg) = view_status_state().await;
+    status_msg.records.push(Record {
+        k: "alice.near".to_string(),
+        v: "hello bytes".to_string(),
+    });
+
+    let _outcome = workspaces::patch_state_bytes(
+        contract_id.clone(),
+        "STATE".to_string(),
+        status_msg.try_to_vec().unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut state_items = workspaces::view_state(contract_id, None).await.unwrap();
+    let state = state_items.remove("STATE").unwrap();
+    assert_eq!(StatusMessage::try_from_slice(&state).unwrap(), status_msg);
+}
+
+#[workspaces::test(sandbox)]
+async fn test_view_state_prefix() {
+    let (contract_id, status_msg) = view_status_state().await;
+
+    let state_items = workspaces::view_state(contract_id.clone(), Some("STATE".into()))
+        .await
+        .unwrap();
+    assert_eq!(state_items.len(), 1);
+    assert_eq!(
+        state_items.get_borsh::<StatusMessage>("STATE").unwrap(),
+        status_msg
+    );
+
+    let prefix = StatePrefix::base64(&base64::encode("STA")).unwrap();
+    let state_items = workspaces::view_state(contract_id.clone(), Some(prefix))
+        .await
+        .unwrap();
+    assert_eq!(state_items.len(), 1);
+
+    let state_items = workspaces::view_state(contract_id, Some(b"missing".into()))
+        .await
+        .unwrap();
+    assert!(state_items.is_empty());
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use futures::future::try_join_all;
+use workspaces::*;
+
+#[tokio::test]
+async fn test_sandbox_pool() -> anyhow::Result<()> {
+    let pool = SandboxPool::new(2).await?;
+    assert_eq!(pool.size(), 2);
+
+    // More cases than sandboxes, so some of them run on a sandbox used by a previous case:
+    let root_keys = try_join_all((0..4).map(|_| async {
+        let worker = pool.lease().await?;
+        let carol_id: AccountId = format!("carol.{}", worker.root_account().id()).parse()?;
+        assert!(worker.view_account(carol_id).await.is_err());
+        worker.carol().await?;
+        // The root keys are generated when a sandbox starts, so they tell sandboxes apart:
+        let root = worker.root_account();
+        Ok::<_, anyhow::Error>(root.secret_key().public_key().to_string())
+    }))
+    .await?;
+
+    let mut distinct = root_keys;
+    distinct.sort_unstable();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_empty_sandbox_pool() {
+    assert!(SandboxPool::new(0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_cancelled_lease() -> anyhow::Result<()> {
+    let pool = SandboxPool::new(1).await?;
+    let carol_id: AccountId = {
+        let worker = pool.lease().await?;
+        worker.carol().await?.id().clone()
+    };
+
+    // Dropped while the sandbox is being reset:
+    let _ = tokio::time::timeout(Duration::from_millis(1), pool.lease()).await;
+
+    let worker = pool.lease().await?;
+    assert!(worker.view_account(carol_id).await.is_err());
+    Ok(())
+}
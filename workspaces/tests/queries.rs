@@ -0,0 +1,109 @@
+use near_primitives::hash::hash;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_view_account_and_code() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let wasm = std::fs::read(STATUS_MSG_WASM_FILEPATH)?;
+
+    let info = worker
+        .view_account(contract.id().clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(info.code_hash, hash(&wasm));
+    assert!(info.used_storage_bytes as usize > wasm.len());
+    assert!(info.balance > NearBalance::from_yoctonear(0));
+
+    assert_eq!(
+        contract.view_code().await.map_err(anyhow::Error::msg)?,
+        wasm
+    );
+
+    let account = worker.dev_create().await?;
+    let info = account.view_account().await.map_err(anyhow::Error::msg)?;
+    assert_eq!(info.code_hash, CryptoHash::default());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_access_key_list() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let account = worker.dev_create().await?;
+
+    let keys = account
+        .view_access_key_list()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].public_key, account.public_key());
+    assert_eq!(
+        keys[0].access_key.permission,
+        AccessKey::full_access().permission
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_block_and_chunk() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+
+    let latest = worker
+        .view_block(BlockReference::Finality(Finality::Final))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let by_height = worker
+        .view_block(BlockReference::BlockId(BlockId::Height(latest.height())))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(by_height.hash(), latest.hash());
+    assert_eq!(by_height.author(), latest.author());
+
+    let chunk = worker
+        .view_chunk(BlockId::Hash(*latest.hash()), 0)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(Some(chunk.hash()), latest.chunk_hashes().next());
+    assert_eq!(chunk.shard_id(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_outcome_block() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let before = worker
+        .view_block(BlockReference::Finality(Finality::Final))
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let result = contract
+        .call("set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let outcome = result.outcomes().next().unwrap();
+    let block = worker
+        .view_block(BlockReference::BlockId(BlockId::Hash(outcome.block_hash)))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(block.height(), outcome.block_height);
+    assert!(block.timestamp() > before.timestamp());
+
+    // The transaction itself is in a chunk of the block it was included in:
+    let chunk = worker
+        .view_chunk(BlockId::Hash(outcome.block_hash), 0)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(chunk
+        .transaction_hashes()
+        .any(|hash| hash == result.transaction_hash()));
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_same_nonce_race() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+    let carol = worker.dev_create().await?;
+    let amount = NearBalance::from_near(1)?;
+
+    let outcome = Race::same_nonce(&alice)
+        .transfer(bob.id(), amount)
+        .transfer(carol.id(), amount)
+        .run()
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    let winner = outcome.assert_single_winner();
+    let loser = 1 - winner;
+    assert!(outcome.result(winner).is_some());
+    assert!(outcome.result(loser).is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_race() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let outcome = Race::concurrent(contract.as_account())
+        .call(
+            contract.id(),
+            "set_status",
+            json!({ "message": "first" }),
+            NearBalance::default(),
+        )
+        .call(
+            contract.id(),
+            "set_status",
+            json!({ "message": "second" }),
+            NearBalance::default(),
+        )
+        .run()
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    // Both got included, as they have different nonces:
+    assert_eq!(outcome.winners().collect::<Vec<_>>(), vec![0, 1]);
+    Ok(())
+}
@@ -0,0 +1,9 @@
+//! Contract crate built by `tests/compile.rs`, with an export only built with the
+//! `integration-test` feature and a tag only known from the environment at build time.
+
+#[no_mangle]
+pub static BUILD_TAG: &str = env!("BUILD_TAG");
+
+#[cfg(feature = "integration-test")]
+#[no_mangle]
+pub extern "C" fn integration_test_hook() {}
@@ -0,0 +1,69 @@
+//! Contract crate built by `tests/events.rs`, emitting NEP-297 events: `emit` logs `first`,
+//! and calls `emit_second` on itself, which logs `second` from another receipt. `nft_mint`
+//! logs the event of a NEP-171 mint, without minting anything. Written against the host
+//! functions directly, so it builds without any dependency.
+
+extern "C" {
+    fn log_utf8(len: u64, ptr: u64);
+    fn current_account_id(register_id: u64);
+    fn register_len(register_id: u64) -> u64;
+    fn read_register(register_id: u64, ptr: u64);
+    fn promise_create(
+        account_id_len: u64,
+        account_id_ptr: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) -> u64;
+}
+
+const EMIT_SECOND_GAS: u64 = 20_000_000_000_000;
+
+fn log(message: &str) {
+    unsafe { log_utf8(message.len() as u64, message.as_ptr() as u64) }
+}
+
+fn log_event(event: &str) {
+    log(&format!(
+        r#"EVENT_JSON:{{"standard":"test","version":"1.0.0","event":"{}"}}"#,
+        event
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn emit() {
+    log_event("first");
+    let method_name = b"emit_second";
+    let amount = 0u128.to_le_bytes();
+    unsafe {
+        current_account_id(0);
+        let mut account_id = vec![0u8; register_len(0) as usize];
+        read_register(0, account_id.as_mut_ptr() as u64);
+        promise_create(
+            account_id.len() as u64,
+            account_id.as_ptr() as u64,
+            method_name.len() as u64,
+            method_name.as_ptr() as u64,
+            0,
+            0,
+            amount.as_ptr() as u64,
+            EMIT_SECOND_GAS,
+        );
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn emit_second() {
+    log_event("second");
+}
+
+#[no_mangle]
+pub extern "C" fn nft_mint() {
+    log(concat!(
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","#,
+        r#""data":[{"owner_id":"alice.test.near","token_ids":["0","1"]}]}"#
+    ));
+}
@@ -0,0 +1,51 @@
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[test]
+fn test_view_result_schema() {
+    let result = ViewResult {
+        result: b"\"hello\"".to_vec(),
+        logs: vec!["log".to_string()],
+    };
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        json!({ "result": base64::encode(b"\"hello\""), "logs": ["log"] })
+    );
+}
+
+#[tokio::test]
+async fn test_execution_result_schema() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let result = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let value = serde_json::to_value(&result)?;
+
+    assert_eq!(
+        value["transaction_hash"],
+        result.transaction_hash().to_string()
+    );
+    assert_eq!(value["status"]["type"], "success");
+    assert_eq!(value["total_gas_burnt"], result.total_gas_burnt());
+    assert_eq!(
+        value["total_cost"],
+        result.total_cost().to_yoctonear().to_string()
+    );
+    assert_eq!(
+        value["transaction_outcome"]["executor_id"],
+        contract.id().to_string()
+    );
+    assert_eq!(
+        value["receipts"].as_array().map(Vec::len),
+        Some(result.receipts().len())
+    );
+
+    Ok(())
+}
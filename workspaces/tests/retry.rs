@@ -0,0 +1,154 @@
+mod common;
+
+use std::convert::TryInto;
+use std::time::Duration;
+
+use common::Proxy;
+use workspaces::*;
+
+fn fixed(initial_delay: Duration, max_delay: Duration) -> RetryPolicy {
+    RetryPolicy::default()
+        .initial_delay(initial_delay)
+        .max_delay(max_delay)
+        .jitter(false)
+}
+
+#[test]
+fn test_delay_grows_exponentially() {
+    let policy = fixed(Duration::from_millis(100), Duration::from_secs(60));
+    let delays: Vec<Duration> = (1..=4).map(|attempt| policy.delay(attempt)).collect();
+    let expected: Vec<Duration> = vec![100, 200, 400, 800]
+        .into_iter()
+        .map(Duration::from_millis)
+        .collect();
+    assert_eq!(delays, expected);
+}
+
+#[test]
+fn test_delay_is_capped() {
+    let policy = fixed(Duration::from_millis(100), Duration::from_millis(300));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(3), Duration::from_millis(300));
+    // Past the point where doubling overflows:
+    assert_eq!(policy.delay(100), Duration::from_millis(300));
+    assert_eq!(policy.delay(usize::MAX), Duration::from_millis(300));
+}
+
+#[test]
+fn test_delay_jitter_bounds() {
+    let policy = fixed(Duration::from_millis(100), Duration::from_millis(1000)).jitter(true);
+    for attempt in 1..=6 {
+        let full = fixed(Duration::from_millis(100), Duration::from_millis(1000)).delay(attempt);
+        for _ in 0..100 {
+            let delay = policy.delay(attempt);
+            // Give or take the rounding of the float factor:
+            let rounding = Duration::from_micros(1);
+            assert!(
+                full / 2 <= delay + rounding && delay <= full + rounding,
+                "{:?} out of bounds",
+                delay
+            );
+        }
+    }
+}
+
+#[test]
+fn test_max_attempts() {
+    assert_eq!(RetryPolicy::default().max_attempts(3).attempts(), 3);
+    // At least one attempt is made:
+    assert_eq!(RetryPolicy::default().max_attempts(0).attempts(), 1);
+}
+
+#[tokio::test]
+async fn test_failing_call_is_retried() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let proxy = Proxy::start(sandbox.rpc_addr(), true).await?;
+    let root = InMemorySigner::from_file(&sandbox.home_dir().join("validator_key.json"));
+    let keystore = sandbox.home_dir().join("retry-credentials");
+    let worker = Worker::custom(proxy.url(), "localnet", Some(root), &keystore);
+    let account = worker.dev_create().await?;
+    let receiver: AccountId = "test.near".to_string().try_into()?;
+    let amount = NearBalance::from_near(1)?;
+
+    worker.set_method_retry_policy(
+        "broadcast_tx_commit",
+        fixed(Duration::from_millis(10), Duration::from_millis(10)).max_attempts(3),
+    );
+    worker.reset_metrics();
+    proxy.drop_requests("broadcast_tx_commit", 2);
+    account
+        .transfer_near(&receiver, amount)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    let metrics = worker.metrics();
+    let broadcasts = metrics.method("broadcast_tx_commit").unwrap();
+    assert_eq!((broadcasts.requests, broadcasts.errors), (3, 2));
+
+    // Giving up once out of attempts:
+    worker.reset_metrics();
+    proxy.drop_requests("broadcast_tx_commit", 3);
+    assert!(account.transfer_near(&receiver, amount).await.is_err());
+    let metrics = worker.metrics();
+    let broadcasts = metrics.method("broadcast_tx_commit").unwrap();
+    assert_eq!((broadcasts.requests, broadcasts.errors), (3, 3));
+
+    Ok(())
+}
+
+/// Runs `retry_idempotent` with an op failing with `make_err` for its first `failures` calls,
+/// returning the result and how many times the op was called.
+async fn count_idempotent_attempts(
+    failures: usize,
+    make_err: impl Fn() -> anyhow::Error,
+) -> (anyhow::Result<usize>, usize) {
+    let policy = fixed(Duration::from_millis(1), Duration::from_millis(1)).max_attempts(3);
+    let mut calls = 0;
+    let result = retry_idempotent(&policy, || {
+        calls += 1;
+        let result = if calls <= failures {
+            Err(make_err())
+        } else {
+            Ok(calls)
+        };
+        async move { result }
+    })
+    .await;
+    (result, calls)
+}
+
+#[tokio::test]
+async fn test_retry_idempotent() -> anyhow::Result<()> {
+    let account_id: AccountId = "taken.test.near".to_string().try_into()?;
+
+    // Transient errors are retried, until the attempts run out:
+    let (result, calls) = count_idempotent_attempts(2, || anyhow::anyhow!("flaky")).await;
+    assert_eq!(result?, 3);
+    assert_eq!(calls, 3);
+    let (result, calls) = count_idempotent_attempts(3, || anyhow::anyhow!("flaky")).await;
+    assert!(result.is_err());
+    assert_eq!(calls, 3);
+
+    // Errors a retry can't fix are returned right away:
+    let (result, calls) = count_idempotent_attempts(1, || {
+        AccountAlreadyExists {
+            account_id: account_id.clone(),
+        }
+        .into()
+    })
+    .await;
+    assert!(result.unwrap_err().is::<AccountAlreadyExists>());
+    assert_eq!(calls, 1);
+    let (result, calls) = count_idempotent_attempts(1, || {
+        HelperError::InvalidAccountId {
+            account_id: account_id.clone(),
+            message: "invalid".to_string(),
+        }
+        .into()
+    })
+    .await;
+    assert!(result.unwrap_err().is::<HelperError>());
+    assert_eq!(calls, 1);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use workspaces::{Sandbox, Worker};
+
+#[test]
+fn test_sandbox_home_dirs_are_unique() -> anyhow::Result<()> {
+    let first = Sandbox::new()?;
+    let second = Sandbox::new()?;
+
+    assert_ne!(first.home_dir(), second.home_dir());
+    assert!(first.home_dir().starts_with(std::env::temp_dir()));
+    assert!(first.home_dir().join("validator_key.json").exists());
+
+    let home_dir = first.home_dir().to_path_buf();
+    drop(first);
+    assert!(!home_dir.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_parallel_sandboxes() -> anyhow::Result<()> {
+    let handles: Vec<_> = (0..4).map(|_| std::thread::spawn(Sandbox::new)).collect();
+    let sandboxes = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut addrs: Vec<_> = sandboxes.iter().map(|sandbox| sandbox.rpc_addr()).collect();
+    addrs.sort_unstable();
+    addrs.dedup();
+    assert_eq!(addrs.len(), sandboxes.len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sandbox_version() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let version = worker.sandbox_version().await?;
+
+    assert!(version.binary.unwrap().exists());
+    assert!(version.protocol_version <= version.latest_protocol_version);
+    assert!(version.at_least("1.0.0"));
+    assert!(!version.at_least("1000.0.0"));
+    println!("{}", version);
+
+    Ok(())
+}
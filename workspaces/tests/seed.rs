@@ -0,0 +1,8 @@
+// The run seed is fixed once per process, so this file holds a single test setting it.
+#[test]
+fn test_run_seed_from_env() {
+    std::env::set_var("NEAR_WORKSPACES_SEED", "42");
+    assert_eq!(workspaces::run_seed(), 42);
+    std::env::set_var("NEAR_WORKSPACES_SEED", "43");
+    assert_eq!(workspaces::run_seed(), 42);
+}
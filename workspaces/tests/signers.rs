@@ -0,0 +1,52 @@
+use serde_json::json;
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_function_call_key_signer() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let key = InMemorySigner::from_seed(contract.id().clone(), KeyType::ED25519, "set-status");
+    let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+        allowance: None,
+        receiver_id: contract.id().to_string(),
+        method_names: vec!["set_status".to_string()],
+    });
+    contract
+        .as_account()
+        .add_key(key.public_key(), permission)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // The contract's own account, signing with the function call key instead:
+    let restricted = contract.as_account().with_signer(key);
+    assert_eq!(restricted.id(), contract.id());
+    restricted
+        .call(contract.id(), "set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // Other methods are only callable with the full access key:
+    let args = json!({ "account_id": contract.id() });
+    assert!(restricted
+        .call(contract.id(), "get_status")
+        .args_json(args.clone())
+        .transact()
+        .await
+        .is_err());
+    contract
+        .call("get_status")
+        .args_json(args)
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use workspaces::*;
+
+#[tokio::test]
+async fn test_snapshot_and_restore() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+    let balance = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+
+    let snapshot = worker.snapshot().await?;
+    // The node is back up with its state intact:
+    assert_eq!(
+        bob.view_balance().await.map_err(anyhow::Error::msg)?,
+        balance
+    );
+
+    // Restoring twice from the same snapshot, as between test cases:
+    for _ in 0..2 {
+        alice
+            .transfer_near(bob.id(), NearBalance::from_near(1)?)
+            .await
+            .map_err(anyhow::Error::msg)?
+            .assert_success();
+        let carol = worker.carol().await?;
+        assert_ne!(
+            bob.view_balance().await.map_err(anyhow::Error::msg)?,
+            balance
+        );
+
+        worker.restore(&snapshot).await?;
+        assert_eq!(
+            bob.view_balance().await.map_err(anyhow::Error::msg)?,
+            balance
+        );
+        assert!(worker.view_account(carol.id().clone()).await.is_err());
+    }
+
+    let other = Worker::new(Sandbox::new()?);
+    assert!(other.restore(&snapshot).await.is_err());
+
+    Ok(())
+}
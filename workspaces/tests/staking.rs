@@ -0,0 +1,20 @@
+use workspaces::{Delegation, NearBalance};
+
+#[test]
+fn test_delegation_rewards() -> anyhow::Result<()> {
+    let before = Delegation {
+        staked: NearBalance::from_near(100)?,
+        unstaked: NearBalance::from_near(5)?,
+        can_withdraw: true,
+    };
+    let after = Delegation {
+        staked: NearBalance::from_near(102)?,
+        ..before
+    };
+
+    assert_eq!(after.total(), NearBalance::from_near(107)?);
+    assert_eq!(after.rewards_since(&before), NearBalance::from_near(2)?);
+    // Slashing or a withdrawal in between isn't a negative reward:
+    assert_eq!(before.rewards_since(&after), NearBalance::from_near(0)?);
+    Ok(())
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "standards")]
+
+use serde_json::json;
+
+use workspaces::standards::{contract_source_metadata, ContractSourceMetadata, NonFungibleToken};
+use workspaces::{NearBalance, Sandbox, Worker};
+
+const NFT_WASM_FILEPATH: &str = "../examples/res/non_fungible_token.wasm";
+
+#[tokio::test]
+async fn test_nft_mint_and_transfer() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker
+        .dev_deploy(std::fs::read(NFT_WASM_FILEPATH)?.as_slice())
+        .await?;
+    contract
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let nft = NonFungibleToken::new(&contract);
+    assert_eq!(nft.nft_token("0").await?, None);
+
+    nft.nft_mint(
+        contract.as_account(),
+        "0",
+        contract.id(),
+        json!({ "title": "Olympus Mons" }),
+        NearBalance::from_millinear(10)?,
+    )
+    .await?
+    .assert_success();
+
+    let token = nft.nft_token("0").await?.expect("token was minted");
+    assert_eq!(&token.owner_id, contract.id());
+    assert_eq!(token.metadata.unwrap()["title"], "Olympus Mons");
+
+    let alice = worker.dev_create().await?;
+    nft.nft_transfer(contract.as_account(), alice.id(), "0", None)
+        .await?
+        .assert_success();
+    assert_eq!(&nft.nft_token("0").await?.unwrap().owner_id, alice.id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nft_metadata() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(NFT_WASM_FILEPATH).await?;
+    contract
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let metadata = NonFungibleToken::new(&contract).nft_metadata().await?;
+    assert_eq!(metadata.spec, "nft-1.0.0");
+    assert_eq!(metadata.symbol, "EXAMPLE");
+    assert_eq!(metadata.base_uri, None);
+
+    // The example contract predates NEP-330:
+    assert!(contract_source_metadata(&worker, contract.id())
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_contract_source_metadata_assertions() -> anyhow::Result<()> {
+    let metadata: ContractSourceMetadata = serde_json::from_value(json!({
+        "version": "1.2.0",
+        "link": "https://github.com/near/example/tree/4c1a2f9",
+        "standards": [{ "standard": "nep141", "version": "1.0.0" }],
+    }))?;
+    metadata.assert_version("1.2.0");
+    metadata.assert_commit("4c1a2f9");
+    assert_eq!(metadata.standard_version("nep141"), Some("1.0.0"));
+    assert_eq!(metadata.standard_version("nep171"), None);
+
+    // Metadata of NEP-330 1.0.0 has no standards:
+    let metadata: ContractSourceMetadata =
+        serde_json::from_value(json!({ "version": null, "link": null }))?;
+    assert!(metadata.standards.is_empty());
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use workspaces::borsh::BorshSerialize;
+use workspaces::StateRecords;
+
+fn records() -> StateRecords {
+    let mut entries = vec![
+        (b"STATE".to_vec(), vec![1]),
+        (b"m".to_vec(), vec![2]),
+        (vec![0xff, 0x00], vec![3]),
+    ];
+    for (k, v) in &[(1u32, 10u64), (2, 20)] {
+        let mut key = b"m".to_vec();
+        key.extend(k.try_to_vec().unwrap());
+        entries.push((key, v.try_to_vec().unwrap()));
+    }
+    entries.into_iter().collect()
+}
+
+#[test]
+fn test_state_records_ordering_and_prefix() {
+    let records = records();
+    assert_eq!(records.len(), 5);
+
+    let keys: Vec<_> = records.iter().map(|(k, _)| k.to_vec()).collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+
+    assert_eq!(records.prefix(b"m").count(), 3);
+    assert_eq!(records.after(b"m").count(), 3);
+    assert_eq!(records.get(b"STATE"), Some(&[1u8][..]));
+    assert_eq!(records.get([0xffu8, 0x00]), Some(&[3u8][..]));
+}
+
+#[test]
+fn test_state_records_decoding() {
+    let records = records();
+
+    // Only the `[0xff, 0x00]` key is not valid UTF-8:
+    let utf8_keys: Vec<_> = records.utf8_keyed().map(|(k, _)| k).collect();
+    assert_eq!(utf8_keys.len(), 4);
+    assert!(utf8_keys.contains(&"STATE"));
+
+    let mut entries = records.clone();
+    entries.remove(b"m");
+    let decoded: Vec<(u32, u64)> = entries.decode_prefixed(b"m").unwrap();
+    assert_eq!(decoded, vec![(1, 10), (2, 20)]);
+
+    // The bare prefix key has no borsh-encoded u32 behind it:
+    assert!(records.decode_prefixed::<u32, u64>(b"m").is_err());
+}
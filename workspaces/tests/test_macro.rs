@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use workspaces::*;
+
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[workspaces::test(sandbox, timeout = "120s")]
+async fn test_worker_argument(worker: Worker<Sandbox>) -> anyhow::Result<()> {
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    contract
+        .call("set_status")
+        .args_json(serde_json::json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[workspaces::test(sandbox, flaky_retries = 2)]
+async fn test_flaky_retries(worker: Worker<Sandbox>) -> anyhow::Result<()> {
+    worker.dev_create().await?;
+    // Fails the first run only, as a flaky test would:
+    if RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+        anyhow::bail!("flaky failure");
+    }
+    Ok(())
+}
+
+#[workspaces::test(sandbox, timeout = "10ms")]
+#[should_panic(expected = "test timed out after")]
+async fn test_timeout(worker: Worker<Sandbox>) {
+    worker.dev_create().await.unwrap();
+}
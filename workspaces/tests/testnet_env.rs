@@ -0,0 +1,42 @@
+use workspaces::*;
+
+// A single test, since the environment is shared by the tests of this file.
+#[test]
+fn test_invalid_testnet_env() {
+    std::env::set_var(
+        "NEAR_TESTNET_HELPER_URLS",
+        "https://helper.testnet.near.org, not a url",
+    );
+    let err = Testnet::new().err().expect("invalid helper URL");
+    assert!(
+        err.to_string().contains("NEAR_TESTNET_HELPER_URLS"),
+        "{}",
+        err
+    );
+    std::env::remove_var("NEAR_TESTNET_HELPER_URLS");
+
+    std::env::set_var(
+        "NEAR_TESTNET_ROOT_ACCOUNT_KEY_FILE",
+        "/nonexistent/root.json",
+    );
+    let err = Testnet::new().err().expect("missing root account key file");
+    assert!(
+        err.to_string().contains("/nonexistent/root.json"),
+        "{}",
+        err
+    );
+    std::env::remove_var("NEAR_TESTNET_ROOT_ACCOUNT_KEY_FILE");
+
+    let key_file = std::env::temp_dir().join("workspaces-invalid-funder.json");
+    std::fs::write(&key_file, "{}").unwrap();
+    std::env::set_var("NEAR_TESTNET_FUNDER_KEY_FILE", &key_file);
+    let err = Testnet::new().err().expect("invalid funder key file");
+    assert!(
+        err.to_string().contains("workspaces-invalid-funder.json"),
+        "{}",
+        err
+    );
+    std::env::remove_var("NEAR_TESTNET_FUNDER_KEY_FILE");
+
+    assert!(Testnet::new().is_ok());
+}
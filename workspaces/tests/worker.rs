@@ -0,0 +1,1123 @@
+use serde_json::json;
+use std::convert::TryInto;
+use std::time::Duration;
+use workspaces::*;
+
+const NFT_WASM_FILEPATH: &str = "../examples/res/non_fungible_token.wasm";
+const STATUS_MSG_WASM_FILEPATH: &str = "../examples/res/status_message.wasm";
+
+#[tokio::test]
+async fn test_sandbox_constructor() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    assert_eq!(worker.name(), "sandbox");
+
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_without_runtime_context() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(NFT_WASM_FILEPATH).await?;
+
+    contract
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let metadata: serde_json::Value = contract
+        .view("nft_metadata")
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(metadata["spec"], "nft-1.0.0");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_workers_are_independent() -> anyhow::Result<()> {
+    let first = Worker::new(Sandbox::new()?);
+    let second = Worker::new(Sandbox::new()?);
+
+    let account = first.dev_create().await?;
+    assert!(account.view_account().await.is_ok());
+    assert!(second
+        .display_account_info(account.id().clone())
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_call_from_another_account() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let alice = worker.dev_create().await?;
+
+    alice
+        .call(contract.id(), "set_status")
+        .args_json(json!({ "message": "hello from alice" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let status: String = contract
+        .view("get_status")
+        .args_json(json!({ "account_id": alice.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "hello from alice");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execution_result_outcomes() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let result = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(result.is_success());
+    assert!(result.failures().is_empty());
+    assert!(!result.receipts().is_empty());
+    assert_eq!(
+        result.total_gas_burnt(),
+        result
+            .outcomes()
+            .map(|outcome| outcome.gas_burnt)
+            .sum::<u64>()
+    );
+
+    let result = contract
+        .call("no_such_method")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(!result.is_success());
+    assert!(result.raw_value().is_err());
+    let failures = result.failures();
+    assert!(!failures.is_empty());
+    assert!(failures.iter().all(|outcome| outcome.failure().is_some()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execution_result_raw() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let result = set_status(&contract, "hello").await?;
+    let raw = result.raw();
+    assert_eq!(&raw.transaction.signer_id, contract.id());
+    assert_eq!(&raw.transaction.hash, result.transaction_hash());
+    assert_eq!(raw.receipts_outcome.len(), result.receipts().len());
+
+    let raw: FinalExecutionOutcomeView = result.clone().into_inner();
+    assert_eq!(&raw, result.raw());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gas_profile() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let result = set_status(&contract, "hello").await?;
+    let profile = result.gas_profile();
+    assert_eq!(profile.total_gas_burnt, result.total_gas_burnt());
+    assert_eq!(profile.outcomes.len(), result.receipts().len() + 1);
+
+    let call = &profile.outcomes[1];
+    assert_eq!(&call.executor_id, contract.id());
+    assert!(!call.costs.is_empty());
+    assert!(call.costs.values().sum::<u64>() <= call.gas_burnt);
+    assert!(profile.costs.values().sum::<u64>() <= profile.total_gas_burnt);
+
+    let report = profile.to_string();
+    assert!(report.starts_with(&format!("gas burnt: {}", profile.total_gas_burnt)));
+    assert!(report.contains(contract.id().as_str()));
+
+    Ok(())
+}
+
+async fn set_status(contract: &Contract, message: &str) -> anyhow::Result<ExecutionResult> {
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": message }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)
+}
+
+#[tokio::test]
+async fn test_execution_result_diff() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let first = set_status(&contract, "hello").await?;
+    let second = set_status(&contract, "hello").await?;
+    assert!(first.diff(&second).is_same_behavior());
+
+    let failed = contract
+        .call("no_such_method")
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let diff = first.diff(&failed);
+    assert!(!diff.is_same_behavior());
+    assert!(diff.status.is_some());
+    assert!(diff.to_string().starts_with("status: "));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_named_test_accounts() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let root = worker.root_account();
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+
+    assert_eq!(alice.id().to_string(), format!("alice.{}", root.id()));
+    assert!(
+        alice
+            .view_account()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .balance
+            >= NearBalance::from_near(99)?
+    );
+
+    alice
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // Asking again hands back the same, already funded account:
+    let alice_again = worker.alice().await?;
+    assert_eq!(alice_again.signer().public_key, alice.signer().public_key);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_subaccount() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+
+    let factory = alice
+        .create_subaccount("factory")
+        .initial_balance(NearBalance::from_near(5)?)
+        .transact()
+        .await?;
+    assert_eq!(factory.id().to_string(), format!("factory.{}", alice.id()));
+
+    let secret_key = SecretKey::from_seed(KeyType::ED25519, "token");
+    let token = factory
+        .create_subaccount("token")
+        .keys(secret_key.clone())
+        .transact()
+        .await?;
+    assert_eq!(token.signer().public_key(), secret_key.public_key());
+    assert!(token.view_account().await.is_ok());
+
+    let err = alice
+        .create_subaccount("factory")
+        .transact()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AccountAlreadyExists>()
+            .map(|err| &err.account_id),
+        Some(factory.id())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_top_level_account_already_exists() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let account = worker.dev_create().await?;
+
+    let err = worker
+        .create_top_level_account(account.id().clone(), account.public_key())
+        .await
+        .unwrap_err();
+    let err = err
+        .downcast::<AccountAlreadyExists>()
+        .expect("a typed error for the taken id");
+    assert_eq!(&err.account_id, account.id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_account_zero_balance() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let creator = worker.dev_create().await?;
+    let new_account_id: AccountId = format!("zero.{}", creator.id()).try_into()?;
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+
+    let result = worker
+        .create_account_zero_balance(
+            creator.signer(),
+            creator.id().clone(),
+            new_account_id.clone(),
+            secret_key.public_key(),
+        )
+        .await;
+    // Which of these we get depends on the protocol version the sandbox runs:
+    match result {
+        Ok(result) => {
+            result.assert_success();
+            let info = worker
+                .view_account(new_account_id)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            assert_eq!(info.balance, NearBalance::default());
+        }
+        Err(err) => {
+            let expected = format!(
+                "protocol does not allow creating {} with zero balance: ",
+                new_account_id
+            );
+            assert!(err.to_string().starts_with(&expected), "{:?}", err);
+            assert!(worker.view_account(new_account_id).await.is_err());
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_only_worker() -> anyhow::Result<()> {
+    // Only queries are available on a read-only worker; `call`, `transfer_near` and the
+    // like don't compile on it.
+    let worker = Worker::new(ReadOnly::new(Sandbox::new()?));
+    assert_eq!(worker.name(), "sandbox");
+
+    let root_id: AccountId = "test.near".to_string().try_into().unwrap();
+    let info = worker
+        .display_account_info(root_id)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(info.balance > NearBalance::default());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_costs() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+
+    let before = alice.view_account().await.map_err(anyhow::Error::msg)?;
+    let amount = NearBalance::from_near(1)?;
+    let result = alice
+        .transfer_near(bob.id(), amount)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let after = alice.view_account().await.map_err(anyhow::Error::msg)?;
+
+    assert!(result
+        .outcomes()
+        .all(|outcome| outcome.gas_price().is_some()));
+    assert_eq!(
+        before.balance.to_yoctonear() - after.balance.to_yoctonear(),
+        amount.to_yoctonear() + result.total_cost().to_yoctonear()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_top_up() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let threshold = NearBalance::from_near(5)?;
+    let amount = NearBalance::from_near(10)?;
+    worker.auto_top_up(threshold, amount);
+    let account = worker.dev_create().await?;
+
+    // Drain the account down to a single NEAR, well below the threshold:
+    let balance = account.view_balance().await.map_err(anyhow::Error::msg)?;
+    let drained = NearBalance::from_yoctonear(
+        balance.to_yoctonear() - NearBalance::from_near(1)?.to_yoctonear(),
+    );
+    account
+        .transfer_near(worker.root_account().id(), drained)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // Topped up before the transfer returned, minus the gas it burnt:
+    let balance = account.view_balance().await.map_err(anyhow::Error::msg)?;
+    assert!(balance >= amount, "{} after the top-up", balance);
+    assert!(
+        balance <= NearBalance::from_near(11)?,
+        "{} after the top-up",
+        balance
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receipt_block_inclusion() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+
+    let result = alice
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    // The transfer is converted into a receipt, which executes on bob in a later block:
+    let transfer = result.outcomes_for(bob.id())[0];
+    transfer.assert_later_block(result.transaction_outcome());
+    assert_eq!(transfer.blocks_after(result.transaction_outcome()), Some(1));
+    assert!(result.block_span() >= 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fast_forward() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let root_id = worker.root_account().id().clone();
+
+    let before = worker
+        .display_account_info(root_id.clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    worker.fast_forward(100).await.map_err(anyhow::Error::msg)?;
+    let after = worker
+        .display_account_info(root_id)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    assert!(after.block_height >= before.block_height + 100);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancelled_fast_forward_completes() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let root_id = worker.root_account().id().clone();
+    // Detects support for fast-forwarding up front, so the cancelled one starts right away:
+    worker.fast_forward(1).await.map_err(anyhow::Error::msg)?;
+
+    let before = worker
+        .display_account_info(root_id.clone())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let cancelled = tokio::time::timeout(Duration::from_millis(1), worker.fast_forward(1000)).await;
+    assert!(cancelled.is_err());
+
+    // Waits for the cancelled fast-forward to be done first:
+    worker.fast_forward(1).await.map_err(anyhow::Error::msg)?;
+    let after = worker
+        .display_account_info(root_id)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(after.block_height >= before.block_height + 1001);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_limit_concurrency() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    worker.limit_concurrency(Some(1), Some(1));
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let accounts = futures::future::try_join_all((0..4).map(|_| worker.dev_create())).await?;
+    let calls = accounts.iter().map(|account| {
+        account
+            .call(contract.id(), "set_status")
+            .args_json(json!({ "message": account.id() }))
+            .transact()
+    });
+    for result in futures::future::join_all(calls).await {
+        result.map_err(anyhow::Error::msg)?.assert_success();
+    }
+
+    for account in &accounts {
+        let status: String = contract
+            .view("get_status")
+            .args_json(json!({ "account_id": account.id() }))
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()?;
+        assert_eq!(status, account.id().to_string());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_policy() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    worker.set_retry_policy(
+        RetryPolicy::default()
+            .max_attempts(20)
+            .initial_delay(std::time::Duration::from_millis(50))
+            .jitter(false),
+    );
+    worker.set_method_retry_policy(
+        "broadcast_tx_commit",
+        RetryPolicy::default().max_attempts(1),
+    );
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    // Transactions are only reported once final, so their effects are visible right away:
+    set_status(&contract, "hello").await?.assert_success();
+    let status: String = contract
+        .view("get_status")
+        .args_json(json!({ "account_id": contract.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transact_async() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let mut pending = Vec::new();
+    for message in &["first", "second", "third"] {
+        let status = contract
+            .call("set_status")
+            .args_json(json!({ "message": message }))
+            .transact_async()
+            .await
+            .map_err(anyhow::Error::msg)?;
+        pending.push(status);
+    }
+
+    let hashes: std::collections::HashSet<_> = pending.iter().map(|s| *s.hash()).collect();
+    assert_eq!(hashes.len(), pending.len());
+    for status in pending {
+        let hash = *status.hash();
+        let result = status.wait().await.map_err(anyhow::Error::msg)?;
+        result.assert_success();
+        assert_eq!(result.transaction_hash(), &hash);
+    }
+
+    let status: String = contract
+        .view("get_status")
+        .args_json(json!({ "account_id": contract.id() }))
+        .fetch()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .json()?;
+    assert_eq!(status, "third");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transaction_replacement() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let nonce = || async {
+        let keys = contract
+            .view_access_key_list()
+            .await
+            .map_err(anyhow::Error::msg)?;
+        Ok::<_, anyhow::Error>(keys[0].access_key.nonce)
+    };
+    let nonce_before = nonce().await?;
+
+    let status = contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact_async()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    let replacement = status.replace().await.map_err(anyhow::Error::msg)?;
+    let hashes = [*status.hash(), *replacement.hash()];
+    assert_ne!(hashes[0], hashes[1]);
+
+    let result = replacement
+        .wait_with_replacement(ReplacementPolicy::default())
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+    assert!(hashes.contains(result.transaction_hash()));
+    // Both share a nonce, so only one of them was executed:
+    assert_eq!(nonce().await?, nonce_before + 1);
+    assert!(!status
+        .is_stuck(&ReplacementPolicy::default())
+        .await
+        .map_err(anyhow::Error::msg)?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_account() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let bob = worker.bob().await?;
+    let contract_id = contract.id().clone();
+
+    let balance = contract.view_balance().await.map_err(anyhow::Error::msg)?;
+    let bob_before = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+    contract
+        .delete_account(bob.id())
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    assert!(worker.view_account(contract_id).await.is_err());
+    // The deleting account pays for the transaction out of the balance sent to bob:
+    bob.view_balance()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_increased_by(bob_before, balance, NearBalance::from_millinear(1)?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_raw_result() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let result = worker
+        .view(
+            contract.id().clone(),
+            "get_status".to_string(),
+            json!({ "account_id": contract.id() })
+                .to_string()
+                .into_bytes()
+                .into(),
+        )
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(result.result, br#""hello""#.to_vec());
+    assert_eq!(result.json::<String>()?, "hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_call_gas() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let set_status = |gas: Gas| {
+        contract
+            .call("set_status")
+            .args_json(json!({ "message": "hello" }))
+            .gas(gas)
+            .transact()
+    };
+
+    // Too little gas to even load the contract:
+    let result = set_status(Gas::from_ggas(1))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(!result.is_success());
+
+    let result = set_status(Gas::from_tgas(5))
+        .await
+        .map_err(anyhow::Error::msg)?;
+    result.assert_success();
+
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .max_gas()
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_balance_assertions() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+    let alice_before = alice.view_balance().await.map_err(anyhow::Error::msg)?;
+    let bob_before = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+
+    let amount = NearBalance::from_near(2)?;
+    alice
+        .transfer_near(bob.id(), amount)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // Alice also paid for the gas of the transfer:
+    let tolerance = NearBalance::from_millinear(1)?;
+    let alice_after = alice.view_balance().await.map_err(anyhow::Error::msg)?;
+    alice_after.assert_decreased_by(alice_before, amount, tolerance);
+    assert!(alice_after < alice_before.checked_sub(amount).unwrap());
+    let bob_after = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+    bob_after.assert_increased_by(bob_before, amount, NearBalance::default());
+
+    let result = std::panic::catch_unwind(|| {
+        bob_after.assert_increased_by(bob_before, NearBalance::from_near(1).unwrap(), tolerance)
+    });
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_account_credentials() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    // As if written by a setup process and read back by a test process:
+    let json = serde_json::to_string(&contract.credentials())?;
+    let credentials: AccountCredentials = serde_json::from_str(&json)?;
+    assert_eq!(credentials, contract.credentials());
+    assert!(!format!("{:?}", credentials).contains(&contract.secret_key().to_string()));
+
+    let contract = worker.contract_from_credentials(credentials);
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_worker_from_spawned_tasks() -> anyhow::Result<()> {
+    // No `#[workspaces::test]` here: workers carry their own connection, so they work from
+    // a plain tokio test, with their futures spawned onto any of its threads.
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let tasks: Vec<_> = (0..3)
+        .map(|i| {
+            let worker = worker.clone();
+            let contract = contract.clone();
+            tokio::spawn(async move {
+                let account = worker.dev_create().await?;
+                account
+                    .call(contract.id(), "set_status")
+                    .args_json(json!({ "message": format!("hello {}", i) }))
+                    .transact()
+                    .await
+                    .map_err(anyhow::Error::msg)?
+                    .assert_success();
+                Ok::<_, anyhow::Error>(account)
+            })
+        })
+        .collect();
+    for task in tasks {
+        let account = task.await??;
+        let message: String = contract
+            .view("get_status")
+            .args_json(json!({ "account_id": account.id() }))
+            .fetch()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .json()?;
+        assert!(message.starts_with("hello "));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assert_no_state_change() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    let set_status = |message: &str| {
+        contract
+            .call("set_status")
+            .args_json(json!({ "message": message }))
+            .transact()
+    };
+    set_status("hello").await.map_err(anyhow::Error::msg)?;
+
+    worker
+        .assert_no_state_change(contract.id().clone(), async {
+            contract
+                .view("get_status")
+                .args_json(json!({ "account_id": contract.id() }))
+                .fetch()
+                .await
+        })
+        .await?
+        .map_err(anyhow::Error::msg)?;
+
+    // A rejected call leaves the state alone:
+    let result = worker
+        .assert_no_state_change(contract.id().clone(), async {
+            contract.call("no_such_method").transact().await
+        })
+        .await?
+        .map_err(anyhow::Error::msg)?;
+    assert!(!result.is_success());
+
+    let err = worker
+        .assert_no_state_change(contract.id().clone(), set_status("bye"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("changed unexpectedly"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rpc_metrics() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+    worker.reset_metrics();
+    assert_eq!(worker.metrics(), RpcMetrics::default());
+
+    contract
+        .call("set_status")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    assert!(worker
+        .display_account_info(contract.id().clone())
+        .await
+        .is_ok());
+
+    let metrics = worker.metrics();
+    let broadcast = metrics.method("broadcast_tx_commit").unwrap();
+    assert_eq!(broadcast.requests, 1);
+    assert_eq!(broadcast.errors, 0);
+    assert!(broadcast.max_latency > std::time::Duration::default());
+    assert!(metrics.method("query").unwrap().requests >= 2);
+    assert!(metrics.total_latency() >= broadcast.total_latency);
+
+    let prometheus = metrics.to_prometheus();
+    assert!(prometheus.contains("workspaces_rpc_requests_total{method=\"broadcast_tx_commit\"} 1"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_transactions_from_one_account() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+    let before = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+
+    let results = futures::future::try_join_all(
+        (0..5).map(|_| alice.transfer_near(bob.id(), NearBalance::from_near(1).unwrap())),
+    )
+    .await
+    .map_err(anyhow::Error::msg)?;
+    for result in results {
+        result.assert_success();
+    }
+
+    let after = bob.view_balance().await.map_err(anyhow::Error::msg)?;
+    assert_eq!(
+        after.to_yoctonear() - before.to_yoctonear(),
+        NearBalance::from_near(5)?.to_yoctonear()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nonce_resync_after_external_transactions() -> anyhow::Result<()> {
+    let sandbox = Sandbox::new()?;
+    let rpc_addr = sandbox.rpc_addr().to_string();
+    let keystore = sandbox.home_dir().join("other-credentials");
+    let worker = Worker::new(sandbox);
+    let alice = worker.alice().await?;
+    let bob = worker.bob().await?;
+
+    // Caches the nonce of alice's key in the worker's client:
+    alice
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    // Another client, such as another process, moves the nonce past the cached one:
+    let other = Worker::custom(rpc_addr, "sandbox", None, keystore);
+    let other_alice = other.account(alice.id().clone(), alice.secret_key().clone());
+    for _ in 0..2 {
+        other_alice
+            .transfer_near(bob.id(), NearBalance::from_near(1)?)
+            .await
+            .map_err(anyhow::Error::msg)?
+            .assert_success();
+    }
+
+    alice
+        .transfer_near(bob.id(), NearBalance::from_near(1)?)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_paginated() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(NFT_WASM_FILEPATH).await?;
+    contract
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    for token_id in 0..5 {
+        contract
+            .call("nft_mint")
+            .args_json(json!({
+                "token_id": token_id.to_string(),
+                "token_owner_id": contract.id(),
+                "token_metadata": { "title": "Olympus Mons" },
+            }))
+            .deposit(NearBalance::from_millinear(10)?)
+            .transact()
+            .await
+            .map_err(anyhow::Error::msg)?
+            .assert_success();
+    }
+
+    // Pages of 2 and 1 items end with a short page, a page of 5 with an empty one:
+    for (page_size, pages) in [(2, 3), (1, 6), (5, 2), (10, 1)] {
+        worker.reset_metrics();
+        let tokens: Vec<serde_json::Value> = worker
+            .view_paginated(
+                contract.id().clone(),
+                "nft_tokens".to_string(),
+                json!({}),
+                page_size,
+            )
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let token_ids: Vec<&str> = tokens
+            .iter()
+            .map(|token| token["token_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            token_ids,
+            ["0", "1", "2", "3", "4"],
+            "page size {}",
+            page_size
+        );
+        let queries = worker.metrics().method("query").unwrap().requests;
+        assert_eq!(queries, pages, "page size {}", page_size);
+    }
+
+    assert!(worker
+        .view_paginated::<serde_json::Value>(
+            contract.id().clone(),
+            "nft_tokens".to_string(),
+            json!({}),
+            0
+        )
+        .await
+        .is_err());
+    Ok(())
+}
+
+/// Transfer a freshly minted NFT to an account without a contract, so that the
+/// `nft_on_transfer` call fails, and the token is transferred back by `nft_resolve_transfer`.
+async fn failing_nft_transfer_call(
+    worker: &Worker<Sandbox>,
+) -> anyhow::Result<(Contract, Account, ExecutionResult)> {
+    let contract = worker.dev_deploy(NFT_WASM_FILEPATH).await?;
+    let alice = worker.dev_create().await?;
+
+    contract
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": contract.id() }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+    contract
+        .call("nft_mint")
+        .args_json(json!({
+            "token_id": "0",
+            "token_owner_id": contract.id(),
+            "token_metadata": { "title": "Olympus Mons" },
+        }))
+        .deposit(NearBalance::from_millinear(10)?)
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?
+        .assert_success();
+
+    let result = contract
+        .call("nft_transfer_call")
+        .args_json(json!({
+            "receiver_id": alice.id(),
+            "token_id": "0",
+            "memo": "to alice",
+            "msg": "",
+        }))
+        .deposit(NearBalance::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    Ok((contract, alice, result))
+}
+
+#[tokio::test]
+async fn test_receipt_calls() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let (contract, alice, result) = failing_nft_transfer_call(&worker).await?;
+
+    let calls = worker.receipt_calls(&result).await?;
+    let methods: Vec<&str> = calls.iter().map(|call| call.method_name.as_str()).collect();
+    assert_eq!(
+        methods,
+        [
+            "nft_transfer_call",
+            "nft_on_transfer",
+            "nft_resolve_transfer"
+        ]
+    );
+
+    assert_eq!(&calls[0].predecessor_id, contract.id());
+    assert_eq!(calls[0].deposit, NearBalance::from_yoctonear(1));
+    assert_eq!(&calls[1].predecessor_id, contract.id());
+    assert_eq!(&calls[1].receiver_id, alice.id());
+    assert_eq!(calls[1].deposit, NearBalance::from_yoctonear(0));
+    assert_eq!(&calls[2].receiver_id, contract.id());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receipt_tree() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let (contract, alice, result) = failing_nft_transfer_call(&worker).await?;
+    result.assert_success();
+
+    // The outcome tree is part of the result itself:
+    let failed = result.failed_receipt().expect("nft_on_transfer failed");
+    assert_eq!(&failed.executor_id, alice.id());
+    let root = &result.children(result.transaction_outcome())[0];
+    assert!(result.children(root).contains(&failed));
+
+    // The receipts themselves are fetched from the node:
+    let tree = worker.receipt_tree(&result).await?;
+    assert_eq!(tree.method_name(), Some("nft_transfer_call"));
+    assert_eq!(&tree.predecessor_id, contract.id());
+
+    let failed = tree.find_failure().expect("nft_on_transfer failed");
+    assert_eq!(failed.method_name(), Some("nft_on_transfer"));
+    assert_eq!(&failed.predecessor_id, contract.id());
+    assert_eq!(&failed.receiver_id, alice.id());
+
+    let methods: Vec<&str> = tree
+        .flatten()
+        .iter()
+        .filter_map(|node| node.method_name())
+        .collect();
+    assert_eq!(
+        methods,
+        [
+            "nft_transfer_call",
+            "nft_on_transfer",
+            "nft_resolve_transfer"
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logs_filters() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let (contract, alice, result) = failing_nft_transfer_call(&worker).await?;
+
+    // The transfer and its memo are logged by the contract, the failed `nft_on_transfer` of
+    // alice logs nothing:
+    let logs = result.logs();
+    assert!(logs.len() >= 2, "{:?}", logs);
+    assert_eq!(result.logs_for(contract.id()), logs);
+    assert!(result.logs_for(alice.id()).is_empty());
+
+    assert_eq!(result.logs_matching("Memo: "), ["Memo: to alice"]);
+    let transfers = result.logs_matching("Transfer 0 from");
+    assert_eq!(transfers.len(), 1, "{:?}", logs);
+    assert!(transfers[0].ends_with(alice.id().as_str()), "{:?}", logs);
+    assert!(result.logs_matching("Olympus Mons").is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_method_not_found_suggestion() -> anyhow::Result<()> {
+    let worker = Worker::new(Sandbox::new()?);
+    let contract = worker.dev_deploy(STATUS_MSG_WASM_FILEPATH).await?;
+
+    let err = contract
+        .view("get_stauts")
+        .args_json(json!({ "account_id": contract.id() }))
+        .fetch()
+        .await
+        .unwrap_err();
+    assert!(err.contains("did you mean `get_status`?"), "{}", err);
+    assert!(err.contains("set_status"), "{}", err);
+
+    let result = contract
+        .call("set_stauts")
+        .args_json(json!({ "message": "hello" }))
+        .transact()
+        .await
+        .map_err(anyhow::Error::msg)?;
+    assert!(!result.is_success());
+    let err = result.raw_value().unwrap_err().to_string();
+    assert!(err.contains("did you mean `set_status`?"), "{}", err);
+
+    // Nothing close enough to suggest, but the methods are still listed:
+    let err = contract.view("total_supply").fetch().await.unwrap_err();
+    assert!(!err.contains("did you mean"), "{}", err);
+    assert!(err.contains("Its methods are: "), "{}", err);
+    Ok(())
+}